@@ -102,6 +102,79 @@ bencher!(mona_12_nonescaping, mona_12_append_nonescaping, mona_12_hash_nonescapi
     Mona12Nonescaping, PASSWORDS_12
 );
 
+macro_rules! bench_hash_many {
+    ($generator:ty, $passwords:ident, $name:ident) => {
+        #[bench]
+        fn $name(b: &mut Bencher) {
+            let mut hashes = Vec::with_capacity(::samples::$passwords.len());
+
+            b.iter(|| {
+                <$generator>::hash_many(&::samples::$passwords, &mut hashes);
+                hashes.clear();
+            });
+        }
+    }
+}
+
+bench_hash_many!(Mona10, PASSWORDS_10, mona_10_hash_many);
+bench_hash_many!(Mona12, PASSWORDS_12, mona_12_hash_many);
+bench_hash_many!(Sc15, PASSWORDS_12, sc_15_hash_many);
+
+// Enumerates passwords that differ only in the last byte, computing the key schedule
+// from scratch for each of them or updating it incrementally.
+
+#[bench]
+fn des_key_schedule_new(b: &mut Bencher) {
+    let salt = des::decode_salt(b'a', b's');
+
+    b.iter(|| {
+        for c in b'a'..b'z' {
+            let keyword = des::secret_to_key(&[b'p', b'a', b's', b's', b'w', b'o', b'r', c]);
+            test::black_box(des::zero_cipher_58(keyword, salt));
+        }
+    });
+}
+
+#[bench]
+fn des_key_schedule_update(b: &mut Bencher) {
+    let salt = des::decode_salt(b'a', b's');
+    let mut schedule = des::KeySchedule::new(des::secret_to_key(b"password"));
+
+    b.iter(|| {
+        for c in b'a'..b'z' {
+            schedule.set_byte(7, c << 1);
+            test::black_box(des::zero_cipher_58_with_schedule(&schedule, salt));
+        }
+    });
+}
+
+// Computes 1024 ciphers with each DES backend, or does nothing if the CPU does not support it.
+macro_rules! bench_des_backend {
+    ($backend:ident, $name:ident) => {
+        #[bench]
+        fn $name(b: &mut Bencher) {
+            let keys: Vec<_> = (0..1024u64).map(|i| (i << 1 & 0xFEFE, des::decode_salt(b'a', b's'))).collect();
+            let mut ciphers = Vec::with_capacity(keys.len());
+            if !des::Backend::$backend.is_supported() {
+                return;
+            }
+
+            b.iter(|| {
+                des::zero_cipher_58_many_with_backend(des::Backend::$backend, keys.iter().cloned(), &mut ciphers);
+                ciphers.clear();
+            });
+        }
+    }
+}
+
+bench_des_backend!(Table, des_backend_table);
+bench_des_backend!(Sse2, des_backend_sse2);
+bench_des_backend!(Avx2, des_backend_avx2);
+bench_des_backend!(Avx512, des_backend_avx512);
+
+bencher!(mona_10_inline, PASSWORDS_10, Mona10::generate_inline);
+bencher!(mona_12_inline, PASSWORDS_12, Mona12::generate_inline);
+
 search_fn!(Mona, search, search_hash);
 
 macro_rules! bench_encode {
@@ -0,0 +1,140 @@
+/* Generated by cbindgen from src/lib.rs. Do not edit by hand. */
+
+#ifndef TRIPCODE_H
+#define TRIPCODE_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+// Generator for 4chan's tripcodes (`Fourchan`).
+#define TRIPCODE_FOURCHAN 0
+
+// `FourchanNonescaping` generator.
+#define TRIPCODE_FOURCHAN_NONESCAPING 1
+
+// Generator for 2channel's tripcodes (`Mona`).
+#define TRIPCODE_MONA 2
+
+// `MonaNonescaping` generator.
+#define TRIPCODE_MONA_NONESCAPING 3
+
+// Generator for 2channel's 10-character tripcodes (`Mona10`).
+#define TRIPCODE_MONA10 4
+
+// Generator for 2channel's 12-character tripcodes (`Mona12`).
+#define TRIPCODE_MONA12 5
+
+// `Mona12Nonescaping` generator.
+#define TRIPCODE_MONA12_NONESCAPING 6
+
+// Generator for 2channel's _nama key_ tripcodes (`MonaRaw`).
+//
+// Generation fails with `TRIPCODE_ERR_INVALID_PASSWORD` if the password is not a valid
+// _nama key_ password.
+#define TRIPCODE_MONA_RAW 7
+
+// Generator for _2ch.sc_'s tripcodes (`Sc`).
+#define TRIPCODE_SC 8
+
+// `ScSjis` generator, which treats passwords as Shift-JIS-encoded.
+#define TRIPCODE_SC_SJIS 9
+
+// Generator for _2ch.sc_'s 15-character tripcodes (`Sc15`).
+#define TRIPCODE_SC15 10
+
+// Generator for _2ch.sc_'s katakana tripcodes (`ScKatakana`).
+#define TRIPCODE_SC_KATAKANA 11
+
+// A required pointer argument was `NULL`.
+#define TRIPCODE_ERR_NULL -1
+
+// The `kind` argument was not one of the `TRIPCODE_*` generator constants.
+#define TRIPCODE_ERR_UNKNOWN_KIND -2
+
+// The output buffer was too small to hold the result.
+#define TRIPCODE_ERR_BUFFER_TOO_SMALL -3
+
+// The password was rejected by the generator (`TRIPCODE_MONA_RAW` only).
+#define TRIPCODE_ERR_INVALID_PASSWORD -4
+
+// The tripcode passed to `tripcode_decode()` was not valid for the generator.
+#define TRIPCODE_ERR_INVALID_TRIPCODE -5
+
+// Maximum length of a generated tripcode in bytes, excluding the terminating NUL.
+#define TRIPCODE_MAX_LEN 45
+
+// The hash value is of a 10-character tripcode.
+#define TRIPCODE_FORMAT_TEN 0
+
+// The hash value is of a 12-character tripcode.
+#define TRIPCODE_FORMAT_TWELVE 1
+
+// The hash value is of a 15-character tripcode.
+#define TRIPCODE_FORMAT_FIFTEEN 2
+
+// The hash value is of a katakana tripcode.
+#define TRIPCODE_FORMAT_KATAKANA 3
+
+// The tripcode is `"???"`, which 2channel and _2ch.sc_ yield for undefined password formats.
+#define TRIPCODE_FORMAT_ERROR 4
+
+// Hash value decoded from a tripcode.
+typedef struct tripcode_hash_value {
+  // One of the `TRIPCODE_FORMAT_*` constants.
+  uint32_t format;
+  // The hash value in big-endian byte order. Only the leading bytes are meaningful;
+  // `tripcode_decode()` returns how many.
+  uint8_t bytes[12];
+} tripcode_hash_value;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Generates a tripcode from a password.
+//
+// `kind` is one of the `TRIPCODE_*` generator constants. The tripcode is written to `out`
+// as a NUL-terminated UTF-8 string, so `out_len` must be greater than the length of
+// the tripcode; `TRIPCODE_MAX_LEN + 1` bytes are always enough.
+//
+// Returns the length of the tripcode excluding the terminating NUL, or a negative error code.
+// `out` is left untouched on failure.
+ptrdiff_t tripcode_generate(uint32_t kind,
+                            const char *password,
+                            size_t password_len,
+                            char *out,
+                            size_t out_len);
+
+// Generates a DES-based tripcode from a password and a pair of custom salt characters,
+// in the same way as `Des` generator.
+//
+// The output and the return value are the same as `tripcode_generate()`.
+ptrdiff_t tripcode_generate_des(const char *password,
+                                size_t password_len,
+                                uint8_t salt1,
+                                uint8_t salt2,
+                                char *out,
+                                size_t out_len);
+
+// Decodes a UTF-8 tripcode into the hash value it represents.
+//
+// `kind` is one of the `TRIPCODE_*` generator constants and determines which tripcode formats
+// are accepted. Use `TRIPCODE_FOURCHAN` for tripcodes generated by `tripcode_generate_des()`.
+//
+// Returns the number of meaningful bytes in `out->bytes`, or a negative error code.
+// `out` is left untouched on failure.
+ptrdiff_t tripcode_decode(uint32_t kind,
+                          const char *tripcode,
+                          size_t tripcode_len,
+                          struct tripcode_hash_value *out);
+
+// Returns a NUL-terminated string describing an error code returned by the other functions.
+//
+// The string is statically allocated and must not be freed.
+const char *tripcode_strerror(ptrdiff_t code);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* TRIPCODE_H */
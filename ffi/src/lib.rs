@@ -0,0 +1,452 @@
+// Copyright 2016 Huton. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+C bindings for the `tripcode` crate.
+
+The declarations are available to C in `include/tripcode.h`, which is generated from this file
+by [cbindgen](https://github.com/eqrion/cbindgen):
+
+```text
+cbindgen --config cbindgen.toml --output include/tripcode.h
+```
+
+Every function returns a non-negative value on success and one of the negative `TRIPCODE_ERR_*`
+codes on failure. Strings passed to the functions are byte sequences with explicit lengths and
+need not be NUL-terminated. Every non-`NULL` pointer must be valid for reads or writes of
+the length passed along with it.
+*/
+
+#![allow(clippy::missing_safety_doc)]
+
+extern crate tripcode;
+
+use std::os::raw::c_char;
+use std::{ptr, slice};
+use tripcode::*;
+use tripcode::hash::*;
+
+/// Generator for 4chan's tripcodes (`Fourchan`).
+pub const TRIPCODE_FOURCHAN: u32 = 0;
+/// `FourchanNonescaping` generator.
+pub const TRIPCODE_FOURCHAN_NONESCAPING: u32 = 1;
+/// Generator for 2channel's tripcodes (`Mona`).
+pub const TRIPCODE_MONA: u32 = 2;
+/// `MonaNonescaping` generator.
+pub const TRIPCODE_MONA_NONESCAPING: u32 = 3;
+/// Generator for 2channel's 10-character tripcodes (`Mona10`).
+pub const TRIPCODE_MONA10: u32 = 4;
+/// Generator for 2channel's 12-character tripcodes (`Mona12`).
+pub const TRIPCODE_MONA12: u32 = 5;
+/// `Mona12Nonescaping` generator.
+pub const TRIPCODE_MONA12_NONESCAPING: u32 = 6;
+/// Generator for 2channel's _nama key_ tripcodes (`MonaRaw`).
+///
+/// Generation fails with `TRIPCODE_ERR_INVALID_PASSWORD` if the password is not a valid
+/// _nama key_ password.
+pub const TRIPCODE_MONA_RAW: u32 = 7;
+/// Generator for _2ch.sc_'s tripcodes (`Sc`).
+pub const TRIPCODE_SC: u32 = 8;
+/// `ScSjis` generator, which treats passwords as Shift-JIS-encoded.
+pub const TRIPCODE_SC_SJIS: u32 = 9;
+/// Generator for _2ch.sc_'s 15-character tripcodes (`Sc15`).
+pub const TRIPCODE_SC15: u32 = 10;
+/// Generator for _2ch.sc_'s katakana tripcodes (`ScKatakana`).
+pub const TRIPCODE_SC_KATAKANA: u32 = 11;
+
+/// A required pointer argument was `NULL`.
+pub const TRIPCODE_ERR_NULL: isize = -1;
+/// The `kind` argument was not one of the `TRIPCODE_*` generator constants.
+pub const TRIPCODE_ERR_UNKNOWN_KIND: isize = -2;
+/// The output buffer was too small to hold the result.
+pub const TRIPCODE_ERR_BUFFER_TOO_SMALL: isize = -3;
+/// The password was rejected by the generator (`TRIPCODE_MONA_RAW` only).
+pub const TRIPCODE_ERR_INVALID_PASSWORD: isize = -4;
+/// The tripcode passed to `tripcode_decode()` was not valid for the generator.
+pub const TRIPCODE_ERR_INVALID_TRIPCODE: isize = -5;
+
+/// Maximum length of a generated tripcode in bytes, excluding the terminating NUL.
+pub const TRIPCODE_MAX_LEN: usize = Tripcode::CAPACITY;
+
+/// The hash value is of a 10-character tripcode.
+pub const TRIPCODE_FORMAT_TEN: u32 = 0;
+/// The hash value is of a 12-character tripcode.
+pub const TRIPCODE_FORMAT_TWELVE: u32 = 1;
+/// The hash value is of a 15-character tripcode.
+pub const TRIPCODE_FORMAT_FIFTEEN: u32 = 2;
+/// The hash value is of a katakana tripcode.
+pub const TRIPCODE_FORMAT_KATAKANA: u32 = 3;
+/// The tripcode is `"???"`, which 2channel and _2ch.sc_ yield for undefined password formats.
+pub const TRIPCODE_FORMAT_ERROR: u32 = 4;
+
+/// Hash value decoded from a tripcode.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct TripcodeHashValue {
+    /// One of the `TRIPCODE_FORMAT_*` constants.
+    pub format: u32,
+    /// The hash value in big-endian byte order. Only the leading bytes are meaningful;
+    /// `tripcode_decode()` returns how many.
+    pub bytes: [u8; 12],
+}
+
+/// Hash values that can be passed to C as a `TripcodeHashValue`.
+trait ToHashValue {
+    /// Stores `self` in `dst` and returns the number of meaningful bytes.
+    fn to_hash_value(self, dst: &mut TripcodeHashValue) -> usize;
+}
+
+impl ToHashValue for FourchanHash {
+    fn to_hash_value(self, dst: &mut TripcodeHashValue) -> usize {
+        dst.format = TRIPCODE_FORMAT_TEN;
+        dst.bytes[..8].copy_from_slice(&self.0.to_be_bytes());
+        8
+    }
+}
+
+impl ToHashValue for Mona12Hash {
+    fn to_hash_value(self, dst: &mut TripcodeHashValue) -> usize {
+        dst.format = TRIPCODE_FORMAT_TWELVE;
+        dst.bytes[..8].copy_from_slice(&self.0.to_be_bytes());
+        dst.bytes[8] = self.1;
+        9
+    }
+}
+
+impl ToHashValue for Sc15Hash {
+    fn to_hash_value(self, dst: &mut TripcodeHashValue) -> usize {
+        dst.format = TRIPCODE_FORMAT_FIFTEEN;
+        dst.bytes[..8].copy_from_slice(&self.0.to_be_bytes());
+        dst.bytes[8..].copy_from_slice(&self.1.to_be_bytes());
+        12
+    }
+}
+
+impl ToHashValue for ScKatakanaHash {
+    fn to_hash_value(self, dst: &mut TripcodeHashValue) -> usize {
+        let len = self.0.to_hash_value(dst);
+        dst.format = TRIPCODE_FORMAT_KATAKANA;
+        len
+    }
+}
+
+impl ToHashValue for MonaHash {
+    fn to_hash_value(self, dst: &mut TripcodeHashValue) -> usize {
+        match self {
+            MonaHash::Ten(h)    => h.to_hash_value(dst),
+            MonaHash::Twelve(h) => h.to_hash_value(dst),
+            MonaHash::Error     => { dst.format = TRIPCODE_FORMAT_ERROR; 0 },
+        }
+    }
+}
+
+impl ToHashValue for ScHash {
+    fn to_hash_value(self, dst: &mut TripcodeHashValue) -> usize {
+        match self {
+            ScHash::Ten(h)      => h.to_hash_value(dst),
+            ScHash::Twelve(h)   => h.to_hash_value(dst),
+            ScHash::Fifteen(h)  => h.to_hash_value(dst),
+            ScHash::Katakana(h) => h.to_hash_value(dst),
+            ScHash::Error       => { dst.format = TRIPCODE_FORMAT_ERROR; 0 },
+        }
+    }
+}
+
+/// Calls `$f` with the generator type corresponding to `$kind`, or returns
+/// `TRIPCODE_ERR_UNKNOWN_KIND` from the enclosing function.
+macro_rules! dispatch {
+    ($kind:expr, $f:ident, $f_failable:ident ($($arg:expr),*)) => {
+        match $kind {
+            TRIPCODE_FOURCHAN               => $f::<Fourchan>($($arg),*),
+            TRIPCODE_FOURCHAN_NONESCAPING   => $f::<FourchanNonescaping>($($arg),*),
+            TRIPCODE_MONA                   => $f::<Mona>($($arg),*),
+            TRIPCODE_MONA_NONESCAPING       => $f::<MonaNonescaping>($($arg),*),
+            TRIPCODE_MONA10                 => $f::<Mona10>($($arg),*),
+            TRIPCODE_MONA12                 => $f::<Mona12>($($arg),*),
+            TRIPCODE_MONA12_NONESCAPING     => $f::<Mona12Nonescaping>($($arg),*),
+            TRIPCODE_MONA_RAW               => $f_failable::<MonaRaw>($($arg),*),
+            TRIPCODE_SC                     => $f::<Sc>($($arg),*),
+            TRIPCODE_SC_SJIS                => $f::<ScSjis>($($arg),*),
+            TRIPCODE_SC15                   => $f::<Sc15>($($arg),*),
+            TRIPCODE_SC_KATAKANA            => $f::<ScKatakana>($($arg),*),
+            _                               => return TRIPCODE_ERR_UNKNOWN_KIND,
+        }
+    };
+}
+
+/// Generates a tripcode from a password.
+///
+/// `kind` is one of the `TRIPCODE_*` generator constants. The tripcode is written to `out`
+/// as a NUL-terminated UTF-8 string, so `out_len` must be greater than the length of
+/// the tripcode; `TRIPCODE_MAX_LEN + 1` bytes are always enough.
+///
+/// Returns the length of the tripcode excluding the terminating NUL, or a negative error code.
+/// `out` is left untouched on failure.
+#[no_mangle]
+pub unsafe extern "C" fn tripcode_generate(kind: u32,
+                                           password: *const c_char,
+                                           password_len: usize,
+                                           out: *mut c_char,
+                                           out_len: usize) -> isize
+{
+    let password = match bytes(password, password_len) {
+        Some(p) => p,
+        None    => return TRIPCODE_ERR_NULL,
+    };
+    if out.is_null() {
+        return TRIPCODE_ERR_NULL;
+    }
+
+    let tripcode = match dispatch!(kind, generate, try_generate(password)) {
+        Some(t) => t,
+        None    => return TRIPCODE_ERR_INVALID_PASSWORD,
+    };
+    write_str(&tripcode, out, out_len)
+}
+
+/// Generates a DES-based tripcode from a password and a pair of custom salt characters,
+/// in the same way as `Des` generator.
+///
+/// The output and the return value are the same as `tripcode_generate()`.
+#[no_mangle]
+pub unsafe extern "C" fn tripcode_generate_des(password: *const c_char,
+                                               password_len: usize,
+                                               salt1: u8,
+                                               salt2: u8,
+                                               out: *mut c_char,
+                                               out_len: usize) -> isize
+{
+    let password = match bytes(password, password_len) {
+        Some(p) => p,
+        None    => return TRIPCODE_ERR_NULL,
+    };
+    if out.is_null() {
+        return TRIPCODE_ERR_NULL;
+    }
+
+    write_str(&Des::generate_inline(password, salt1, salt2), out, out_len)
+}
+
+/// Decodes a UTF-8 tripcode into the hash value it represents.
+///
+/// `kind` is one of the `TRIPCODE_*` generator constants and determines which tripcode formats
+/// are accepted. Use `TRIPCODE_FOURCHAN` for tripcodes generated by `tripcode_generate_des()`.
+///
+/// Returns the number of meaningful bytes in `out->bytes`, or a negative error code.
+/// `out` is left untouched on failure.
+#[no_mangle]
+pub unsafe extern "C" fn tripcode_decode(kind: u32,
+                                         tripcode: *const c_char,
+                                         tripcode_len: usize,
+                                         out: *mut TripcodeHashValue) -> isize
+{
+    let tripcode = match bytes(tripcode, tripcode_len) {
+        Some(t) => t,
+        None    => return TRIPCODE_ERR_NULL,
+    };
+    let out = match out.as_mut() {
+        Some(o) => o,
+        None    => return TRIPCODE_ERR_NULL,
+    };
+
+    let tripcode = match std::str::from_utf8(tripcode) {
+        Ok(t)  => t,
+        Err(_) => return TRIPCODE_ERR_INVALID_TRIPCODE,
+    };
+
+    let mut value = TripcodeHashValue::default();
+    match dispatch!(kind, decode, try_decode(tripcode, &mut value)) {
+        Some(len) => { *out = value; len as isize },
+        None      => TRIPCODE_ERR_INVALID_TRIPCODE,
+    }
+}
+
+/// Returns a NUL-terminated string describing an error code returned by the other functions.
+///
+/// The string is statically allocated and must not be freed.
+#[no_mangle]
+pub extern "C" fn tripcode_strerror(code: isize) -> *const c_char {
+    let message: &'static [u8] = match code {
+        TRIPCODE_ERR_NULL               => b"null pointer argument\0",
+        TRIPCODE_ERR_UNKNOWN_KIND       => b"unknown tripcode kind\0",
+        TRIPCODE_ERR_BUFFER_TOO_SMALL   => b"output buffer too small\0",
+        TRIPCODE_ERR_INVALID_PASSWORD   => b"invalid password\0",
+        TRIPCODE_ERR_INVALID_TRIPCODE   => b"invalid tripcode\0",
+        c if c >= 0                     => b"success\0",
+        _                               => b"unknown error\0",
+    };
+    message.as_ptr() as *const c_char
+}
+
+fn generate<G: TripcodeGenerator>(password: &[u8]) -> Option<Tripcode> {
+    Some(G::generate_inline(password))
+}
+
+fn try_generate<G: TripcodeGeneratorFailable>(password: &[u8]) -> Option<Tripcode> {
+    G::try_generate_inline(password)
+}
+
+fn decode<G>(tripcode: &str, dst: &mut TripcodeHashValue) -> Option<usize>
+    where G: TripcodeGenerator, G::Hash: ToHashValue
+{
+    G::Hash::decode(tripcode).map(|h| h.to_hash_value(dst))
+}
+
+fn try_decode<G>(tripcode: &str, dst: &mut TripcodeHashValue) -> Option<usize>
+    where G: TripcodeGeneratorFailable, G::Hash: ToHashValue
+{
+    G::Hash::decode(tripcode).map(|h| h.to_hash_value(dst))
+}
+
+/// Borrows a byte string from C, allowing `NULL` only when the length is zero.
+unsafe fn bytes<'a>(ptr: *const c_char, len: usize) -> Option<&'a [u8]> {
+    if ptr.is_null() {
+        if len == 0 { Some(&[]) } else { None }
+    } else {
+        Some(slice::from_raw_parts(ptr as *const u8, len))
+    }
+}
+
+/// Copies `s` into `out` with a terminating NUL.
+unsafe fn write_str(s: &str, out: *mut c_char, out_len: usize) -> isize {
+    if s.len() >= out_len {
+        return TRIPCODE_ERR_BUFFER_TOO_SMALL;
+    }
+    ptr::copy_nonoverlapping(s.as_ptr(), out as *mut u8, s.len());
+    *out.add(s.len()) = 0;
+    s.len() as isize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    fn generate(kind: u32, password: &str) -> Result<String, isize> {
+        let mut buf = [0 as c_char; TRIPCODE_MAX_LEN + 1];
+        let ret = unsafe {
+            tripcode_generate(kind, password.as_ptr() as *const c_char, password.len(),
+                buf.as_mut_ptr(), buf.len())
+        };
+        if ret < 0 {
+            return Err(ret);
+        }
+        let tripcode = unsafe { CStr::from_ptr(buf.as_ptr()) }.to_str().unwrap();
+        assert_eq!(ret as usize, tripcode.len());
+        Ok(tripcode.to_owned())
+    }
+
+    fn decode(kind: u32, tripcode: &str) -> Result<(u32, Vec<u8>), isize> {
+        let mut value = TripcodeHashValue::default();
+        let ret = unsafe {
+            tripcode_decode(kind, tripcode.as_ptr() as *const c_char, tripcode.len(), &mut value)
+        };
+        if ret < 0 {
+            Err(ret)
+        } else {
+            Ok((value.format, value.bytes[..ret as usize].to_vec()))
+        }
+    }
+
+    #[test]
+    fn header_max_len() {
+        // cbindgen cannot evaluate `Tripcode::CAPACITY`, so the header spells the value out.
+        let header = include_str!("../include/tripcode.h");
+        let define = format!("#define TRIPCODE_MAX_LEN {}\n", TRIPCODE_MAX_LEN);
+        assert!(header.contains(&define), "include/tripcode.h is out of date");
+    }
+
+    #[test]
+    fn generate_all_kinds() {
+        macro_rules! test_generate {
+            ($($kind:ident, $generator:ty, $password:expr;)*) => {$(
+                assert_eq!(Ok(<$generator>::generate($password)), generate($kind, $password));
+            )*};
+        }
+
+        test_generate!(
+            TRIPCODE_FOURCHAN,              Fourchan,           "&amp";
+            TRIPCODE_FOURCHAN_NONESCAPING,  FourchanNonescaping,"&amp";
+            TRIPCODE_MONA,                  Mona,               "twelve bytes";
+            TRIPCODE_MONA_NONESCAPING,      MonaNonescaping,    "<twelve bytes>";
+            TRIPCODE_MONA10,                Mona10,             "password";
+            TRIPCODE_MONA12,                Mona12,             "<12 bytes";
+            TRIPCODE_MONA12_NONESCAPING,    Mona12Nonescaping,  "<12 bytes";
+            TRIPCODE_SC,                    Sc,                 "$｡1008343131";
+            TRIPCODE_SC_SJIS,               ScSjis,             "$0123456789ab";
+            TRIPCODE_SC15,                  Sc15,               "0123456789ab";
+            TRIPCODE_SC_KATAKANA,           ScKatakana,         "0123456789ab";
+        );
+    }
+
+    #[test]
+    fn generate_errors() {
+        assert_eq!(Ok("IP9Lda5FPc".to_owned()), generate(TRIPCODE_MONA_RAW, "#0123456789abcdef./"));
+        assert_eq!(Err(TRIPCODE_ERR_INVALID_PASSWORD), generate(TRIPCODE_MONA_RAW, "#abcdefghijklmnop"));
+        assert_eq!(Err(TRIPCODE_ERR_INVALID_PASSWORD), generate(TRIPCODE_MONA_RAW, "password"));
+        assert_eq!(Err(TRIPCODE_ERR_UNKNOWN_KIND), generate(12, "password"));
+
+        let mut buf = [1 as c_char; 10];
+        unsafe {
+            assert_eq!(TRIPCODE_ERR_BUFFER_TOO_SMALL,
+                tripcode_generate(TRIPCODE_FOURCHAN, b"a".as_ptr() as *const c_char, 1, buf.as_mut_ptr(), 10));
+            assert_eq!([1; 10], buf);
+            assert_eq!(TRIPCODE_ERR_NULL,
+                tripcode_generate(TRIPCODE_FOURCHAN, ptr::null(), 1, buf.as_mut_ptr(), 10));
+            assert_eq!(TRIPCODE_ERR_NULL,
+                tripcode_generate(TRIPCODE_FOURCHAN, b"a".as_ptr() as *const c_char, 1, ptr::null_mut(), 10));
+        }
+    }
+
+    #[test]
+    fn generate_des() {
+        let mut buf = [0 as c_char; 11];
+        let ret = unsafe {
+            tripcode_generate_des(b"password".as_ptr() as *const c_char, 8, b'a', b's',
+                buf.as_mut_ptr(), buf.len())
+        };
+        assert_eq!(10, ret);
+        assert_eq!("ozOtJW9BFA", unsafe { CStr::from_ptr(buf.as_ptr()) }.to_str().unwrap());
+    }
+
+    #[test]
+    fn decode_all_formats() {
+        fn value<H: ToHashValue>(hash: H) -> (u32, Vec<u8>) {
+            let mut value = TripcodeHashValue::default();
+            let len = hash.to_hash_value(&mut value);
+            (value.format, value.bytes[..len].to_vec())
+        }
+
+        macro_rules! test_decode {
+            ($($kind:ident, $generator:ty, $password:expr;)*) => {$(
+                let hash = <$generator>::hash($password);
+                assert_eq!(Ok(value(hash)), decode($kind, &hash.encode()));
+            )*};
+        }
+
+        test_decode!(
+            TRIPCODE_FOURCHAN,      Fourchan,   "password";
+            TRIPCODE_MONA,          Mona,       "password";
+            TRIPCODE_MONA,          Mona,       "twelve bytes";
+            TRIPCODE_MONA,          Mona,       "$0123456789ab";
+            TRIPCODE_MONA12,        Mona12,     "<12 bytes";
+            TRIPCODE_SC,            Sc,         "$0123456789ab";
+            TRIPCODE_SC_SJIS,       ScSjis,     "$｡1008343131";
+            TRIPCODE_SC15,          Sc15,       "0123456789ab";
+            TRIPCODE_SC_KATAKANA,   ScKatakana, "0123456789ab";
+        );
+        assert_eq!(Ok((TRIPCODE_FORMAT_TEN, vec![0; 8])), decode(TRIPCODE_MONA_RAW, ".........."));
+        assert_eq!(Ok((TRIPCODE_FORMAT_ERROR, vec![])), decode(TRIPCODE_SC, "???"));
+        assert_eq!(TRIPCODE_FORMAT_KATAKANA, decode(TRIPCODE_SC, "ﾃｽﾄ!ｹﾏﾜｬｴ･ｧﾎﾖｲﾎ").unwrap().0);
+
+        assert_eq!(Err(TRIPCODE_ERR_INVALID_TRIPCODE), decode(TRIPCODE_MONA12, "/9L00Vb1PB"));
+        assert_eq!(Err(TRIPCODE_ERR_INVALID_TRIPCODE), decode(TRIPCODE_FOURCHAN, "hocho.🔪"));
+        assert_eq!(Err(TRIPCODE_ERR_UNKNOWN_KIND), decode(u32::MAX, ".........."));
+    }
+}
@@ -0,0 +1,67 @@
+/* Links against the tripcode_ffi library and checks a few tripcodes.
+ * Build and run it with `make test` in the ffi directory. */
+
+#include <stdio.h>
+#include <string.h>
+#include "tripcode.h"
+
+static int failures = 0;
+
+static void check_generate(uint32_t kind, const char *password, ptrdiff_t expected_ret,
+                           const char *expected)
+{
+    char out[TRIPCODE_MAX_LEN + 1];
+    ptrdiff_t ret = tripcode_generate(kind, password, strlen(password), out, sizeof out);
+
+    if (ret != expected_ret || (ret >= 0 && strcmp(out, expected) != 0)) {
+        fprintf(stderr, "FAIL: generate(%u, \"%s\"): %ld (%s) \"%s\"\n",
+                kind, password, (long)ret, tripcode_strerror(ret), ret >= 0 ? out : "");
+        failures++;
+    }
+}
+
+int main(void)
+{
+    char out[TRIPCODE_MAX_LEN + 1];
+    tripcode_hash_value hash;
+    ptrdiff_t ret;
+
+    check_generate(TRIPCODE_FOURCHAN, "password", 10, "ozOtJW9BFA");
+    check_generate(TRIPCODE_MONA, "twelve bytes", 12, "t+lnR7LBqNQY");
+    check_generate(TRIPCODE_MONA12, "<12 bytes", 12, "/9L00Vb1PBcb");
+    check_generate(TRIPCODE_MONA_RAW, "#0123456789ABCDEF./", 10, "IP9Lda5FPc");
+    check_generate(TRIPCODE_MONA_RAW, "#abcdefghijklmnop", TRIPCODE_ERR_INVALID_PASSWORD, NULL);
+    check_generate(TRIPCODE_MONA_RAW, "password", TRIPCODE_ERR_INVALID_PASSWORD, NULL);
+    check_generate(100, "password", TRIPCODE_ERR_UNKNOWN_KIND, NULL);
+
+    ret = tripcode_generate(TRIPCODE_FOURCHAN, "password", 8, out, 10);
+    if (ret != TRIPCODE_ERR_BUFFER_TOO_SMALL) {
+        fprintf(stderr, "FAIL: generate into a short buffer: %ld\n", (long)ret);
+        failures++;
+    }
+
+    ret = tripcode_generate_des("password", 8, 'a', 's', out, sizeof out);
+    if (ret != 10 || strcmp(out, "ozOtJW9BFA") != 0) {
+        fprintf(stderr, "FAIL: generate_des: %ld\n", (long)ret);
+        failures++;
+    }
+
+    ret = tripcode_decode(TRIPCODE_MONA, "/9L00Vb1PBcb", 12, &hash);
+    if (ret != 9 || hash.format != TRIPCODE_FORMAT_TWELVE) {
+        fprintf(stderr, "FAIL: decode: %ld\n", (long)ret);
+        failures++;
+    }
+
+    ret = tripcode_decode(TRIPCODE_MONA12, "ozOtJW9BFA", 10, &hash);
+    if (ret != TRIPCODE_ERR_INVALID_TRIPCODE) {
+        fprintf(stderr, "FAIL: decode of an invalid tripcode: %ld\n", (long)ret);
+        failures++;
+    }
+
+    if (failures > 0) {
+        fprintf(stderr, "%d failure(s)\n", failures);
+        return 1;
+    }
+    puts("ok");
+    return 0;
+}
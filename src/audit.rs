@@ -0,0 +1,419 @@
+// Copyright 2016 Huton. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+Dictionary attacks on a tripcode, which tell whether its password is weak.
+
+An `Audit` tries each word of a wordlist, along with the variants of the word that its
+`Rules` make, and reports the first candidate whose tripcode is the target. The candidates are
+hashed in batches through `TripcodeGenerator::hash_many()`, and the words are consumed one at
+a time, so wordlists of any size can be streamed from an iterator or, with the `std` feature,
+from any `BufRead`.
+
+# Examples
+
+```
+use tripcode::*;
+use tripcode::audit::{Audit, Rules};
+
+let tripcode = Fourchan::generate("P4ssw0rd");
+let audit = Audit::<Fourchan>::from_tripcode(&tripcode).unwrap()
+    .rules(Rules { toggle_case: true, leet: true, append_digits: 2 });
+
+let report = audit.run(&["letmein", "password", "qwerty"]);
+let found = report.found.unwrap();
+assert_eq!(b"P4ssw0rd", &found.password[..]);
+assert_eq!(1, found.word);
+assert_eq!(2, report.words);
+```
+*/
+
+use alloc::vec::Vec;
+use core::convert::Infallible;
+use core::ops::ControlFlow;
+#[cfg(feature = "std")]
+use std::io::{self, BufRead};
+use hash::TripcodeHash;
+use TripcodeGenerator;
+
+/// Number of candidates that are hashed at once.
+const BATCH_LEN: usize = 1024;
+
+/// Pairs of a letter and the digit that leetspeak substitutes for it.
+const LEET: [(u8, u8); 6] = [
+    (b'a', b'4'), (b'e', b'3'), (b'i', b'1'), (b'o', b'0'), (b's', b'5'), (b't', b'7'),
+];
+
+/// Largest number of digits that `Rules::append_digits` appends.
+pub const MAX_APPENDED_DIGITS: u8 = 19;
+
+/// Rules that mangle each word of a wordlist into the candidates to try.
+///
+/// The rules apply in the order of the fields, each to every variant that the ones before it
+/// made. The word itself is always a candidate.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Rules {
+    /// Also tries the word in lowercase, in uppercase, capitalized, with the case of every
+    /// letter toggled and with the case of each single letter toggled.
+    pub toggle_case: bool,
+    /// Also tries the word with every combination of the leetspeak substitutions
+    /// `a` → `4`, `e` → `3`, `i` → `1`, `o` → `0`, `s` → `5` and `t` → `7`, each of which
+    /// replaces every occurrence of the letter in either case.
+    pub leet: bool,
+    /// Also tries the word followed by every number of up to this many digits, including ones
+    /// with leading zeros, e.g. `0` to `9` and `00` to `99` for 2. Values greater than
+    /// `MAX_APPENDED_DIGITS` are treated as `MAX_APPENDED_DIGITS`.
+    pub append_digits: u8,
+}
+
+impl Rules {
+    /// Returns rules that only try the words themselves.
+    pub fn none() -> Rules {
+        Rules::default()
+    }
+
+    /// Calls `f` with each candidate that the rules make from `word`, until `f` returns
+    /// `ControlFlow::Break`, which is then returned.
+    ///
+    /// Each candidate is made once, even if several rules lead to it.
+    pub fn apply<F>(&self, word: &[u8], mut f: F) -> ControlFlow<()>
+        where F: FnMut(&[u8]) -> ControlFlow<()>
+    {
+        let mut variants = Vec::new();
+        variants.push(word.to_vec());
+
+        if self.toggle_case {
+            variants.push(word.to_ascii_lowercase());
+            variants.push(word.to_ascii_uppercase());
+            let mut capitalized = word.to_ascii_lowercase();
+            if let Some(c) = capitalized.first_mut() {
+                c.make_ascii_uppercase();
+            }
+            variants.push(capitalized);
+            variants.push(word.iter().map(|&c| toggle_case(c)).collect());
+            for (i, &c) in word.iter().enumerate() {
+                if c.is_ascii_alphabetic() {
+                    let mut v = word.to_vec();
+                    v[i] = toggle_case(c);
+                    variants.push(v);
+                }
+            }
+        }
+
+        if self.leet {
+            for i in 0..variants.len() {
+                let present = LEET.iter().enumerate()
+                    .filter(|&(_, &(l, _))| variants[i].iter().any(|c| c.to_ascii_lowercase() == l))
+                    .fold(0u32, |m, (j, _)| m | 1 << j);
+                // Every nonempty subset of the substitutions whose letters are in the word.
+                let mut mask = present;
+                while mask != 0 {
+                    let v = variants[i].iter().map(|&c| {
+                        LEET.iter().enumerate()
+                            .find(|&(j, &(l, _))| mask >> j & 1 != 0 && c.to_ascii_lowercase() == l)
+                            .map_or(c, |(_, &(_, d))| d)
+                    }).collect();
+                    variants.push(v);
+                    mask = (mask - 1) & present;
+                }
+            }
+        }
+
+        // The first variant is the word itself, so it stays first.
+        variants[1..].sort_unstable();
+        variants.dedup();
+        let rest = variants[1..].iter().filter(|v| **v != word).collect::<Vec<_>>();
+
+        let digits = self.append_digits.min(MAX_APPENDED_DIGITS) as u32;
+        let mut candidate = Vec::new();
+        for v in Some(&variants[0]).into_iter().chain(rest) {
+            f(v)?;
+            for n in 1..=digits {
+                candidate.clear();
+                candidate.extend_from_slice(v);
+                candidate.resize(v.len() + n as usize, b'0');
+                for _ in 0..10u64.pow(n) {
+                    f(&candidate)?;
+                    increment(&mut candidate);
+                }
+            }
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+/// Toggles the case of an ASCII letter.
+fn toggle_case(c: u8) -> u8 {
+    if c.is_ascii_alphabetic() { c ^ 0x20 } else { c }
+}
+
+/// Increments the decimal number at the end of `s`, wrapping around to zeros.
+fn increment(s: &mut [u8]) {
+    for c in s.iter_mut().rev() {
+        if *c == b'9' {
+            *c = b'0';
+        } else {
+            *c += 1;
+            return;
+        }
+    }
+}
+
+/// Candidate that reproduces the target tripcode.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Found {
+    /// The password.
+    pub password: Vec<u8>,
+    /// Index in the wordlist, counted from 0, of the word that the password was made from.
+    pub word: u64,
+}
+
+/// Result of an audit.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Report {
+    /// The first candidate that reproduces the target, if any.
+    pub found: Option<Found>,
+    /// Number of words that were read from the wordlist.
+    pub words: u64,
+    /// Number of candidates that were tried, up to the one that was found.
+    pub candidates: u64,
+}
+
+impl Report {
+    /// Returns whether the password of the target was found, i.e. it is weak.
+    pub fn is_weak(&self) -> bool {
+        self.found.is_some()
+    }
+}
+
+/// Dictionary attack on a tripcode of generator `G`.
+pub struct Audit<G: TripcodeGenerator> {
+    target: G::Hash,
+    rules: Rules,
+}
+
+impl<G: TripcodeGenerator> Audit<G> where G::Hash: PartialEq {
+    /// Creates an audit of the tripcode that `target` represents, which only tries the words
+    /// themselves.
+    pub fn new(target: G::Hash) -> Self {
+        Audit {
+            target,
+            rules: Rules::none(),
+        }
+    }
+
+    /// Creates an audit of `tripcode`, or returns `None` if it is not a valid tripcode of `G`.
+    pub fn from_tripcode(tripcode: &str) -> Option<Self> {
+        G::Hash::decode(tripcode).map(Self::new)
+    }
+
+    /// Sets the rules that mangle the words.
+    pub fn rules(mut self, rules: Rules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Tries the words of `wordlist` until a candidate reproduces the target.
+    pub fn run<I>(&self, wordlist: I) -> Report
+        where I: IntoIterator, I::Item: AsRef<[u8]>
+    {
+        match self.try_run(wordlist.into_iter().map(Ok::<_, Infallible>)) {
+            Ok(report) => report,
+            Err(e) => match e {},
+        }
+    }
+
+    /// Tries the lines of `reader` as words until a candidate reproduces the target.
+    ///
+    /// Lines are separated by `\n`, and a `\r` that precedes it is removed.
+    ///
+    /// This method is only available with the `std` feature.
+    #[cfg(feature = "std")]
+    pub fn run_reader<R: BufRead>(&self, reader: R) -> io::Result<Report> {
+        self.try_run(reader.split(b'\n').map(|line| line.map(|mut line| {
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+            line
+        })))
+    }
+
+    /// Tries the words of `wordlist` until a candidate reproduces the target, stopping at the
+    /// first error that it yields.
+    pub fn try_run<I, W, E>(&self, wordlist: I) -> Result<Report, E>
+        where I: IntoIterator<Item=Result<W, E>>, W: AsRef<[u8]>
+    {
+        let mut batch = Batch {
+            passwords: Vec::with_capacity(BATCH_LEN),
+            words: Vec::with_capacity(BATCH_LEN),
+            hashes: Vec::with_capacity(BATCH_LEN),
+        };
+        let mut report = Report {
+            found: None,
+            words: 0,
+            candidates: 0,
+        };
+
+        for word in wordlist {
+            let word = word?;
+            let n = report.words;
+            report.words += 1;
+
+            let flow = self.rules.apply(word.as_ref(), |candidate| {
+                batch.passwords.push(candidate.to_vec());
+                batch.words.push(n);
+                if batch.passwords.len() == BATCH_LEN {
+                    batch.flush::<G>(&self.target, &mut report);
+                    if report.found.is_some() {
+                        return ControlFlow::Break(());
+                    }
+                }
+                ControlFlow::Continue(())
+            });
+            if flow.is_break() {
+                return Ok(report);
+            }
+        }
+
+        batch.flush::<G>(&self.target, &mut report);
+        Ok(report)
+    }
+}
+
+/// Candidates waiting to be hashed, with the indices of the words they were made from.
+struct Batch<H> {
+    passwords: Vec<Vec<u8>>,
+    words: Vec<u64>,
+    hashes: Vec<H>,
+}
+
+impl<H: PartialEq> Batch<H> {
+    /// Hashes the candidates and records them in `report`, up to the first one that
+    /// reproduces `target`.
+    fn flush<G: TripcodeGenerator<Hash=H>>(&mut self, target: &H, report: &mut Report) {
+        self.hashes.clear();
+        G::hash_many(&self.passwords, &mut self.hashes);
+
+        match self.hashes.iter().position(|h| h == target) {
+            Some(i) => {
+                report.candidates += i as u64 + 1;
+                report.found = Some(Found {
+                    password: self.passwords.swap_remove(i),
+                    word: self.words[i],
+                });
+            },
+            None => report.candidates += self.passwords.len() as u64,
+        }
+        self.passwords.clear();
+        self.words.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use *;
+
+    fn candidates(rules: Rules, word: &str) -> Vec<Vec<u8>> {
+        let mut candidates = Vec::new();
+        let flow = rules.apply(word.as_bytes(), |c| {
+            candidates.push(c.to_vec());
+            ControlFlow::Continue(())
+        });
+        assert_eq!(ControlFlow::Continue(()), flow);
+        candidates
+    }
+
+    #[test]
+    fn rules() {
+        assert_eq!([b"aB1".to_vec()], &candidates(Rules::none(), "aB1")[..]);
+
+        let mut c = candidates(Rules { toggle_case: true, ..Rules::none() }, "aB1");
+        assert_eq!(b"aB1", &c[0][..]);
+        c.sort();
+        assert_eq!([&b"AB1"[..], b"Ab1", b"aB1", b"ab1"], &c.iter().map(|c| &c[..]).collect::<Vec<_>>()[..]);
+
+        let c = candidates(Rules { leet: true, ..Rules::none() }, "Toast");
+        assert_eq!(16, c.len());
+        assert!(c.iter().any(|c| c == b"70457"));
+        assert!(c.iter().any(|c| c == b"To4st"));
+
+        let c = candidates(Rules { append_digits: 2, ..Rules::none() }, "x");
+        assert_eq!(111, c.len());
+        assert_eq!(b"x0", &c[1][..]);
+        assert_eq!(b"x99", &c[110][..]);
+
+        // Every candidate is made once.
+        let rules = Rules { toggle_case: true, leet: true, append_digits: 1 };
+        let mut c = candidates(rules, "seattle");
+        let len = c.len();
+        c.sort();
+        c.dedup();
+        assert_eq!(len, c.len());
+        assert!(c.iter().any(|c| c == b"S3477l35"));
+    }
+
+    #[test]
+    fn stop() {
+        // Going through every number of up to 19 digits would never end.
+        let rules = Rules { append_digits: MAX_APPENDED_DIGITS, ..Rules::none() };
+        let mut tried = 0;
+        let flow = rules.apply(b"x", |c| {
+            tried += 1;
+            if c == b"x42" { ControlFlow::Break(()) } else { ControlFlow::Continue(()) }
+        });
+        assert_eq!(ControlFlow::Break(()), flow);
+        assert_eq!(1 + 10 + 43, tried);
+
+        let audit = Audit::<Fourchan>::new(Fourchan::hash("dragon12")).rules(rules);
+        let report = audit.run(&["dragon"]);
+        assert_eq!(Some(Found { password: b"dragon12".to_vec(), word: 0 }), report.found);
+        assert_eq!(1 + 10 + 13, report.candidates);
+    }
+
+    #[test]
+    fn run() {
+        let rules = Rules { toggle_case: true, leet: true, append_digits: 2 };
+        let words = ["dragon", "monkey", "sunshine", "master"];
+
+        let audit = Audit::<Fourchan>::from_tripcode(&Fourchan::generate("M0nk3y42")).unwrap().rules(rules);
+        let report = audit.run(&words);
+        assert!(report.is_weak());
+        assert_eq!(Some(Found { password: b"M0nk3y42".to_vec(), word: 1 }), report.found);
+        assert_eq!(2, report.words);
+        let tried = candidates(rules, "dragon").len()
+            + candidates(rules, "monkey").iter().position(|c| c == b"M0nk3y42").unwrap() + 1;
+        assert_eq!(tried as u64, report.candidates);
+
+        let audit = Audit::<Mona12>::new(Mona12::hash("Sunsh1n342")).rules(rules);
+        assert_eq!(Some(Found { password: b"Sunsh1n342".to_vec(), word: 2 }), audit.run(&words).found);
+
+        let audit = Audit::<Fourchan>::new(Fourchan::hash("correct horse")).rules(rules);
+        let report = audit.run(&words);
+        assert!(!report.is_weak());
+        assert_eq!(4, report.words);
+        let tried = words.iter().map(|w| candidates(rules, w).len()).sum::<usize>();
+        assert_eq!(tried as u64, report.candidates);
+
+        assert!(Audit::<Fourchan>::from_tripcode("!").is_none());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn run_reader() {
+        let audit = Audit::<Fourchan>::from_tripcode(&Fourchan::generate("qwerty")).unwrap();
+        let report = audit.run_reader(&b"123456\r\nqwerty\r\nabc123"[..]).unwrap();
+        assert_eq!(Some(Found { password: b"qwerty".to_vec(), word: 1 }), report.found);
+        assert_eq!(2, report.candidates);
+
+        let report = audit.run_reader(&b"123456\nabc123\n"[..]).unwrap();
+        assert_eq!(None, report.found);
+        assert_eq!(2, report.words);
+    }
+}
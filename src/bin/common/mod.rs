@@ -0,0 +1,94 @@
+// Copyright 2016 Huton. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Code shared by the `tripcode` and `tripcode-server` binaries, which include this file with
+//! `#[path]`.
+
+/// Names of the tripcode types, as in the `--type` option of the `tripcode` command.
+pub const TYPES: &[&str] = &[
+    "4chan", "4chan-nonescaping",
+    "2ch", "2ch-nonescaping", "2ch10", "2ch10-nonescaping", "2ch12", "2ch12-nonescaping", "2ch-raw",
+    "sc", "sc-utf8", "sc15", "sc-katakana",
+    "des",
+];
+
+/// Returns the canonical name of a tripcode type, expanding abbreviations such as `4`, or `None`
+/// if there is no such type.
+pub fn type_name(code_type: &str) -> Option<&'static str> {
+    match code_type {
+        "4" => Some("4chan"),
+        "2" => Some("2ch"),
+        "s" => Some("sc"),
+        t   => TYPES.iter().find(|&&n| n == t).cloned(),
+    }
+}
+
+/// Calls `$f::<G, ...>($args...)` with the generator `G` selected by the name of a tripcode
+/// type, or evaluates `$other` if the name does not denote a `TripcodeGenerator`.
+macro_rules! with_generator {
+    ($t:expr, $f:ident::<_ $(, $p:tt)*>($($arg:expr),*), $other:expr) => {
+        match $t {
+            "4chan" | "4"           => $f::<Fourchan $(, $p)*>($($arg),*),
+            "4chan-nonescaping"     => $f::<FourchanNonescaping $(, $p)*>($($arg),*),
+            "2ch"   | "2"           => $f::<Mona $(, $p)*>($($arg),*),
+            "2ch-nonescaping"       => $f::<MonaNonescaping $(, $p)*>($($arg),*),
+            "2ch10"                 => $f::<Mona10 $(, $p)*>($($arg),*),
+            "2ch10-nonescaping"     => $f::<Mona10Nonescaping $(, $p)*>($($arg),*),
+            "2ch12"                 => $f::<Mona12 $(, $p)*>($($arg),*),
+            "2ch12-nonescaping"     => $f::<Mona12Nonescaping $(, $p)*>($($arg),*),
+            "sc"    | "s"           => $f::<ScSjis $(, $p)*>($($arg),*),
+            "sc-utf8"               => $f::<Sc $(, $p)*>($($arg),*),
+            "sc15"                  => $f::<Sc15 $(, $p)*>($($arg),*),
+            "sc-katakana"           => $f::<ScKatakana $(, $p)*>($($arg),*),
+            _                       => $other,
+        }
+    };
+}
+
+/// Decodes a hexadecimal string, e.g. `70617373` into `pass`.
+///
+/// Returns `None` if `hex` has an odd length or a non-hexadecimal character.
+pub fn decode_hex(hex: &[u8]) -> Option<Vec<u8>> {
+    fn digit(c: u8) -> Option<u8> {
+        match c {
+            b'0'..=b'9' => Some(c - b'0'),
+            b'a'..=b'f' => Some(c - b'a' + 10),
+            b'A'..=b'F' => Some(c - b'A' + 10),
+            _ => None,
+        }
+    }
+
+    if hex.len() & 1 != 0 {
+        return None;
+    }
+    hex.chunks(2).map(|c| Some(digit(c[0])? << 4 | digit(c[1])?)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn type_names() {
+        for t in TYPES {
+            assert_eq!(Some(*t), type_name(t));
+        }
+        assert_eq!(Some("4chan"), type_name("4"));
+        assert_eq!(Some("sc"), type_name("s"));
+        assert_eq!(None, type_name("md5"));
+    }
+
+    #[test]
+    fn hex() {
+        assert_eq!(Some(b"pass\0\xFF".to_vec()), decode_hex(b"7061737300fF"));
+        assert_eq!(Some(Vec::new()), decode_hex(b""));
+        assert_eq!(None, decode_hex(b"706"));
+        assert_eq!(None, decode_hex(b"7g"));
+    }
+}
@@ -0,0 +1,195 @@
+// Copyright 2016 Huton. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Handlers of the endpoints of the API.
+
+use tripcode::*;
+use common::{self, TYPES};
+use json::Value;
+
+/// Function that generates a tripcode from a password, or `None` if the password is invalid.
+type Generate = Box<dyn Fn(&[u8]) -> Option<String>>;
+
+/// Handles a request and returns the status code and the body of the response.
+pub fn handle(method: &str, path: &str, body: &[u8]) -> (u16, Value) {
+    let result = match (method, path) {
+        ("GET", "/types") => Ok(types()),
+        ("POST", "/generate") => Value::parse(body).and_then(|r| generate(&r)),
+        ("POST", "/verify") => Value::parse(body).and_then(|r| verify(&r)),
+        (_, "/types") | (_, "/generate") | (_, "/verify") => {
+            return (405, error(format!("method `{}` is not allowed for `{}`", method, path)));
+        },
+        _ => return (404, error(format!("no such endpoint `{}`", path))),
+    };
+
+    match result {
+        Ok(v) => (200, v),
+        Err(message) => (400, error(message)),
+    }
+}
+
+pub fn error(message: String) -> Value {
+    Value::Object(vec![("error".to_owned(), Value::from(message))])
+}
+
+/// `GET /types`: lists the tripcode types.
+fn types() -> Value {
+    Value::Object(vec![("types".to_owned(), Value::from(TYPES.to_vec()))])
+}
+
+/// `POST /generate`: generates tripcodes from `password` or `passwords`.
+///
+/// Responds with `tripcode`, or `tripcodes` for `passwords`, which is `null` for invalid
+/// passwords of `2ch-raw`.
+fn generate(request: &Value) -> Result<Value, String> {
+    let (type_name, generate) = generator(request)?;
+    let (passwords, batch) = passwords(request)?;
+    let tripcodes: Vec<_> = passwords.iter().map(|p| generate(p)).collect();
+
+    let tripcodes = if batch {
+        ("tripcodes".to_owned(), Value::from(tripcodes))
+    } else {
+        ("tripcode".to_owned(), Value::from(tripcodes.into_iter().next().unwrap()))
+    };
+    Ok(Value::Object(vec![("type".to_owned(), Value::from(type_name)), tripcodes]))
+}
+
+/// `POST /verify`: checks whether `password` yields `tripcode`, or each of `passwords` yields
+/// the corresponding element of `tripcodes`.
+///
+/// Responds with `valid`, which is an array for `passwords`.
+fn verify(request: &Value) -> Result<Value, String> {
+    let (_, generate) = generator(request)?;
+    let (passwords, batch) = passwords(request)?;
+
+    let tripcodes: Vec<&str> = match (batch, request.get("tripcode"), request.get("tripcodes")) {
+        (false, Some(t), None) => vec![t.as_str().ok_or("`tripcode` must be a string")?],
+        (true, None, Some(t)) => t.as_array()
+            .and_then(|a| a.iter().map(Value::as_str).collect())
+            .ok_or("`tripcodes` must be an array of strings")?,
+        (false, _, _) => return Err("`password` requires `tripcode`".to_owned()),
+        (true, _, _) => return Err("`passwords` requires `tripcodes`".to_owned()),
+    };
+    if tripcodes.len() != passwords.len() {
+        return Err("`passwords` and `tripcodes` must have the same length".to_owned());
+    }
+
+    let valid: Vec<_> = passwords.iter().zip(tripcodes)
+        .map(|(p, t)| generate(p).is_some_and(|g| g == t))
+        .collect();
+    let valid = if batch { Value::from(valid) } else { Value::from(valid[0]) };
+    Ok(Value::Object(vec![("valid".to_owned(), valid)]))
+}
+
+/// Looks up the generator for the `type` of a request, which defaults to `4chan`.
+/// `des` requires the 2 salt characters in `salt`.
+fn generator(request: &Value) -> Result<(&'static str, Generate), String> {
+    fn boxed<G: TripcodeGenerator + 'static>() -> Generate {
+        Box::new(|p| Some(G::generate(p)))
+    }
+
+    let code_type = match request.get("type") {
+        Some(t) => t.as_str().ok_or("`type` must be a string")?,
+        None => "4chan",
+    };
+    let salt = match request.get("salt") {
+        Some(s) => Some(s.as_str().ok_or("`salt` must be a string")?.as_bytes()),
+        None => None,
+    };
+    let type_name = common::type_name(code_type)
+        .ok_or_else(|| format!("unknown tripcode type `{}`", code_type))?;
+    let generate: Generate = match (type_name, salt) {
+        ("des", Some(&[salt1, salt2])) => Box::new(move |p| Some(Des::generate(p, salt1, salt2))),
+        ("des", Some(_)) => return Err("`salt` must be 2 bytes long".to_owned()),
+        ("des", None) => return Err("`des` requires `salt`".to_owned()),
+        (_, Some(_)) => return Err("`salt` is only valid with `des`".to_owned()),
+        ("2ch-raw", None) => Box::new(|p| MonaRaw::try_generate(p)),
+        (t, None) => with_generator!(t, boxed::<_>(),
+            unreachable!("type `{}` is in `TYPES` but has no generator", t)),
+    };
+    Ok((type_name, generate))
+}
+
+/// Reads `password` or `passwords`, or their hexadecimal forms `password_hex` or
+/// `passwords_hex`, from a request. Returns the passwords and whether it is a batch request.
+fn passwords(request: &Value) -> Result<(Vec<Vec<u8>>, bool), String> {
+    let fields = ["password", "password_hex", "passwords", "passwords_hex"];
+    let mut given = fields.iter().filter_map(|&f| request.get(f).map(|v| (f, v)));
+
+    let (field, value) = match (given.next(), given.next()) {
+        (Some(f), None) => f,
+        (None, _) => return Err("either `password` or `passwords` is required".to_owned()),
+        (Some(_), Some(_)) => return Err("only one of `password` and `passwords` can be given".to_owned()),
+    };
+    let hex = field.ends_with("_hex");
+    let decode = |v: &Value| {
+        let s = v.as_str()?;
+        if hex { common::decode_hex(s.as_bytes()) } else { Some(s.as_bytes().to_vec()) }
+    };
+
+    if field.starts_with("passwords") {
+        let passwords = value.as_array().and_then(|a| a.iter().map(decode).collect());
+        passwords.map(|p| (p, true)).ok_or_else(|| format!("`{}` must be an array of {}", field,
+            if hex { "hexadecimal strings" } else { "strings" }))
+    } else {
+        decode(value).map(|p| (vec![p], false)).ok_or_else(|| format!("`{}` must be a {}", field,
+            if hex { "hexadecimal string" } else { "string" }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn post(path: &str, body: &str) -> (u16, String) {
+        let (status, v) = handle("POST", path, body.as_bytes());
+        (status, v.to_string())
+    }
+
+    #[test]
+    fn generate() {
+        assert_eq!((200, r#"{"type":"4chan","tripcode":"ozOtJW9BFA"}"#.to_owned()),
+            post("/generate", r#"{"password":"password"}"#));
+        assert_eq!((200, r#"{"type":"2ch-raw","tripcodes":["IP9Lda5FPc",null]}"#.to_owned()),
+            post("/generate", r##"{"type":"2ch-raw","passwords":["#0123456789abcdef./","a"]}"##));
+        assert_eq!((200, r#"{"type":"des","tripcodes":["ozOtJW9BFA"]}"#.to_owned()),
+            post("/generate", r#"{"type":"des","salt":"as","passwords_hex":["70617373776f7264"]}"#));
+        assert_eq!((200, r#"{"type":"sc","tripcode":"ﾃｽﾄ!ｹﾏﾜｬｴ･ｧﾎﾖｲﾎ"}"#.to_owned()),
+            post("/generate", r#"{"type":"s","password_hex":"24a131303038333433313331"}"#));
+
+        for &body in &[
+            "", "[]", r#"{}"#, r#"{"password":1}"#, r#"{"password_hex":"zz"}"#,
+            r#"{"password":"a","passwords":["b"]}"#, r#"{"type":"x","password":"a"}"#,
+            r#"{"type":"des","password":"a"}"#, r#"{"salt":"as","password":"a"}"#,
+        ] {
+            assert_eq!(400, post("/generate", body).0, "{}", body);
+        }
+    }
+
+    #[test]
+    fn verify() {
+        assert_eq!((200, r#"{"valid":true}"#.to_owned()),
+            post("/verify", r#"{"type":"2ch","password":"password","tripcode":"ozOtJW9BFA"}"#));
+        assert_eq!((200, r#"{"valid":[false,true,false]}"#.to_owned()),
+            post("/verify", r##"{"type":"2ch-raw","passwords":["a","#0123456789abcdef./","a"],
+                                "tripcodes":["ozOtJW9BFA","IP9Lda5FPc","???"]}"##));
+        assert_eq!(400, post("/verify", r#"{"password":"a"}"#).0);
+        assert_eq!(400, post("/verify", r#"{"passwords":["a"],"tripcodes":[]}"#).0);
+        assert_eq!(400, post("/verify", r#"{"passwords":["a"],"tripcode":"ozOtJW9BFA"}"#).0);
+    }
+
+    #[test]
+    fn routes() {
+        let (status, types) = handle("GET", "/types", b"");
+        assert_eq!(200, status);
+        assert_eq!(Some(TYPES.len()), types.get("types").and_then(Value::as_array).map(|a| a.len()));
+        assert_eq!(405, handle("GET", "/generate", b"").0);
+        assert_eq!(404, handle("GET", "/", b"").0);
+    }
+}
@@ -0,0 +1,158 @@
+// Copyright 2016 Huton. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Minimal HTTP/1.1 server side, which handles one request per connection.
+
+use std::io::{self, BufRead, Read, Write};
+
+/// Maximum length of the request line and headers.
+const MAX_HEAD_LEN: u64 = 8 * 1024;
+/// Maximum length of request bodies.
+pub const MAX_BODY_LEN: usize = 1024 * 1024;
+
+pub struct Request {
+    pub method: String,
+    /// The path of the request target without the query string.
+    pub path: String,
+    pub body: Vec<u8>,
+}
+
+/// Error in reading a request, which is answered with the status code.
+#[derive(Debug)]
+pub enum RequestError {
+    Io(io::Error),
+    Status(u16, &'static str),
+}
+
+impl From<io::Error> for RequestError {
+    fn from(e: io::Error) -> Self {
+        RequestError::Io(e)
+    }
+}
+
+/// Reads a request from `reader`.
+pub fn read_request<R: BufRead>(reader: &mut R) -> Result<Request, RequestError> {
+    let mut head = reader.take(MAX_HEAD_LEN);
+    let bad_request = RequestError::Status(400, "malformed request");
+
+    let mut line = String::new();
+    // Clients may send empty lines before the request line.
+    while line.trim_end().is_empty() {
+        line.clear();
+        if head.read_line(&mut line)? == 0 {
+            return Err(bad_request);
+        }
+    }
+
+    let mut parts = line.split_whitespace();
+    let (method, target) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(m), Some(t), Some(v)) if v.starts_with("HTTP/1.") => (m.to_owned(), t),
+        _ => return Err(bad_request),
+    };
+    let path = target.split('?').next().unwrap_or("").to_owned();
+
+    let mut content_length = None;
+    let mut chunked = false;
+    loop {
+        let mut header = String::new();
+        if head.read_line(&mut header)? == 0 || !header.ends_with('\n') {
+            return Err(RequestError::Status(431, "request header fields too large"));
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+
+        let (name, value) = match header.find(':') {
+            Some(i) => (header[..i].trim().to_ascii_lowercase(), header[i + 1..].trim()),
+            None => return Err(bad_request),
+        };
+        match name.as_str() {
+            "content-length" => match value.parse::<usize>() {
+                Ok(len) => content_length = Some(len),
+                Err(_) => return Err(bad_request),
+            },
+            "transfer-encoding" => chunked = true,
+            _ => (),
+        }
+    }
+    let reader = head.into_inner();
+
+    if chunked {
+        return Err(RequestError::Status(501, "transfer codings are not supported"));
+    }
+    let body = match content_length {
+        Some(len) if len > MAX_BODY_LEN => {
+            return Err(RequestError::Status(413, "request body too large"));
+        },
+        Some(len) => {
+            let mut body = vec![0; len];
+            reader.read_exact(&mut body)?;
+            body
+        },
+        None if method == "POST" => return Err(RequestError::Status(411, "length required")),
+        None => Vec::new(),
+    };
+
+    Ok(Request { method, path, body })
+}
+
+/// Writes a response with a JSON body, and asks the client to close the connection.
+pub fn write_response<W: Write>(dst: &mut W, status: u16, body: &str) -> io::Result<()> {
+    write!(dst, "HTTP/1.1 {} {}\r\n\
+                 Content-Type: application/json; charset=utf-8\r\n\
+                 Content-Length: {}\r\n\
+                 Connection: close\r\n\
+                 \r\n\
+                 {}",
+        status, reason_phrase(status), body.len(), body)?;
+    dst.flush()
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        411 => "Length Required",
+        413 => "Payload Too Large",
+        431 => "Request Header Fields Too Large",
+        501 => "Not Implemented",
+        _   => "Internal Server Error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(request: &str) -> Result<Request, RequestError> {
+        read_request(&mut request.as_bytes())
+    }
+
+    #[test]
+    fn requests() {
+        let r = read("\r\nPOST /generate?x=1 HTTP/1.1\r\nHost: localhost\r\ncontent-length: 2\r\n\r\n{}").unwrap();
+        assert_eq!(("POST", "/generate", &b"{}"[..]), (&r.method[..], &r.path[..], &r.body[..]));
+        let r = read("GET /types HTTP/1.0\n\n").unwrap();
+        assert_eq!(("GET", "/types", 0), (&r.method[..], &r.path[..], r.body.len()));
+
+        let status = |request: &str| match read(request) {
+            Err(RequestError::Status(s, _)) => s,
+            _ => 0,
+        };
+        assert_eq!(400, status("GET /types\r\n\r\n"));
+        assert_eq!(400, status("GET /types HTTP/1.1\r\nHost\r\n\r\n"));
+        assert_eq!(411, status("POST /generate HTTP/1.1\r\n\r\n"));
+        assert_eq!(501, status("POST /generate HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n0\r\n\r\n"));
+        assert_eq!(413, status(&format!("POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\n", MAX_BODY_LEN + 1)));
+        assert_eq!(431, status(&format!("GET / HTTP/1.1\r\nX: {}\r\n\r\n", "a".repeat(MAX_HEAD_LEN as usize))));
+    }
+}
@@ -0,0 +1,387 @@
+// Copyright 2016 Huton. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Minimal JSON values, parser and serializer for the requests and responses of the API.
+
+use std::{char, fmt, str};
+
+/// Maximum depth of nested arrays and objects, which bounds the recursion of the parser.
+const MAX_DEPTH: usize = 32;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    /// Members of an object in the order they appear.
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// Parses a JSON text. Returns a description of the error if it is malformed.
+    pub fn parse(text: &[u8]) -> Result<Value, String> {
+        let mut parser = Parser { text, pos: 0 };
+        let value = parser.value(0)?;
+        parser.skip_whitespace();
+        if parser.pos < text.len() {
+            return Err(parser.error("trailing characters"));
+        }
+        Ok(value)
+    }
+
+    /// Returns the member `key` of an object, or `None` if it is absent or `self` is not
+    /// an object.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match *self {
+            Value::Object(ref members) => members.iter().find(|m| m.0 == key).map(|m| &m.1),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            Value::String(ref s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match *self {
+            Value::Array(ref a) => Some(a),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> From<&'a str> for Value {
+    fn from(s: &'a str) -> Self {
+        Value::String(s.to_owned())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::String(s)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Bool(b)
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(v: Option<T>) -> Self {
+        v.map_or(Value::Null, Into::into)
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(v: Vec<T>) -> Self {
+        Value::Array(v.into_iter().map(Into::into).collect())
+    }
+}
+
+/// Serializes the value compactly.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Value::Null => f.write_str("null"),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Number(n) if n.is_finite() => write!(f, "{}", n),
+            Value::Number(_) => f.write_str("null"),
+            Value::String(ref s) => write_str(f, s),
+            Value::Array(ref a) => {
+                f.write_str("[")?;
+                for (i, v) in a.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{}", v)?;
+                }
+                f.write_str("]")
+            },
+            Value::Object(ref members) => {
+                f.write_str("{")?;
+                for (i, (k, v)) in members.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write_str(f, k)?;
+                    write!(f, ":{}", v)?;
+                }
+                f.write_str("}")
+            },
+        }
+    }
+}
+
+fn write_str(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    f.write_str("\"")?;
+    for c in s.chars() {
+        match c {
+            '"'  => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if c < ' ' || c == '\u{7F}' => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    f.write_str("\"")
+}
+
+struct Parser<'a> {
+    text: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: &str) -> String {
+        format!("invalid JSON at byte {}: {}", self.pos, message)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(&b' ') | Some(&b'\t') | Some(&b'\n') | Some(&b'\r') = self.text.get(self.pos) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.get(self.pos).cloned()
+    }
+
+    fn expect(&mut self, literal: &[u8]) -> Result<(), String> {
+        if self.text[self.pos..].starts_with(literal) {
+            self.pos += literal.len();
+            Ok(())
+        } else {
+            Err(self.error("unexpected character"))
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value, String> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("too deeply nested"));
+        }
+
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'n') => self.expect(b"null").map(|()| Value::Null),
+            Some(b't') => self.expect(b"true").map(|()| Value::Bool(true)),
+            Some(b'f') => self.expect(b"false").map(|()| Value::Bool(false)),
+            Some(b'"') => self.string().map(Value::String),
+            Some(b'[') => {
+                self.pos += 1;
+                let mut values = Vec::new();
+                self.skip_whitespace();
+                if self.peek() == Some(b']') {
+                    self.pos += 1;
+                    return Ok(Value::Array(values));
+                }
+                loop {
+                    values.push(self.value(depth + 1)?);
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some(b',') => self.pos += 1,
+                        Some(b']') => {
+                            self.pos += 1;
+                            return Ok(Value::Array(values));
+                        },
+                        _ => return Err(self.error("expected `,` or `]`")),
+                    }
+                }
+            },
+            Some(b'{') => {
+                self.pos += 1;
+                let mut members = Vec::new();
+                self.skip_whitespace();
+                if self.peek() == Some(b'}') {
+                    self.pos += 1;
+                    return Ok(Value::Object(members));
+                }
+                loop {
+                    self.skip_whitespace();
+                    if self.peek() != Some(b'"') {
+                        return Err(self.error("expected a string"));
+                    }
+                    let key = self.string()?;
+                    self.skip_whitespace();
+                    self.expect(b":")?;
+                    members.push((key, self.value(depth + 1)?));
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some(b',') => self.pos += 1,
+                        Some(b'}') => {
+                            self.pos += 1;
+                            return Ok(Value::Object(members));
+                        },
+                        _ => return Err(self.error("expected `,` or `}`")),
+                    }
+                }
+            },
+            Some(b'-') | Some(b'0'..=b'9') => self.number(),
+            Some(_) => Err(self.error("unexpected character")),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn number(&mut self) -> Result<Value, String> {
+        let start = self.pos;
+        let digits = |p: &mut Parser| {
+            let start = p.pos;
+            while let Some(b'0'..=b'9') = p.peek() {
+                p.pos += 1;
+            }
+            p.pos > start
+        };
+
+        if self.peek() == Some(b'-') {
+            self.pos += 1;
+        }
+        if self.peek() == Some(b'0') {
+            self.pos += 1;
+        } else if !digits(self) {
+            return Err(self.error("expected a digit"));
+        }
+        if self.peek() == Some(b'.') {
+            self.pos += 1;
+            if !digits(self) {
+                return Err(self.error("expected a digit"));
+            }
+        }
+        if let Some(b'e') | Some(b'E') = self.peek() {
+            self.pos += 1;
+            if let Some(b'+') | Some(b'-') = self.peek() {
+                self.pos += 1;
+            }
+            if !digits(self) {
+                return Err(self.error("expected a digit"));
+            }
+        }
+
+        // The number only consists of ASCII characters.
+        let number = str::from_utf8(&self.text[start..self.pos]).unwrap();
+        number.parse().map(Value::Number).map_err(|_| self.error("invalid number"))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.pos += 1; // the opening quote
+        let mut s = Vec::new();
+        loop {
+            match self.peek() {
+                Some(b'"') => {
+                    self.pos += 1;
+                    break;
+                },
+                Some(b'\\') => {
+                    let c = self.text.get(self.pos + 1).cloned();
+                    self.pos += 2;
+                    let escaped = match c {
+                        Some(b'"')  => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/')  => '/',
+                        Some(b'b')  => '\u{8}',
+                        Some(b'f')  => '\u{C}',
+                        Some(b'n')  => '\n',
+                        Some(b'r')  => '\r',
+                        Some(b't')  => '\t',
+                        Some(b'u')  => self.unicode_escape()?,
+                        _ => return Err(self.error("invalid escape sequence")),
+                    };
+                    let mut buf = [0; 4];
+                    s.extend_from_slice(escaped.encode_utf8(&mut buf).as_bytes());
+                },
+                Some(c) if c < b' ' => return Err(self.error("control character in a string")),
+                Some(c) => {
+                    s.push(c);
+                    self.pos += 1;
+                },
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+        String::from_utf8(s).map_err(|_| self.error("invalid UTF-8 in a string"))
+    }
+
+    /// Parses the 4 hexadecimal digits after `\u`, and a following `\uXXXX` for the low
+    /// surrogate if the digits are a high surrogate.
+    fn unicode_escape(&mut self) -> Result<char, String> {
+        let high = self.hex4()?;
+        let c = if (0xD800..0xDC00).contains(&high) {
+            if !self.text[self.pos..].starts_with(b"\\u") {
+                return Err(self.error("unpaired surrogate"));
+            }
+            self.pos += 2;
+            let low = self.hex4()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(self.error("unpaired surrogate"));
+            }
+            0x10000 + ((high - 0xD800) << 10 | (low - 0xDC00))
+        } else {
+            high
+        };
+        char::from_u32(c).ok_or_else(|| self.error("unpaired surrogate"))
+    }
+
+    fn hex4(&mut self) -> Result<u32, String> {
+        let digits = self.text.get(self.pos..self.pos + 4)
+            .and_then(|d| str::from_utf8(d).ok())
+            .and_then(|d| u32::from_str_radix(d, 16).ok().filter(|_| !d.starts_with('+')));
+        match digits {
+            Some(d) => {
+                self.pos += 4;
+                Ok(d)
+            },
+            None => Err(self.error("invalid unicode escape")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Result<Value, String> {
+        Value::parse(text.as_bytes())
+    }
+
+    #[test]
+    fn parse_values() {
+        let v = parse(r#" {"a": [1, -2.5e1, true, false, null], "b": "x\"\\\/\n\u00e9\ud83d\udd11", "c": {}} "#);
+        assert_eq!(Ok(Value::Object(vec![
+            ("a".to_owned(), Value::Array(vec![
+                Value::Number(1.0), Value::Number(-25.0), Value::Bool(true), Value::Bool(false), Value::Null,
+            ])),
+            ("b".to_owned(), Value::from("x\"\\/\né🔑")),
+            ("c".to_owned(), Value::Object(vec![])),
+        ])), v);
+        assert_eq!(Some("x\"\\/\né🔑"), v.unwrap().get("b").and_then(Value::as_str));
+        assert_eq!(Ok(Value::Array(vec![])), parse("[]"));
+
+        for invalid in &["", "[1,]", "{\"a\"}", "01", "\"\\ud83d\"", "\"\\u12\"", "\"\n\"", "[] x", "tru"] {
+            assert!(parse(invalid).is_err(), "{:?}", invalid);
+        }
+        assert!(parse(&"[".repeat(MAX_DEPTH + 2)).is_err());
+    }
+
+    #[test]
+    fn serialize() {
+        let v = Value::Object(vec![
+            ("s".to_owned(), Value::from("a\"\\\n\u{1}ﾄ")),
+            ("v".to_owned(), Value::from(vec![Some(true), None])),
+            ("n".to_owned(), Value::Number(1.5)),
+        ]);
+        assert_eq!(r#"{"s":"a\"\\\n\u0001ﾄ","v":[true,null],"n":1.5}"#, v.to_string());
+        assert_eq!(Ok(v.clone()), parse(&v.to_string()));
+    }
+}
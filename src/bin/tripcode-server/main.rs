@@ -0,0 +1,194 @@
+// Copyright 2016 Huton. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! HTTP server with a JSON API for generating and verifying tripcodes.
+
+extern crate getopts;
+extern crate tripcode;
+
+use getopts::Options;
+use std::env;
+use std::io::{self, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::process;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use http::RequestError;
+
+#[macro_use]
+#[path = "../common/mod.rs"]
+mod common;
+mod api;
+mod http;
+mod json;
+
+/// Address that the server binds to by default, which only accepts local connections.
+const DEFAULT_BIND: &str = "127.0.0.1:8080";
+
+/// Number of worker threads by default, which is the number of connections handled at once.
+const DEFAULT_THREADS: usize = 16;
+
+/// Timeout of reading requests and writing responses.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+fn main() {
+    let mut args = env::args();
+    let program = args.next().unwrap();
+    let brief = format!("Usage: {} [options]", program);
+
+    let mut opts = Options::new();
+    opts.optopt( "b", "bind", &format!("address to listen on. defaults to `{}`", DEFAULT_BIND),
+                 "<addr:port>")
+        .optopt( "j", "threads", &format!("number of connections to handle at once. defaults to {}",
+                                          DEFAULT_THREADS), "<n>")
+        .optflag("h", "help", "print this help message and exit");
+
+    let fail = |message: &str| -> ! {
+        let _ = write!(io::stderr(), "{}: {}\n\n{}", program, message, opts.usage(&brief));
+        process::exit(2);
+    };
+    let matches = match opts.parse(args) {
+        Ok(m) => m,
+        Err(f) => fail(&f.to_string()),
+    };
+    if matches.opt_present("h") {
+        let _ = write!(io::stdout(), "{}", opts.usage(&brief));
+        return;
+    }
+    let threads = match matches.opt_str("j").map(|n| n.parse()) {
+        Some(Ok(n)) if n > 0 => n,
+        Some(_) => fail("invalid value for `--threads`"),
+        None => DEFAULT_THREADS,
+    };
+
+    let bind = matches.opt_str("b").unwrap_or_else(|| DEFAULT_BIND.to_owned());
+    let listener = match TcpListener::bind(&bind) {
+        Ok(l) => l,
+        Err(e) => {
+            let _ = writeln!(io::stderr(), "{}: error binding to `{}`: {}", program, bind, e);
+            process::exit(3);
+        },
+    };
+    if let Ok(addr) = listener.local_addr() {
+        let _ = writeln!(io::stderr(), "{}: listening on http://{}", program, addr);
+    }
+
+    serve(listener, threads);
+}
+
+/// Accepts connections and hands them to `threads` worker threads.
+///
+/// Connections are only accepted while a worker is free, so the others wait in the backlog of
+/// the listener.
+fn serve(listener: TcpListener, threads: usize) {
+    let (sender, receiver) = mpsc::sync_channel::<TcpStream>(0);
+    let receiver = Arc::new(Mutex::new(receiver));
+    for _ in 0..threads {
+        let receiver = receiver.clone();
+        thread::spawn(move || loop {
+            // The lock is released before handling the connection.
+            let stream = match receiver.lock().unwrap().recv() {
+                Ok(stream) => stream,
+                Err(_) => return,
+            };
+            if let Err(e) = handle_connection(stream) {
+                let _ = writeln!(io::stderr(), "error handling a connection: {}", e);
+            }
+        });
+    }
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                // This only fails if every worker has panicked.
+                if sender.send(stream).is_err() {
+                    return;
+                }
+            },
+            Err(e) => {
+                let _ = writeln!(io::stderr(), "error accepting a connection: {}", e);
+            },
+        }
+    }
+}
+
+fn handle_connection(stream: TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    let mut reader = BufReader::new(&stream);
+
+    let (status, body) = match http::read_request(&mut reader) {
+        Ok(r) => api::handle(&r.method, &r.path, &r.body),
+        Err(RequestError::Status(status, message)) => (status, api::error(message.to_owned())),
+        Err(RequestError::Io(e)) => return Err(e),
+    };
+    http::write_response(&mut &stream, status, &body.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::net::SocketAddr;
+    use std::sync::OnceLock;
+
+    /// Starts a server on an ephemeral port of the loopback interface, which is shared by
+    /// the tests.
+    fn server() -> SocketAddr {
+        static ADDR: OnceLock<SocketAddr> = OnceLock::new();
+
+        *ADDR.get_or_init(|| {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let addr = listener.local_addr().unwrap();
+            thread::spawn(move || serve(listener, DEFAULT_THREADS));
+            addr
+        })
+    }
+
+    fn request(request: &str) -> String {
+        let mut stream = TcpStream::connect(server()).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    fn post(path: &str, body: &str) -> String {
+        request(&format!("POST {} HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\n\
+                          Content-Length: {}\r\n\r\n{}", path, body.len(), body))
+    }
+
+    #[test]
+    fn loopback() {
+        let response = post("/generate", r#"{"type":"2ch","passwords":["password","<12 bytes"]}"#);
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{}", response);
+        assert!(response.contains("\r\nContent-Type: application/json; charset=utf-8\r\n"));
+        assert!(response.ends_with(r#"{"type":"2ch","tripcodes":["ozOtJW9BFA","/9L00Vb1PBcb"]}"#));
+
+        let response = post("/verify", r#"{"password":"password","tripcode":"ozOtJW9BFA"}"#);
+        assert!(response.ends_with("\r\n\r\n{\"valid\":true}"), "{}", response);
+
+        let response = request("GET /types HTTP/1.1\r\nHost: localhost\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{}", response);
+        assert!(response.contains(r#""sc-katakana""#));
+    }
+
+    #[test]
+    fn loopback_errors() {
+        let response = post("/generate", "{");
+        assert!(response.starts_with("HTTP/1.1 400 Bad Request\r\n"), "{}", response);
+        assert!(response.contains(r#"{"error":"invalid JSON"#));
+
+        let response = request("POST /generate HTTP/1.1\r\nHost: localhost\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 411 Length Required\r\n"), "{}", response);
+        let response = request("GET /nothing HTTP/1.1\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"), "{}", response);
+    }
+}
@@ -0,0 +1,195 @@
+// Copyright 2016 Huton. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The `bench` subcommand.
+
+use getopts::Options;
+use std::io::{self, Write};
+use std::str::FromStr;
+use std::time::Duration;
+use tripcode::*;
+use tripcode::{des, sha1};
+use tripcode::estimate;
+use tripcode::search::Search;
+use error::{Context, Result, WRITING_STDOUT};
+
+/// The types measured when `--type` is not given, one name for each generator.
+const TYPES: [&str; 12] = [
+    "4chan", "4chan-nonescaping", "2ch", "2ch-nonescaping", "2ch10", "2ch10-nonescaping",
+    "2ch12", "2ch12-nonescaping", "sc", "sc-utf8", "sc15", "sc-katakana",
+];
+
+/// A backend that a generator hashes batches of passwords with.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Backend {
+    Des(des::Backend),
+    Sha1(sha1::Backend),
+}
+
+impl Backend {
+    fn name(self) -> &'static str {
+        match self {
+            Backend::Des(b) => b.name(),
+            Backend::Sha1(b) => b.name(),
+        }
+    }
+
+    fn select(self) {
+        match self {
+            Backend::Des(b) => des::set_backend(b),
+            Backend::Sha1(b) => sha1::set_backend(b),
+        }
+    }
+}
+
+/// Runs the `bench` subcommand.
+pub fn main(program: &str, args: &[String]) -> Result<()> {
+    let brief = format!("Usage: {} bench [options]", program);
+
+    let mut opts = Options::new();
+    opts.optmulti("t", "type",     "type of tripcodes to measure, as with `--type` of the main \
+                                    command. may be given more than once. defaults to all the \
+                                    types but `2ch-raw` and `des`", "<type>")
+        .optopt(  "d", "duration", "seconds to measure each type and backend for. defaults to 1",
+                                   "<secs>")
+        .optopt(  "l", "length",   "length of passwords in bytes. defaults to 12 for the 12 and \
+                                    15-character tripcodes, and to 8 otherwise", "<n>")
+        .optopt(  "j", "threads",  "number of threads. defaults to the number of CPUs", "<n>")
+        .optflag( "h", "help",     "print this help message and exit");
+
+    macro_rules! fail {
+        ($($arg:tt)*) => (usage_error!(program, opts, &brief, $($arg)*));
+    }
+
+    let matches = match opts.parse(args) {
+        Ok(m) => m,
+        Err(f) => fail!("{}", f),
+    };
+
+    if matches.opt_present("h") {
+        return io::stdout().write_all(opts.usage(&brief).as_bytes()).context(WRITING_STDOUT);
+    }
+    if !matches.free.is_empty() {
+        fail!("unexpected argument `{}`", matches.free[0]);
+    }
+
+    macro_rules! parse_opt {
+        ($name:expr) => {
+            match matches.opt_str($name).map(|s| FromStr::from_str(&s)) {
+                Some(Ok(v)) => Some(v),
+                Some(Err(_)) => fail!("invalid value for `--{}`", $name),
+                None => None,
+            }
+        };
+    }
+
+    let mut types = matches.opt_strs("type");
+    if types.is_empty() {
+        types = TYPES.iter().map(|&t| t.to_owned()).collect();
+    }
+    for t in &types {
+        if backends(t).is_none() {
+            fail!("unknown or unsupported tripcode type `{}`", t);
+        }
+    }
+    let duration = match parse_opt!("duration").map(Duration::try_from_secs_f64) {
+        Some(Ok(d)) if !d.is_zero() => d,
+        Some(_) => fail!("invalid value for `--duration`"),
+        None => Duration::from_secs(1),
+    };
+    let len = match parse_opt!("length") {
+        Some(0) => fail!("invalid value for `--length`"),
+        len => len,
+    };
+    let mut search = Search::new(Vec::new());
+    if let Some(threads) = parse_opt!("threads") {
+        search = search.threads(threads);
+    }
+
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    writeln!(stdout, "{:<18} {:<8} {:>6} {:>14}", "type", "backend", "length", "tripcodes/s")
+        .context(WRITING_STDOUT)?;
+
+    for t in &types {
+        let len = len.unwrap_or_else(|| default_len(t));
+        let search = search.clone().password_len(len);
+        let backends = backends(t).unwrap();
+        // Generators that hash passwords one by one are measured once.
+        let names = if backends.is_empty() { vec![None] } else { backends.into_iter().map(Some).collect() };
+        for backend in names {
+            if let Some(b) = backend {
+                b.select();
+            }
+            let rate = with_generator!(t.as_str(), measure::<_>(search.clone(), duration),
+                unreachable!());
+            writeln!(stdout, "{:<18} {:<8} {:>6} {:>14.0}",
+                t, backend.map_or("-", Backend::name), len, rate)
+                .and_then(|()| stdout.flush())
+                .context(WRITING_STDOUT)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns how many tripcodes per second `search` tests with the generator `G`.
+fn measure<G: TripcodeGenerator + 'static>(search: Search, duration: Duration) -> f64 {
+    estimate::measure_rate::<G>(search, duration)
+}
+
+/// Returns the backends that the CPU supports for the generator of `code_type`, fastest first,
+/// or `None` if the type is unknown.
+///
+/// The list is empty for the generators that hash passwords one by one rather than in batches.
+fn backends(code_type: &str) -> Option<Vec<Backend>> {
+    match code_type {
+        "4chan" | "4" | "4chan-nonescaping" | "2ch10" | "2ch10-nonescaping" => Some(
+            des::Backend::ALL.iter().rev().cloned().filter(|b| b.is_supported()).map(Backend::Des).collect()
+        ),
+        "2ch12" | "2ch12-nonescaping" | "sc15" | "sc-katakana" => Some(
+            sha1::Backend::ALL.iter().rev().cloned().filter(|b| b.is_supported()).map(Backend::Sha1).collect()
+        ),
+        "2ch" | "2" | "2ch-nonescaping" | "sc" | "s" | "sc-utf8" => Some(Vec::new()),
+        _ => None,
+    }
+}
+
+/// Returns the default length of passwords for `code_type`.
+fn default_len(code_type: &str) -> usize {
+    match code_type {
+        "2ch12" | "2ch12-nonescaping" | "sc15" | "sc-katakana" => 12,
+        _ => 8,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn types() {
+        for t in &TYPES {
+            let backends = backends(t).unwrap();
+            assert!(backends.iter().all(|b| match *b {
+                Backend::Des(b) => b.is_supported(),
+                Backend::Sha1(b) => b.is_supported(),
+            }), "{}", t);
+            // The portable backends run on any CPU.
+            assert!(backends.is_empty() || backends.last().unwrap().name() == match backends[0] {
+                Backend::Des(_) => "table",
+                Backend::Sha1(_) => "portable",
+            }, "{}", t);
+        }
+        assert_eq!(Some(Vec::new()), backends("sc"));
+        assert_eq!(None, backends("2ch-raw"));
+        assert_eq!(12, default_len("sc15"));
+        assert_eq!(8, default_len("4"));
+    }
+}
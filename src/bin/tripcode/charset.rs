@@ -0,0 +1,129 @@
+// Copyright 2016 Huton. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Transcoding of passwords and tripcodes for `--input-encoding` and `--output-encoding`.
+
+use encoding::{DecoderTrap, EncoderTrap, Encoding, EncodingRef};
+use encoding::all::{UTF_8, WINDOWS_31J};
+use encoding::label::encoding_from_whatwg_label;
+use tripcode::hash::TripcodeHash;
+
+/// Looks up an encoding by a WHATWG label such as `shift_jis` or `euc-jp`, or by `cp932`.
+pub fn encoding_from_label(label: &str) -> Option<EncodingRef> {
+    match label.to_ascii_lowercase().as_str() {
+        "cp932" => Some(WINDOWS_31J),
+        label => encoding_from_whatwg_label(label),
+    }
+}
+
+/// Returns the encoding in which the site of the tripcode type `type_name` sends passwords,
+/// i.e. Shift-JIS (CP932) for 2channel and _2ch.sc_, and UTF-8 otherwise.
+pub fn password_encoding(type_name: &str) -> EncodingRef {
+    if type_name.starts_with("2ch") || (type_name.starts_with("sc") && type_name != "sc-utf8") {
+        WINDOWS_31J
+    } else {
+        UTF_8
+    }
+}
+
+/// Converter of passwords from the input encoding into the encoding they are hashed in.
+#[derive(Copy, Clone)]
+pub struct PasswordTranscoder {
+    input: EncodingRef,
+    password: EncodingRef,
+}
+
+impl PasswordTranscoder {
+    pub fn new(input: EncodingRef, password: EncodingRef) -> Self {
+        PasswordTranscoder { input, password }
+    }
+
+    /// Transcodes a password. Returns `None` if it is not valid in the input encoding.
+    ///
+    /// Characters that the password encoding cannot represent are replaced with HTML numeric
+    /// character references, as web browsers do when submitting a form.
+    pub fn transcode(&self, password: &[u8]) -> Option<Vec<u8>> {
+        let text = self.input.decode(password, DecoderTrap::Strict).ok()?;
+        self.password.encode(&text, EncoderTrap::NcrEscape).ok()
+    }
+
+    /// Converts a transcoded password into `output` to print it along with its tripcode.
+    pub fn to_output(self, password: &[u8], output: &OutputEncoding) -> Vec<u8> {
+        let text = self.password.decode(password, DecoderTrap::Replace).unwrap();
+        output.encode_str(&text)
+    }
+}
+
+/// Encoding of printed tripcodes.
+pub enum OutputEncoding {
+    Utf8,
+    /// Shift-JIS, which is written with the `*_sjis` methods of `TripcodeHash`.
+    Sjis,
+    Other(EncodingRef),
+}
+
+impl OutputEncoding {
+    pub fn from_label(label: &str) -> Option<Self> {
+        encoding_from_label(label).map(|e| match e.name() {
+            "utf-8"       => OutputEncoding::Utf8,
+            "windows-31j" => OutputEncoding::Sjis,
+            _             => OutputEncoding::Other(e),
+        })
+    }
+
+    /// Encodes `hash` into a tripcode in this encoding and appends it to `dst`.
+    pub fn append_tripcode<H: TripcodeHash>(&self, hash: H, dst: &mut Vec<u8>) {
+        match *self {
+            OutputEncoding::Utf8 => dst.extend_from_slice(hash.encode().as_bytes()),
+            OutputEncoding::Sjis => hash.append_sjis(dst),
+            OutputEncoding::Other(e) => {
+                e.encode_to(&hash.encode(), EncoderTrap::NcrEscape, dst).unwrap();
+            },
+        }
+    }
+
+    fn encode_str(&self, s: &str) -> Vec<u8> {
+        match *self {
+            OutputEncoding::Utf8 => s.as_bytes().to_vec(),
+            OutputEncoding::Sjis => WINDOWS_31J.encode(s, EncoderTrap::NcrEscape).unwrap(),
+            OutputEncoding::Other(e) => e.encode(s, EncoderTrap::NcrEscape).unwrap(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding::all::EUC_JP;
+    use tripcode::hash::*;
+
+    #[test]
+    fn transcode() {
+        let t = PasswordTranscoder::new(EUC_JP, WINDOWS_31J);
+        assert_eq!(Some(b"\x83g\x83\x8a\x83b\x83v".to_vec()), t.transcode(b"\xA5\xC8\xA5\xEA\xA5\xC3\xA5\xD7"));
+        assert_eq!(None, t.transcode(b"\xA5"));
+
+        let t = PasswordTranscoder::new(UTF_8, WINDOWS_31J);
+        assert_eq!(Some(b"a&#128273;".to_vec()), t.transcode("a🔑".as_bytes()));
+        assert_eq!("トリップ".as_bytes(), &t.to_output(&t.transcode("トリップ".as_bytes()).unwrap()[..],
+            &OutputEncoding::Utf8)[..]);
+    }
+
+    #[test]
+    fn output() {
+        let h = ScKatakanaHash(Sc15Hash(0x0123456789ABCDE0, 0xFEDCBA98));
+        for label in &["utf-8", "cp932", "shift_jis", "euc-jp"] {
+            let mut tripcode = Vec::new();
+            OutputEncoding::from_label(label).unwrap().append_tripcode(h, &mut tripcode);
+            let expected = encoding_from_label(label).unwrap().encode(&h.encode(), EncoderTrap::Strict);
+            assert_eq!(expected.unwrap(), tripcode);
+        }
+        assert!(OutputEncoding::from_label("no such encoding").is_none());
+    }
+}
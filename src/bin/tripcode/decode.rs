@@ -0,0 +1,158 @@
+// Copyright 2016 Huton. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The `decode` subcommand.
+
+use getopts::Options;
+use std::io::{self, BufReader, Write};
+use std::str;
+use tripcode::*;
+use tripcode::hash::*;
+use describe::Describe;
+use error::{Context, Error, Result, WRITING_STDOUT};
+use input::Records;
+use TYPE_DESC;
+
+/// Tripcodes paired with where they come from, e.g. `("line", 1)`, and whether they are
+/// encoded in Shift-JIS.
+type Tripcodes = Box<dyn Iterator<Item=Result<(&'static str, usize, Vec<u8>, bool)>>>;
+
+/// Runs the `decode` subcommand, which fails with `Error::Failed` if any tripcode is invalid.
+pub fn main(program: &str, args: &[String]) -> Result<()> {
+    let brief = format!("Usage: {} decode [options] [--] [tripcodes]", program);
+
+    let mut opts = Options::new();
+    opts.optopt( "t", "type",   TYPE_DESC, "<type>")
+        .optflag("f", "filter", "read tripcodes from standard input")
+        .optflag("",  "sjis",   "decode tripcodes read from standard input as Shift-JIS")
+        .optflag("h", "help",   "print this help message and exit");
+
+    macro_rules! fail {
+        ($($arg:tt)*) => (usage_error!(program, opts, &brief, $($arg)*));
+    }
+
+    let matches = match opts.parse(args) {
+        Ok(m) => m,
+        Err(f) => fail!("{}", f),
+    };
+
+    if matches.opt_present("h") {
+        return io::stdout().write_all(opts.usage(&brief).as_bytes()).context(WRITING_STDOUT);
+    }
+
+    let (opt_f, opt_sjis, opt_t) = (
+        matches.opt_present("f"),
+        matches.opt_present("sjis"),
+        matches.opt_str("t"),
+    );
+    if opt_sjis && !opt_f {
+        fail!("`--sjis` requires `--filter`");
+    }
+
+    let mut tripcodes: Tripcodes = Box::new(
+        matches.free.into_iter().enumerate().map(|(i, s)| Ok(("argument", i + 1, s.into_bytes(), false)))
+    );
+    if opt_f {
+        tripcodes = Box::new(
+            tripcodes.chain(
+                Records::new(BufReader::new(io::stdin()), b'\n')
+                    .enumerate()
+                    .map(move |(i, r)| {
+                        let t = r.with_context(|| format!("reading standard input at line {}", i + 1))?;
+                        Ok(("line", i + 1, t, opt_sjis))
+                    })
+            )
+        );
+    }
+
+    let code_type = opt_t.unwrap_or_else(|| "4chan".to_owned());
+    with_generator!(code_type.as_str(), run::<_>(program, tripcodes),
+        fail!("unknown or unsupported tripcode type `{}`", code_type))
+}
+
+/// Decodes each of `tripcodes` and prints the variant and the value of the resulting hash.
+///
+/// A warning is printed to stderr for each tripcode that is not in the canonical form, and
+/// an error for each invalid tripcode.
+fn run<G>(program: &str, tripcodes: Tripcodes) -> Result<()>
+    where G: TripcodeGenerator, G::Hash: Describe
+{
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    let mut ok = true;
+
+    for item in tripcodes {
+        let (unit, n, tripcode, sjis) = item?;
+        let decode = |t: &[u8]| if sjis {
+            G::Hash::decode_from_sjis(t)
+        } else {
+            str::from_utf8(t).ok().and_then(G::Hash::decode)
+        };
+        let encode = |h: G::Hash| if sjis { h.encode_to_sjis() } else { h.encode().into_bytes() };
+
+        // Retry with the canonical form of the tripcode if it only has an extraneous last
+        // character, which `decode` rejects.
+        let hash = decode(&tripcode).or_else(|| canonicalize(&tripcode).and_then(|t| decode(&t)));
+
+        stdout.flush().context(WRITING_STDOUT)?;
+        match hash {
+            Some(h) => {
+                let description = h.describe();
+                let canonical = encode(h);
+                if canonical != tripcode {
+                    let _ = writeln!(io::stderr(), "{}: {} {}: `{}` is not in the canonical form `{}`",
+                        program, unit, n, String::from_utf8_lossy(&tripcode),
+                        String::from_utf8_lossy(&canonical));
+                }
+                writeln!(stdout, "{}", description).context(WRITING_STDOUT)?;
+            },
+            None => {
+                let _ = writeln!(io::stderr(), "{}: {} {}: invalid tripcode `{}`", program, unit, n,
+                    String::from_utf8_lossy(&tripcode));
+                ok = false;
+            },
+        }
+    }
+
+    if ok { Ok(()) } else { Err(Error::Failed) }
+}
+
+/// Returns `tripcode` with the last character replaced by its canonical form if it is
+/// a 10-character tripcode of the `crypt(3)` alphabet, or `None` otherwise.
+///
+/// The last character of a canonical 10-character tripcode is one of `.26AEIMQUYcgkosw`, since
+/// it only represents 4 bits of the hash value. The other characters of the alphabet map to
+/// the canonical character that has the same upper 4 bits.
+fn canonicalize(tripcode: &[u8]) -> Option<Vec<u8>> {
+    if tripcode.len() != 10 {
+        return None;
+    }
+    let crypt = Alphabet::Crypt.chars();
+    let i = crypt.iter().position(|c| c[..] == tripcode[9..])?;
+    let mut ret = tripcode.to_vec();
+    ret[9] = crypt[i & !0b11][0];
+    Some(ret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn canonical_forms() {
+        for t in &["abcd/0123.", "abcd/0123/", "abcd/01230", "abcd/01231"] {
+            assert_eq!(Some(b"abcd/0123.".to_vec()), canonicalize(t.as_bytes()));
+        }
+        assert_eq!(Some(b"abcd/01232".to_vec()), canonicalize(b"abcd/01232"));
+        assert_eq!(Some(b"ozOtJW9BFA".to_vec()), canonicalize(b"ozOtJW9BFB"));
+
+        assert_eq!(None, canonicalize(b"abcd/0123+"));
+        assert_eq!(None, canonicalize(b"abcd/0123"));
+    }
+}
@@ -0,0 +1,93 @@
+// Copyright 2016 Huton. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Human-readable descriptions of hash values.
+
+use tripcode::hash::*;
+
+/// Hash values that can be described by the name of their variant and their value.
+pub trait Describe: TripcodeHash {
+    /// Returns the name of the variant, e.g. `Ten`.
+    fn variant(&self) -> &'static str;
+
+    /// Returns the value in hexadecimal, or `None` for `Error`.
+    fn value_hex(&self) -> Option<String>;
+
+    /// Returns the name of the variant followed by the value in hexadecimal,
+    /// e.g. `Ten d3f6b95622cd44c0`.
+    fn describe(&self) -> String {
+        match self.value_hex() {
+            Some(v) => format!("{} {}", self.variant(), v),
+            None => self.variant().to_owned(),
+        }
+    }
+}
+
+impl Describe for FourchanHash {
+    fn variant(&self) -> &'static str {
+        "Ten"
+    }
+
+    fn value_hex(&self) -> Option<String> {
+        Some(format!("{:016x}", self.0))
+    }
+}
+
+impl Describe for Mona12Hash {
+    fn variant(&self) -> &'static str {
+        "Twelve"
+    }
+
+    fn value_hex(&self) -> Option<String> {
+        Some(format!("{:016x}{:02x}", self.0, self.1))
+    }
+}
+
+impl Describe for Sc15Hash {
+    fn variant(&self) -> &'static str {
+        "Fifteen"
+    }
+
+    fn value_hex(&self) -> Option<String> {
+        Some(format!("{:016x}{:08x}", self.0, self.1))
+    }
+}
+
+impl Describe for ScKatakanaHash {
+    fn variant(&self) -> &'static str {
+        "Katakana"
+    }
+
+    fn value_hex(&self) -> Option<String> {
+        self.0.value_hex()
+    }
+}
+
+macro_rules! describe_enum {
+    ($hash:ident { $($variant:ident),* }) => {
+        impl Describe for $hash {
+            fn variant(&self) -> &'static str {
+                match *self {
+                    $($hash::$variant(ref h) => h.variant(),)*
+                    $hash::Error => "Error",
+                }
+            }
+
+            fn value_hex(&self) -> Option<String> {
+                match *self {
+                    $($hash::$variant(ref h) => h.value_hex(),)*
+                    $hash::Error => None,
+                }
+            }
+        }
+    };
+}
+
+describe_enum!(MonaHash { Ten, Twelve });
+describe_enum!(ScHash { Ten, Twelve, Fifteen, Katakana });
@@ -0,0 +1,104 @@
+// Copyright 2016 Huton. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Errors of the command and their exit statuses.
+
+use std::{fmt, result};
+use std::io::{self, Write};
+use std::process;
+
+/// Exit status for when some of the inputs were rejected, or a search found nothing.
+pub const EXIT_FAILURE: i32 = 1;
+/// Exit status for invalid command-line arguments.
+pub const EXIT_USAGE: i32 = 2;
+/// Exit status for errors in reading the input or writing the output.
+pub const EXIT_IO: i32 = 3;
+/// Exit status for inputs that cannot be decoded, e.g. invalid hexadecimal passwords.
+pub const EXIT_DECODE: i32 = 4;
+
+/// Context of errors in writing to stdout.
+pub const WRITING_STDOUT: &str = "writing to standard output";
+
+pub type Result<T> = result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    /// Some of the inputs were rejected, which has already been reported, or a search found
+    /// nothing.
+    Failed,
+    /// An I/O error, with a description of what was being done.
+    Io(String, io::Error),
+    /// An input that cannot be decoded, with a description of where it comes from.
+    Decode(String),
+}
+
+impl Error {
+    /// Returns the exit status for the error.
+    pub fn exit_code(&self) -> i32 {
+        match *self {
+            Error::Failed    => EXIT_FAILURE,
+            Error::Io(..)    => EXIT_IO,
+            Error::Decode(_) => EXIT_DECODE,
+        }
+    }
+
+    /// Checks whether the error is caused by the reader of the output having exited,
+    /// e.g. when piping the output into `head`.
+    pub fn is_broken_pipe(&self) -> bool {
+        match *self {
+            Error::Io(_, ref e) => e.kind() == io::ErrorKind::BrokenPipe,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Failed => f.write_str("failed"),
+            Error::Io(ref context, ref e) => write!(f, "error {}: {}", context, e),
+            Error::Decode(ref message) => f.write_str(message),
+        }
+    }
+}
+
+/// Extension trait that attaches a description of what was being done to I/O errors.
+pub trait Context<T> {
+    fn context(self, context: &str) -> Result<T>;
+
+    fn with_context<F: FnOnce() -> String>(self, context: F) -> Result<T>;
+}
+
+impl<T> Context<T> for io::Result<T> {
+    #[inline]
+    fn context(self, context: &str) -> Result<T> {
+        self.map_err(|e| Error::Io(context.to_owned(), e))
+    }
+
+    #[inline]
+    fn with_context<F: FnOnce() -> String>(self, context: F) -> Result<T> {
+        self.map_err(|e| Error::Io(context(), e))
+    }
+}
+
+/// Exits with the status for `result`, printing the error message if any.
+///
+/// Broken pipes are not reported and exit with 0, since there is no one to read the rest of
+/// the output.
+pub fn exit(program: &str, result: Result<()>) -> ! {
+    match result {
+        Ok(()) => process::exit(0),
+        Err(ref e) if e.is_broken_pipe() => process::exit(0),
+        Err(Error::Failed) => process::exit(EXIT_FAILURE),
+        Err(e) => {
+            let _ = writeln!(io::stderr(), "{}: {}", program, e);
+            process::exit(e.exit_code());
+        },
+    }
+}
@@ -0,0 +1,121 @@
+// Copyright 2016 Huton. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The `explain` subcommand.
+
+use getopts::Options;
+use std::ascii;
+use std::io::{self, Write};
+use std::str;
+use tripcode::*;
+use tripcode::explain::Branch;
+use tripcode::hash::TripcodeHash;
+use describe::Describe;
+use error::{Context, Error, Result, WRITING_STDOUT};
+use common;
+use TYPE_DESC;
+
+/// Runs the `explain` subcommand.
+pub fn main(program: &str, args: &[String]) -> Result<()> {
+    let brief = format!("Usage: {} explain [options] [--] [passwords]", program);
+
+    let mut opts = Options::new();
+    opts.optopt( "t", "type", TYPE_DESC, "<type>")
+        .optflag("x", "hex",  "decode passwords from hexadecimal")
+        .optflag("h", "help", "print this help message and exit");
+
+    macro_rules! fail {
+        ($($arg:tt)*) => (usage_error!(program, opts, &brief, $($arg)*));
+    }
+
+    let matches = match opts.parse(args) {
+        Ok(m) => m,
+        Err(f) => fail!("{}", f),
+    };
+
+    if matches.opt_present("h") {
+        return io::stdout().write_all(opts.usage(&brief).as_bytes()).context(WRITING_STDOUT);
+    }
+
+    let mut passwords = Vec::with_capacity(matches.free.len());
+    for (i, p) in matches.free.iter().enumerate() {
+        if matches.opt_present("x") {
+            match common::decode_hex(p.as_bytes()) {
+                Some(p) => passwords.push(p),
+                None => return Err(Error::Decode(
+                    format!("argument {}: invalid hexadecimal password `{}`", i + 1, p))),
+            }
+        } else {
+            passwords.push(p.as_bytes().to_vec());
+        }
+    }
+
+    let code_type = matches.opt_str("t").unwrap_or_else(|| "4chan".to_owned());
+    with_generator!(code_type.as_str(), run::<_>(&passwords),
+        fail!("unknown or unsupported tripcode type `{}`", code_type))
+}
+
+/// Prints the explanation for each of `passwords`, separated by blank lines.
+fn run<G>(passwords: &[Vec<u8>]) -> Result<()> where G: Explain, G::Hash: Describe {
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+
+    for (i, p) in passwords.iter().enumerate() {
+        let e = G::explain(p);
+        let salt = e.salt.map(|s| format!("{}{} -> {}{}",
+            printable(&s.chars[..1]), printable(&s.chars[1..]),
+            s.reinterpreted[0] as char, s.reinterpreted[1] as char));
+
+        if i > 0 {
+            writeln!(stdout).context(WRITING_STDOUT)?;
+        }
+        writeln!(stdout, "password: {}", printable(p)).context(WRITING_STDOUT)?;
+        writeln!(stdout, "escaped:  {}", printable(&e.escaped)).context(WRITING_STDOUT)?;
+        writeln!(stdout, "salt:     {}", salt.as_ref().map_or("-", String::as_str))
+            .context(WRITING_STDOUT)?;
+        writeln!(stdout, "branch:   {}", describe_branch(e.branch)).context(WRITING_STDOUT)?;
+        writeln!(stdout, "hash:     {}", e.hash.describe()).context(WRITING_STDOUT)?;
+        writeln!(stdout, "tripcode: {}", e.hash.encode()).context(WRITING_STDOUT)?;
+    }
+
+    Ok(())
+}
+
+fn describe_branch(branch: Branch) -> &'static str {
+    match branch {
+        Branch::Ten       => "10-character tripcode (DES)",
+        Branch::NamaKey   => "nama key tripcode (DES)",
+        Branch::Twelve    => "12-character tripcode (SHA-1)",
+        Branch::Fifteen   => "15-character tripcode (SHA-1)",
+        Branch::Katakana  => "katakana tripcode (SHA-1)",
+        Branch::Undefined => "undefined (12 or more bytes starting with `$`)",
+    }
+}
+
+/// Formats `bytes` as text with control characters escaped, or with every non-ASCII byte
+/// escaped as `\xNN` if it is not valid UTF-8, e.g. in Shift-JIS.
+fn printable(bytes: &[u8]) -> String {
+    match str::from_utf8(bytes) {
+        Ok(s) => s.chars().map(|c| if c.is_control() {
+            c.escape_default().collect()
+        } else {
+            c.to_string()
+        }).collect(),
+        Err(_) => bytes.iter().flat_map(|&b| ascii::escape_default(b)).map(|b| b as char).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn printable() {
+        assert_eq!("ｶﾀｶﾅ\\n", super::printable("ｶﾀｶﾅ\n".as_bytes()));
+        assert_eq!("$\\xb6\\t", super::printable(b"$\xB6\t"));
+    }
+}
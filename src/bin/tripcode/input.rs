@@ -0,0 +1,102 @@
+// Copyright 2016 Huton. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Splitting of the standard input into passwords or tripcodes.
+
+use std::io::{self, BufRead};
+use error::{Error, Result};
+
+/// Iterator over the records of a reader, which are separated by a delimiter.
+///
+/// When the delimiter is `\n`, a `\r` that precedes it (or the end of the input) is also
+/// removed, so that input with CRLF line endings yields the same records as with LF.
+pub struct Records<R> {
+    reader: R,
+    delimiter: u8,
+}
+
+impl<R: BufRead> Records<R> {
+    pub fn new(reader: R, delimiter: u8) -> Self {
+        Records { reader, delimiter }
+    }
+}
+
+impl<R: BufRead> Iterator for Records<R> {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<io::Result<Vec<u8>>> {
+        let mut buf = Vec::new();
+        match self.reader.read_until(self.delimiter, &mut buf) {
+            Ok(0) => return None,
+            Ok(_) => (),
+            Err(e) => return Some(Err(e)),
+        }
+
+        if buf.last() == Some(&self.delimiter) {
+            buf.pop();
+        }
+        if self.delimiter == b'\n' && buf.last() == Some(&b'\r') {
+            buf.pop();
+        }
+        Some(Ok(buf))
+    }
+}
+
+/// Iterator adapter that yields the values of `Ok` items, and stops at the first `Err` item,
+/// whose error is stored to be checked after the iteration.
+pub struct UntilError<'a, I> {
+    iter: I,
+    error: &'a mut Option<Error>,
+}
+
+impl<'a, I> UntilError<'a, I> {
+    pub fn new(iter: I, error: &'a mut Option<Error>) -> Self {
+        UntilError { iter, error }
+    }
+}
+
+impl<'a, I, T> Iterator for UntilError<'a, I> where I: Iterator<Item=Result<T>> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.error.is_some() {
+            return None;
+        }
+        match self.iter.next() {
+            Some(Ok(v)) => Some(v),
+            Some(Err(e)) => {
+                *self.error = Some(e);
+                None
+            },
+            None => None,
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Any of the items may be an error.
+        (0, self.iter.size_hint().1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records(input: &[u8], delimiter: u8) -> Vec<Vec<u8>> {
+        Records::new(input, delimiter).map(io::Result::unwrap).collect()
+    }
+
+    #[test]
+    fn split() {
+        assert_eq!(vec![b"a".to_vec(), b"".to_vec(), b"b".to_vec()], records(b"a\n\nb\n", b'\n'));
+        assert_eq!(vec![b"a".to_vec(), b"b\r".to_vec(), b"c".to_vec()], records(b"a\r\nb\r\r\nc\r", b'\n'));
+        assert_eq!(vec![b"a\r\n".to_vec(), b"b".to_vec()], records(b"a\r\n\0b", b'\0'));
+        assert!(records(b"", b'\n').is_empty());
+    }
+}
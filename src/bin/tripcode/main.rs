@@ -0,0 +1,310 @@
+// Copyright 2016 Huton. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate encoding;
+extern crate getopts;
+extern crate tripcode;
+
+use getopts::Options;
+use std::env;
+use std::io::{self, BufReader, Write};
+use charset::{OutputEncoding, PasswordTranscoder};
+use describe::Describe;
+use error::{Context, Error, Result, WRITING_STDOUT};
+use input::{Records, UntilError};
+use output::{Format, Output};
+use tripcode::*;
+
+/// Description of the `--type` option.
+const TYPE_DESC: &str = "type of tripcodes: 4chan (4), 4chan-nonescaping, 2ch (2), 2ch-nonescaping, \
+                         2ch10, 2ch10-nonescaping, 2ch12, 2ch12-nonescaping, 2ch-raw, sc (s), \
+                         sc-utf8, sc15, sc-katakana or des. defaults to `4chan`";
+
+/// Prints an error message followed by the usage to stderr and exits with `EXIT_USAGE`.
+macro_rules! usage_error {
+    ($program:expr, $opts:expr, $brief:expr, $($arg:tt)*) => {{
+        let _ = writeln!(io::stderr(), "{}: {}\n", $program, format!($($arg)*));
+        let _ = write!(io::stderr(), "{}", $opts.usage($brief));
+        ::std::process::exit(::error::EXIT_USAGE);
+    }};
+}
+
+#[macro_use]
+#[path = "../common/mod.rs"]
+mod common;
+mod bench;
+mod charset;
+mod decode;
+mod describe;
+mod error;
+mod explain;
+mod input;
+mod output;
+mod rainbow;
+mod repl;
+mod search;
+
+fn main() {
+    let mut args = env::args();
+    let program = args.next().unwrap();
+
+    let args: Vec<String> = args.collect();
+    let result = match args.first().map(String::as_str) {
+        Some("bench")   => bench::main(&program, &args[1..]),
+        Some("decode")  => decode::main(&program, &args[1..]),
+        Some("explain") => explain::main(&program, &args[1..]),
+        Some("rainbow") => rainbow::main(&program, &args[1..]),
+        Some("repl")    => repl::main(&program, &args[1..]),
+        Some("search")  => search::main(&program, &args[1..]),
+        _               => run(&program, &args),
+    };
+
+    error::exit(&program, result);
+}
+
+/// Passwords paired with where they come from, e.g. `("line", 1)`, for error messages.
+type Passwords = Box<dyn Iterator<Item=Result<(&'static str, usize, Vec<u8>)>>>;
+
+/// Runs the command to generate tripcodes.
+fn run(program: &str, args: &[String]) -> Result<()> {
+    let brief = format!("Usage: {0} [options] [--] [passwords]\n       \
+                         {0} bench [options]\n       \
+                         {0} decode [options] [--] [tripcodes]\n       \
+                         {0} explain [options] [--] [passwords]\n       \
+                         {0} rainbow (build | lookup) [options] <file>\n       \
+                         {0} repl [options]\n       \
+                         {0} search [options]",
+        program);
+    let stdout = io::stdout();
+
+    let mut opts = Options::new();
+    opts.optopt( "t", "type",     TYPE_DESC, "<type>")
+        .optopt( "",  "salt",     "salt characters for `--type=des`", "<XY>")
+        .optopt( "",  "format",   "output format: plain, json, ndjson, csv or tsv. the formats \
+                                   other than `plain` always include passwords. \
+                                   defaults to `plain`", "<format>")
+        .optflag("f", "filter",   "read passwords from standard input, one per line")
+        .optflag("0", "null",     "separate passwords read from standard input, and tripcodes \
+                                   printed in the `plain` format, by NUL characters instead of \
+                                   newlines")
+        .optflag("x", "hex",      "decode passwords from hexadecimal")
+        .optopt( "",  "input-encoding", "encoding of passwords, e.g. `cp932` or `euc-jp`. \
+                                   passwords are transcoded into the encoding that the site \
+                                   uses, i.e. Shift-JIS for 2channel and 2ch.sc, and UTF-8 \
+                                   otherwise", "<encoding>")
+        .optopt( "",  "output-encoding", "encoding of tripcodes and passwords in the `plain` \
+                                   format, e.g. `shift_jis`. defaults to `utf-8`", "<encoding>")
+        .optflag("h", "help",     "print this help message and exit")
+        .optflag("p", "password", "print passwords along with tripcodes");
+
+    macro_rules! fail {
+        ($($arg:tt)*) => (usage_error!(program, opts, &brief, $($arg)*));
+    }
+
+    let matches = match opts.parse(args) {
+        Ok(m) => m,
+        Err(f) => fail!("{}", f),
+    };
+
+    if matches.opt_present("h") {
+        return io::stdout().write_all(opts.usage(&brief).as_bytes()).context(WRITING_STDOUT);
+    }
+
+    let (opt_f, opt_p, opt_0, opt_x, opt_t, opt_salt) = (
+        matches.opt_present("f"),
+        matches.opt_present("p"),
+        matches.opt_present("0"),
+        matches.opt_present("x"),
+        matches.opt_str("t"),
+        matches.opt_str("salt"),
+    );
+    let delimiter = if opt_0 { b'\0' } else { b'\n' };
+    let format = match matches.opt_str("format").map(|f| f.parse()) {
+        Some(Ok(f)) => f,
+        Some(Err(())) => fail!("invalid value for `--format`"),
+        None => Format::Plain,
+    };
+
+    let code_type = opt_t.unwrap_or_else(|| "4chan".to_owned());
+    let type_name = match common::type_name(&code_type) {
+        Some(t) => t.to_owned(),
+        None => fail!("unknown tripcode type `{}`", code_type),
+    };
+
+    let transcoder = match matches.opt_str("input-encoding") {
+        Some(label) => match charset::encoding_from_label(&label) {
+            Some(e) => Some(PasswordTranscoder::new(e, charset::password_encoding(&type_name))),
+            None => fail!("unknown encoding `{}`", label),
+        },
+        None => None,
+    };
+    let output_encoding = match matches.opt_str("output-encoding") {
+        Some(ref label) if format != Format::Plain => {
+            fail!("`--output-encoding` is only valid with `--format=plain`, not with `{}`", label)
+        },
+        Some(label) => match OutputEncoding::from_label(&label) {
+            Some(e) => e,
+            None => fail!("unknown encoding `{}`", label),
+        },
+        None => OutputEncoding::Utf8,
+    };
+
+    let mut passwords: Passwords = Box::new(
+        matches.free.into_iter().enumerate().map(|(i, s)| Ok(("argument", i + 1, s.into_bytes())))
+    );
+    if opt_f {
+        let unit = if opt_0 { "record" } else { "line" };
+        passwords = Box::new(
+            passwords.chain(
+                Records::new(BufReader::new(io::stdin()), delimiter)
+                    .enumerate()
+                    .map(move |(i, r)| {
+                        let p = r.with_context(|| format!("reading standard input at {} {}", unit, i + 1))?;
+                        Ok((unit, i + 1, p))
+                    })
+            )
+        );
+    }
+
+    let passwords = passwords.map(move |r| {
+        let (unit, n, mut p) = r?;
+        if opt_x {
+            p = common::decode_hex(&p).ok_or_else(|| Error::Decode(format!(
+                "{} {}: invalid hexadecimal password `{}`", unit, n, String::from_utf8_lossy(&p))))?;
+        }
+        if let Some(ref t) = transcoder {
+            p = t.transcode(&p).ok_or_else(|| Error::Decode(format!(
+                "{} {}: password `{}` is not valid in the input encoding",
+                unit, n, String::from_utf8_lossy(&p))))?;
+        }
+        Ok(p)
+    });
+
+    let mut out = Output::new(stdout, format, type_name)
+        .with_password(opt_p)
+        .terminator(delimiter)
+        .encoding(output_encoding);
+    if let Some(t) = transcoder {
+        out = out.transcoder(t);
+    }
+
+    // The passwords stop at the first error in reading or decoding them, which is stored here.
+    let mut input_error = None;
+    let passwords = UntilError::new(passwords, &mut input_error);
+
+    let ok = match (code_type.as_str(), opt_salt) {
+        ("des", Some(salt)) => {
+            if salt.len() != 2 {
+                fail!("`--salt` must be 2 bytes long");
+            }
+            let salt = salt.as_bytes();
+            generate_each(program, &mut out, passwords, |p| Some(Des::hash(p, salt[0], salt[1])))?
+        },
+        ("des", None) => fail!("`--type=des` requires `--salt`"),
+        (_, Some(_)) => fail!("`--salt` is only valid with `--type=des`"),
+        ("2ch-raw", None) => {
+            generate_each(program, &mut out, passwords, |p| MonaRaw::try_hash(p))?
+        },
+        (code_type, None) => {
+            with_generator!(code_type, generate::<_, _, _>(&mut out, passwords, opt_f),
+                fail!("unknown tripcode type `{}`", code_type))?;
+            true
+        },
+    };
+
+    out.finish().context(WRITING_STDOUT)?;
+    if let Some(e) = input_error {
+        return Err(e);
+    }
+    if ok { Ok(()) } else { Err(Error::Failed) }
+}
+
+/// Generates tripcodes in batches with `G`.
+///
+/// If `streaming` is `true`, e.g. when reading passwords from stdin, each password is hashed and
+/// written as soon as it is read, so that the output does not wait for a whole batch of input.
+fn generate<G, W, I>(out: &mut Output<W>, passwords: I, streaming: bool) -> Result<()>
+    where G: TripcodeGenerator, G::Hash: Describe, W: Write, I: Iterator<Item=Vec<u8>>
+{
+    if streaming {
+        for (p, h) in passwords.hashes_with_passwords::<G>().batch_len(1) {
+            out.record(&p, h).context(WRITING_STDOUT)?;
+            out.flush().context(WRITING_STDOUT)?;
+        }
+    } else {
+        for (p, h) in passwords.hashes_with_passwords::<G>() {
+            out.record(&p, h).context(WRITING_STDOUT)?;
+        }
+    }
+    Ok(())
+}
+
+/// Generates tripcodes one by one with `hash`, which yields `None` for invalid passwords.
+///
+/// An error is printed to stderr for each invalid password. Returns `false` if there were any.
+fn generate_each<W, I, F, H>(program: &str, out: &mut Output<W>, passwords: I, mut hash: F)
+    -> Result<bool>
+    where W: Write, I: Iterator<Item=Vec<u8>>, F: FnMut(&[u8]) -> Option<H>, H: Describe
+{
+    let mut ok = true;
+
+    for p in passwords {
+        if let Some(h) = hash(&p) {
+            out.record(&p, h).context(WRITING_STDOUT)?;
+        } else {
+            out.flush().context(WRITING_STDOUT)?;
+            let _ = writeln!(io::stderr(), "{}: invalid password `{}`", program,
+                String::from_utf8_lossy(&p));
+            ok = false;
+        }
+    }
+
+    Ok(ok)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    struct Shared(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn streaming() {
+        let buf = Rc::new(RefCell::new(Vec::new()));
+        let mut out = Output::new(Shared(buf.clone()), Format::Plain, "4chan".to_owned());
+
+        // The second password is not available until the tripcode of the first one is written,
+        // as when stdin blocks after the first line.
+        let mut passwords = vec![b"password".to_vec()].into_iter();
+        let mut polled = false;
+        let passwords = ::std::iter::from_fn(|| passwords.next().or_else(|| {
+            if !polled {
+                polled = true;
+                assert_eq!(format!("{}\n", Fourchan::generate("password")).as_bytes(), &buf.borrow()[..]);
+            }
+            None
+        }));
+
+        generate::<Fourchan, _, _>(&mut out, passwords, true).unwrap();
+        assert!(polled);
+    }
+}
@@ -0,0 +1,327 @@
+// Copyright 2016 Huton. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Output formats of generated tripcodes.
+
+use std::borrow::Cow;
+use std::io::{self, Write};
+use std::str::{self, FromStr};
+use charset::{OutputEncoding, PasswordTranscoder};
+use describe::Describe;
+
+/// Format of the records written by `Output`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// `tripcode` or `tripcode#password` lines, or NUL-terminated records.
+    Plain,
+    /// A JSON array of objects.
+    Json,
+    /// A JSON object per line.
+    Ndjson,
+    /// Comma-separated values with a header line, as described in RFC 4180.
+    Csv,
+    /// Tab-separated values with a header line. Tabs, newlines, carriage returns and backslashes
+    /// in the fields are escaped as `\t`, `\n`, `\r` and `\\`.
+    Tsv,
+}
+
+impl FromStr for Format {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Format, ()> {
+        match s {
+            "plain"  => Ok(Format::Plain),
+            "json"   => Ok(Format::Json),
+            "ndjson" => Ok(Format::Ndjson),
+            "csv"    => Ok(Format::Csv),
+            "tsv"    => Ok(Format::Tsv),
+            _        => Err(()),
+        }
+    }
+}
+
+/// Field names of the structured formats, in the order of the CSV/TSV columns and JSON keys.
+const FIELDS: [&str; 5] = ["password", "password_encoding", "tripcode", "type", "variant"];
+
+/// Writer of generated tripcodes, one record per password.
+///
+/// The structured formats always include the password and are always encoded in UTF-8.
+/// Passwords that are not valid UTF-8 are written in hexadecimal, which is indicated by
+/// `hex` (instead of `utf-8`) in the `password_encoding` field.
+pub struct Output<W: Write> {
+    dst: W,
+    format: Format,
+    type_name: String,
+    /// Whether `Format::Plain` includes passwords.
+    with_password: bool,
+    /// Terminator of the records of `Format::Plain`.
+    terminator: u8,
+    /// Encoding of `Format::Plain`.
+    encoding: OutputEncoding,
+    /// Converter of passwords back from the encoding they were hashed in, if they were
+    /// transcoded from the input encoding.
+    transcoder: Option<PasswordTranscoder>,
+    records: u64,
+    buf: Vec<u8>,
+}
+
+impl<W: Write> Output<W> {
+    /// Creates an `Output` that writes records of tripcodes of the type named `type_name`.
+    pub fn new(dst: W, format: Format, type_name: String) -> Self {
+        Output {
+            dst,
+            format,
+            type_name,
+            with_password: false,
+            terminator: b'\n',
+            encoding: OutputEncoding::Utf8,
+            transcoder: None,
+            records: 0,
+            buf: Vec::new(),
+        }
+    }
+
+    /// Sets whether `Format::Plain` includes passwords, as `tripcode#password`.
+    pub fn with_password(mut self, with_password: bool) -> Self {
+        self.with_password = with_password;
+        self
+    }
+
+    /// Sets the terminator of the records of `Format::Plain`, which defaults to `\n`.
+    pub fn terminator(mut self, terminator: u8) -> Self {
+        self.terminator = terminator;
+        self
+    }
+
+    /// Sets the encoding of `Format::Plain`, which defaults to UTF-8.
+    pub fn encoding(mut self, encoding: OutputEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Makes the `Output` convert passwords back from the encoding they were transcoded into
+    /// by `transcoder` before writing them.
+    pub fn transcoder(mut self, transcoder: PasswordTranscoder) -> Self {
+        self.transcoder = Some(transcoder);
+        self
+    }
+
+    /// Writes a record of the tripcode for `hash`, generated from `password`.
+    pub fn record<H: Describe>(&mut self, password: &[u8], hash: H) -> io::Result<()> {
+        if self.records == 0 {
+            self.header()?;
+        }
+        self.records += 1;
+
+        let password = match self.transcoder {
+            Some(t) if self.format == Format::Plain => Cow::Owned(t.to_output(password, &self.encoding)),
+            Some(t) => Cow::Owned(t.to_output(password, &OutputEncoding::Utf8)),
+            None => Cow::Borrowed(password),
+        };
+        let variant = hash.variant();
+        let dst = &mut self.dst;
+
+        if self.format == Format::Plain {
+            self.buf.clear();
+            self.encoding.append_tripcode(hash, &mut self.buf);
+            dst.write_all(&self.buf)?;
+            if self.with_password {
+                dst.write_all(b"#")?;
+                dst.write_all(&password)?;
+            }
+            return dst.write_all(&[self.terminator]);
+        }
+
+        let tripcode = hash.encode();
+        let (password, encoding) = match str::from_utf8(&password) {
+            Ok(p) => (Cow::Borrowed(p), "utf-8"),
+            Err(_) => (Cow::Owned(to_hex(&password)), "hex"),
+        };
+        let fields = [&password as &str, encoding, &tripcode, &self.type_name, variant];
+
+        match self.format {
+            Format::Json | Format::Ndjson => {
+                if self.format == Format::Json {
+                    dst.write_all(if self.records == 1 { b"\n  " } else { b",\n  " })?;
+                }
+                for (i, (name, field)) in FIELDS.iter().zip(&fields).enumerate() {
+                    dst.write_all(if i == 0 { b"{" } else { b"," })?;
+                    write_json_str(dst, name)?;
+                    dst.write_all(b":")?;
+                    write_json_str(dst, field)?;
+                }
+                dst.write_all(b"}")?;
+                if self.format == Format::Ndjson {
+                    dst.write_all(b"\n")?;
+                }
+            },
+            Format::Csv | Format::Tsv => {
+                for (i, field) in fields.iter().enumerate() {
+                    if self.format == Format::Csv {
+                        if i > 0 { dst.write_all(b",")?; }
+                        write_csv_field(dst, field)?;
+                    } else {
+                        if i > 0 { dst.write_all(b"\t")?; }
+                        write_tsv_field(dst, field)?;
+                    }
+                }
+                dst.write_all(b"\n")?;
+            },
+            Format::Plain => unreachable!(),
+        }
+
+        Ok(())
+    }
+
+    /// Writes the trailer of the format, if any, and flushes the writer.
+    pub fn finish(&mut self) -> io::Result<()> {
+        if self.records == 0 {
+            self.header()?;
+        }
+        if self.format == Format::Json {
+            self.dst.write_all(if self.records == 0 { b"]\n" } else { b"\n]\n" })?;
+        }
+        self.dst.flush()
+    }
+
+    /// Flushes the writer, e.g. before writing to stderr.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.dst.flush()
+    }
+
+    fn header(&mut self) -> io::Result<()> {
+        match self.format {
+            Format::Csv => writeln!(self.dst, "{}", FIELDS.join(",")),
+            Format::Tsv => writeln!(self.dst, "{}", FIELDS.join("\t")),
+            Format::Json => self.dst.write_all(b"["),
+            Format::Plain | Format::Ndjson => Ok(()),
+        }
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn write_json_str<W: Write>(dst: &mut W, s: &str) -> io::Result<()> {
+    dst.write_all(b"\"")?;
+    for c in s.chars() {
+        match c {
+            '"'  => dst.write_all(b"\\\"")?,
+            '\\' => dst.write_all(b"\\\\")?,
+            '\n' => dst.write_all(b"\\n")?,
+            '\r' => dst.write_all(b"\\r")?,
+            '\t' => dst.write_all(b"\\t")?,
+            c if c < ' ' || c == '\u{7F}' => write!(dst, "\\u{:04x}", c as u32)?,
+            c => write!(dst, "{}", c)?,
+        }
+    }
+    dst.write_all(b"\"")
+}
+
+fn write_csv_field<W: Write>(dst: &mut W, s: &str) -> io::Result<()> {
+    if s.contains(&[',', '"', '\n', '\r'][..]) {
+        write!(dst, "\"{}\"", s.replace('"', "\"\""))
+    } else {
+        dst.write_all(s.as_bytes())
+    }
+}
+
+fn write_tsv_field<W: Write>(dst: &mut W, s: &str) -> io::Result<()> {
+    for c in s.chars() {
+        match c {
+            '\t' => dst.write_all(b"\\t")?,
+            '\n' => dst.write_all(b"\\n")?,
+            '\r' => dst.write_all(b"\\r")?,
+            '\\' => dst.write_all(b"\\\\")?,
+            c => write!(dst, "{}", c)?,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use encoding::all::{UTF_8, WINDOWS_31J};
+    use tripcode::hash::{MonaHash, ScHash, TripcodeHash};
+
+    fn write(format: Format, records: &[(&[u8], &str)]) -> String {
+        let mut buf = Vec::new();
+        {
+            let mut out = Output::new(&mut buf, format, "2ch".to_owned()).with_password(true);
+            for &(p, t) in records {
+                out.record(p, MonaHash::decode(t).unwrap()).unwrap();
+            }
+            out.finish().unwrap();
+        }
+        String::from_utf8(buf).unwrap()
+    }
+
+    const RECORDS: &[(&[u8], &str)] = &[
+        (b"a#b", "ZnBI2EKkq."),
+        (b"\"q\",\t\\\n\x01", "qJgGsrhiJ10r"),
+        (b"\xFF\x00", "U/Wld38xVY"),
+    ];
+
+    #[test]
+    fn plain() {
+        assert_eq!("ZnBI2EKkq.#a#b\n", write(Format::Plain, &RECORDS[..1]));
+
+        let mut buf = Vec::new();
+        {
+            let mut out = Output::new(&mut buf, Format::Plain, "sc".to_owned())
+                .with_password(true)
+                .terminator(b'\0')
+                .encoding(OutputEncoding::Sjis)
+                .transcoder(PasswordTranscoder::new(UTF_8, WINDOWS_31J));
+            out.record(b"$\x83g", ScHash::decode("ｶﾀｶﾅﾄﾘｯﾌﾟﾃｽﾄﾃﾞｽ").unwrap()).unwrap();
+            out.finish().unwrap();
+        }
+        assert_eq!(&b"\xB6\xC0\xB6\xC5\xC4\xD8\xAF\xCC\xDF\xC3\xBD\xC4\xC3\xDE\xBD#$\x83g\0"[..], &buf[..]);
+    }
+
+    #[test]
+    fn json() {
+        assert_eq!("[]\n", write(Format::Json, &[]));
+        assert_eq!("password,password_encoding,tripcode,type,variant\n", write(Format::Csv, &[]));
+        assert_eq!(
+            "[\n  \
+             {\"password\":\"a#b\",\"password_encoding\":\"utf-8\",\"tripcode\":\"ZnBI2EKkq.\",\"type\":\"2ch\",\"variant\":\"Ten\"},\n  \
+             {\"password\":\"\\\"q\\\",\\t\\\\\\n\\u0001\",\"password_encoding\":\"utf-8\",\"tripcode\":\"qJgGsrhiJ10r\",\
+              \"type\":\"2ch\",\"variant\":\"Twelve\"},\n  \
+             {\"password\":\"ff00\",\"password_encoding\":\"hex\",\"tripcode\":\"U/Wld38xVY\",\"type\":\"2ch\",\"variant\":\"Ten\"}\n\
+             ]\n",
+            write(Format::Json, RECORDS));
+        assert_eq!(
+            "{\"password\":\"a#b\",\"password_encoding\":\"utf-8\",\"tripcode\":\"ZnBI2EKkq.\",\"type\":\"2ch\",\"variant\":\"Ten\"}\n",
+            write(Format::Ndjson, &RECORDS[..1]));
+    }
+
+    #[test]
+    fn csv() {
+        assert_eq!(
+            "password,password_encoding,tripcode,type,variant\n\
+             a#b,utf-8,ZnBI2EKkq.,2ch,Ten\n\
+             \"\"\"q\"\",\t\\\n\x01\",utf-8,qJgGsrhiJ10r,2ch,Twelve\n\
+             ff00,hex,U/Wld38xVY,2ch,Ten\n",
+            write(Format::Csv, RECORDS));
+    }
+
+    #[test]
+    fn tsv() {
+        assert_eq!(
+            "password\tpassword_encoding\ttripcode\ttype\tvariant\n\
+             a#b\tutf-8\tZnBI2EKkq.\t2ch\tTen\n\
+             \"q\",\\t\\\\\\n\x01\tutf-8\tqJgGsrhiJ10r\t2ch\tTwelve\n\
+             ff00\thex\tU/Wld38xVY\t2ch\tTen\n",
+            write(Format::Tsv, RECORDS));
+    }
+}
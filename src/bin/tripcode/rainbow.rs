@@ -0,0 +1,229 @@
+// Copyright 2016 Huton. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The `rainbow` subcommand.
+
+use getopts::Options;
+use std::cmp;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Write};
+use std::str::{self, FromStr};
+use tripcode::*;
+use tripcode::hash::{FourchanHash, TripcodeHash};
+use tripcode::rainbow::{Builder, Table};
+use error::{Context, Error, Result, WRITING_STDOUT};
+use input::Records;
+use search::expand_charset;
+
+/// Description of the `--type` option, which only takes the types of 10-character tripcodes.
+const RAINBOW_TYPE_DESC: &str = "type of tripcodes: 4chan (4), 4chan-nonescaping, 2ch10 or \
+                                 2ch10-nonescaping. defaults to `4chan`";
+
+/// Maximum number of chains that `build` builds, which take 16 GiB of memory.
+const MAX_CHAINS: u64 = 1 << 30;
+
+/// Tripcodes paired with where they come from, e.g. `("line", 1)`.
+type Tripcodes = Box<dyn Iterator<Item=Result<(&'static str, usize, Vec<u8>)>>>;
+
+/// Same as `with_generator!` but only for the generators that rainbow tables support.
+macro_rules! with_rainbow_generator {
+    ($t:expr, $f:ident::<_>($($arg:expr),*), $other:expr) => {
+        match $t {
+            "4chan" | "4"       => $f::<Fourchan>($($arg),*),
+            "4chan-nonescaping" => $f::<FourchanNonescaping>($($arg),*),
+            "2ch10"             => $f::<Mona10>($($arg),*),
+            "2ch10-nonescaping" => $f::<Mona10Nonescaping>($($arg),*),
+            _                   => $other,
+        }
+    };
+}
+
+/// Runs the `rainbow` subcommand.
+///
+/// `rainbow lookup` fails with `Error::Failed` if no tripcode was found or any was invalid.
+pub fn main(program: &str, args: &[String]) -> Result<()> {
+    let brief = format!("Usage: {0} rainbow build [options] <file>\n       \
+                         {0} rainbow lookup [options] <file> [--] [tripcodes]",
+        program);
+
+    let mut opts = Options::new();
+    opts.optopt( "t", "type",      RAINBOW_TYPE_DESC, "<type>")
+        .optopt( "c", "charset",   "build: characters to make passwords from, e.g. `a-z0-9`. \
+                                    defaults to `./0-9A-Za-z`", "<chars>")
+        .optopt( "l", "length",    "build: length of passwords in bytes. defaults to 4", "<n>")
+        .optopt( "",  "chain-len", "build: number of passwords in each chain. defaults to 1000",
+                                   "<n>")
+        .optopt( "",  "chains",    "build: number of chains. defaults to twice the number of \
+                                    passwords divided by `--chain-len`", "<n>")
+        .optopt( "j", "threads",   "build: number of threads. defaults to the number of CPUs",
+                                   "<n>")
+        .optflag("f", "filter",    "lookup: read tripcodes from standard input")
+        .optflag("h", "help",      "print this help message and exit");
+
+    macro_rules! fail {
+        ($($arg:tt)*) => (usage_error!(program, opts, &brief, $($arg)*));
+    }
+
+    let mut matches = match opts.parse(args) {
+        Ok(m) => m,
+        Err(f) => fail!("{}", f),
+    };
+
+    if matches.opt_present("h") {
+        return io::stdout().write_all(opts.usage(&brief).as_bytes()).context(WRITING_STDOUT);
+    }
+
+    macro_rules! parse_opt {
+        ($name:expr) => {
+            match matches.opt_str($name).map(|s| FromStr::from_str(&s)) {
+                Some(Ok(v)) => Some(v),
+                Some(Err(_)) => fail!("invalid value for `--{}`", $name),
+                None => None,
+            }
+        };
+    }
+
+    let code_type = matches.opt_str("t").unwrap_or_else(|| "4chan".to_owned());
+    let rest = matches.free.split_off(cmp::min(2, matches.free.len()));
+    let (action, path) = match (matches.free.first(), matches.free.get(1)) {
+        (Some(action), Some(path)) => (action.as_str(), path.as_str()),
+        (Some(_), None) => fail!("no table file given"),
+        (None, _) => fail!("no action given"),
+    };
+
+    match action {
+        "build" => {
+            if let Some(arg) = rest.first() {
+                fail!("unexpected argument `{}`", arg);
+            }
+            if matches.opt_present("f") {
+                fail!("`--filter` is only valid with `lookup`");
+            }
+
+            let mut builder = Builder::new();
+            if let Some(charset) = matches.opt_str("charset") {
+                match expand_charset(charset.as_bytes()) {
+                    Some(c) => builder = builder.charset(&c),
+                    None => fail!("invalid charset `{}`", charset),
+                }
+            }
+            if let Some(len) = parse_opt!("length") {
+                builder = builder.password_len(len);
+            }
+            if let Some(len) = parse_opt!("chain-len") {
+                builder = builder.chain_len(len);
+            }
+            if let Some(chains) = parse_opt!("chains") {
+                builder = builder.chains(chains);
+            }
+            if let Some(threads) = parse_opt!("threads") {
+                builder = builder.threads(threads);
+            }
+            if builder.keyspace_len().is_none_or(|l| l > 1 << 58) {
+                fail!("too many passwords of the charset and the length");
+            }
+            let len = builder.table_len().unwrap();
+            if len > MAX_CHAINS {
+                fail!("too many chains to build ({}, {} bytes); set a smaller `--chains` or \
+                       a larger `--chain-len`", len, len.saturating_mul(16));
+            }
+
+            with_rainbow_generator!(code_type.as_str(), build::<_>(builder, path),
+                fail!("unknown or unsupported tripcode type `{}`", code_type))
+        },
+        "lookup" => {
+            for name in &["charset", "length", "chain-len", "chains", "threads"] {
+                if matches.opt_present(name) {
+                    fail!("`--{}` is only valid with `build`", name);
+                }
+            }
+
+            if rest.is_empty() && !matches.opt_present("f") {
+                fail!("no tripcodes given");
+            }
+
+            let mut tripcodes: Tripcodes = Box::new(
+                rest.into_iter().enumerate()
+                    .map(|(i, s)| Ok(("argument", i + 1, s.into_bytes())))
+            );
+            if matches.opt_present("f") {
+                tripcodes = Box::new(
+                    tripcodes.chain(
+                        Records::new(BufReader::new(io::stdin()), b'\n')
+                            .enumerate()
+                            .map(|(i, r)| {
+                                let t = r.with_context(|| format!("reading standard input at line {}", i + 1))?;
+                                Ok(("line", i + 1, t))
+                            })
+                    )
+                );
+            }
+
+            with_rainbow_generator!(code_type.as_str(), lookup::<_>(program, path, tripcodes),
+                fail!("unknown or unsupported tripcode type `{}`", code_type))
+        },
+        _ => fail!("unknown action `{}`", action),
+    }
+}
+
+/// Builds a table and writes it to the file at `path`, printing its size.
+fn build<G>(builder: Builder, path: &str) -> Result<()>
+    where G: TripcodeGenerator<Hash=FourchanHash>
+{
+    let table = builder.build::<G>();
+
+    let file = File::create(path).with_context(|| format!("creating `{}`", path))?;
+    let mut file = BufWriter::new(file);
+    table.write_to(&mut file)
+        .and_then(|()| file.flush())
+        .with_context(|| format!("writing `{}`", path))?;
+
+    writeln!(io::stdout(), "{} chains of {} passwords out of {}",
+        table.len(), table.chain_len(), table.keyspace_len()).context(WRITING_STDOUT)
+}
+
+/// Looks up each of `tripcodes` in the table at `path`, printing each found password as
+/// `<password> #<tripcode>`.
+fn lookup<G>(program: &str, path: &str, tripcodes: Tripcodes) -> Result<()>
+    where G: TripcodeGenerator<Hash=FourchanHash>
+{
+    let file = File::open(path).with_context(|| format!("opening `{}`", path))?;
+    let table = Table::<G>::read_from(BufReader::new(file)).with_context(|| format!("reading `{}`", path))?;
+
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    let mut found = false;
+    let mut ok = true;
+
+    for item in tripcodes {
+        let (unit, n, tripcode) = item?;
+        let hash = match str::from_utf8(&tripcode).ok().and_then(FourchanHash::decode) {
+            Some(hash) => hash,
+            None => {
+                stdout.flush().context(WRITING_STDOUT)?;
+                let _ = writeln!(io::stderr(), "{}: {} {}: invalid tripcode `{}`", program, unit, n,
+                    String::from_utf8_lossy(&tripcode));
+                ok = false;
+                continue;
+            },
+        };
+
+        if let Some(password) = table.lookup(&hash) {
+            stdout.write_all(&password)
+                .and_then(|()| stdout.write_all(b" #"))
+                .and_then(|()| stdout.write_all(&tripcode))
+                .and_then(|()| stdout.write_all(b"\n"))
+                .and_then(|()| stdout.flush())
+                .context(WRITING_STDOUT)?;
+            found = true;
+        }
+    }
+
+    if found && ok { Ok(()) } else { Err(Error::Failed) }
+}
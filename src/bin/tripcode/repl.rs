@@ -0,0 +1,278 @@
+// Copyright 2016 Huton. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The `repl` subcommand.
+
+use encoding::EncodingRef;
+use getopts::Options;
+use std::io::{self, Write};
+use std::result;
+use tripcode::*;
+use charset::{self, PasswordTranscoder};
+use error::{Context, Result, WRITING_STDOUT};
+use input::Records;
+
+const DEFAULT_TYPES: &[&str] = &["4chan", "2ch", "sc"];
+
+const COMMANDS: &str = "\
+:type <type>...       show tripcodes of the given types. prints the current types without arguments
+:salt [<XY>]          also show DES tripcodes with the salt characters. removes them without arguments
+:encoding [<label>]   transcode passwords from the encoding into the ones that the sites use.
+                      disables transcoding without arguments
+:help                 print this help message
+:quit                 exit
+
+Any other line is taken as a password. Start a password with `::` to begin it with `:`.
+";
+
+/// Runs the `repl` subcommand, which reads passwords and commands from stdin until EOF or
+/// `:quit`. The prompt is written to stderr so that the output can be piped.
+pub fn main(program: &str, args: &[String]) -> Result<()> {
+    let brief = format!("Usage: {} repl [options]\n\nCommands:\n{}", program, COMMANDS);
+
+    let mut opts = Options::new();
+    opts.optmulti("t", "type",           "type of tripcodes to show side by side. can be given \
+                                          multiple times. defaults to 4chan, 2ch and sc", "<type>")
+        .optopt(  "",  "salt",           "also show DES tripcodes with the salt characters", "<XY>")
+        .optopt(  "",  "input-encoding", "encoding of passwords to transcode from", "<encoding>")
+        .optflag( "h", "help",           "print this help message and exit");
+
+    macro_rules! fail {
+        ($($arg:tt)*) => (usage_error!(program, opts, &brief, $($arg)*));
+    }
+
+    let matches = match opts.parse(args) {
+        Ok(m) => m,
+        Err(f) => fail!("{}", f),
+    };
+
+    if matches.opt_present("h") {
+        return io::stdout().write_all(opts.usage(&brief).as_bytes()).context(WRITING_STDOUT);
+    }
+    if !matches.free.is_empty() {
+        fail!("unexpected argument `{}`", matches.free[0]);
+    }
+
+    let mut types = matches.opt_strs("t");
+    if types.is_empty() {
+        types = DEFAULT_TYPES.iter().map(|&t| t.to_owned()).collect();
+    }
+
+    let mut repl = Repl::new();
+    let set = repl.set_types(&types)
+        .and_then(|()| repl.set_salt(matches.opt_str("salt").as_deref()))
+        .and_then(|()| repl.set_encoding(matches.opt_str("input-encoding").as_deref()));
+    if let Err(message) = set {
+        fail!("{}", message);
+    }
+
+    repl.run(program)
+}
+
+/// Function that generates a tripcode from a password.
+type Generate = Box<dyn Fn(&[u8]) -> String>;
+
+/// Column of the output, which shows tripcodes of a type.
+struct Column {
+    name: String,
+    generate: Generate,
+}
+
+impl Column {
+    fn new(code_type: &str) -> Option<Self> {
+        let name = ::common::type_name(code_type)?;
+        let generate: Generate = match name {
+            "2ch-raw" => Box::new(|p| MonaRaw::try_generate(p).unwrap_or_else(|| "(invalid)".to_owned())),
+            _ => with_generator!(name, boxed_generator::<_>(), return None),
+        };
+        Some(Column { name: name.to_owned(), generate })
+    }
+
+    fn des(salt: [u8; 2]) -> Self {
+        Column {
+            name: "des".to_owned(),
+            generate: Box::new(move |p| Des::generate(p, salt[0], salt[1])),
+        }
+    }
+}
+
+fn boxed_generator<G: TripcodeGenerator + 'static>() -> Generate {
+    Box::new(|p| G::generate(p))
+}
+
+/// State of the REPL.
+struct Repl {
+    columns: Vec<Column>,
+    /// The column of DES tripcodes, which is shown after `columns`.
+    des: Option<Column>,
+    /// The encoding of passwords, or `None` to hash them as is.
+    encoding: Option<EncodingRef>,
+}
+
+impl Repl {
+    fn new() -> Self {
+        Repl { columns: Vec::new(), des: None, encoding: None }
+    }
+
+    fn set_types<S: AsRef<str>>(&mut self, types: &[S]) -> result::Result<(), String> {
+        if types.is_empty() {
+            return Err("no types given".to_owned());
+        }
+        let mut columns = Vec::with_capacity(types.len());
+        for t in types {
+            let t = t.as_ref();
+            match (t, Column::new(t)) {
+                ("des", _) => return Err("use `:salt` to show DES tripcodes".to_owned()),
+                (_, Some(c)) => columns.push(c),
+                (_, None) => return Err(format!("unknown tripcode type `{}`", t)),
+            }
+        }
+        self.columns = columns;
+        Ok(())
+    }
+
+    fn set_salt(&mut self, salt: Option<&str>) -> result::Result<(), String> {
+        self.des = match salt.map(str::as_bytes) {
+            Some(&[salt1, salt2]) => Some(Column::des([salt1, salt2])),
+            Some(_) => return Err("the salt must be 2 bytes long".to_owned()),
+            None => None,
+        };
+        Ok(())
+    }
+
+    fn set_encoding(&mut self, label: Option<&str>) -> result::Result<(), String> {
+        self.encoding = match label {
+            Some(label) => match charset::encoding_from_label(label) {
+                Some(e) => Some(e),
+                None => return Err(format!("unknown encoding `{}`", label)),
+            },
+            None => None,
+        };
+        Ok(())
+    }
+
+    /// Formats the tripcodes of `password` in every column, e.g.
+    /// `4chan: ozOtJW9BFA  2ch: ozOtJW9BFA`.
+    fn tripcodes(&self, password: &[u8]) -> String {
+        let mut line = String::new();
+        for c in self.columns.iter().chain(&self.des) {
+            if !line.is_empty() {
+                line.push_str("  ");
+            }
+            line.push_str(&c.name);
+            line.push_str(": ");
+            let transcoded = self.encoding.map(|e| {
+                PasswordTranscoder::new(e, charset::password_encoding(&c.name)).transcode(password)
+            });
+            match transcoded {
+                Some(Some(ref p)) => line.push_str(&(c.generate)(p)),
+                Some(None) => line.push_str("(not valid in the encoding)"),
+                None => line.push_str(&(c.generate)(password)),
+            }
+        }
+        line
+    }
+
+    /// Runs a command, i.e. a line without the leading `:`. Returns `false` to exit.
+    fn command<W: Write>(&mut self, program: &str, command: &str, out: &mut W) -> Result<bool> {
+        let mut words = command.split_whitespace();
+        let name = words.next().unwrap_or("");
+        let args: Vec<_> = words.collect();
+
+        let set = match (name, args.len()) {
+            ("type", 0) => {
+                let names: Vec<_> = self.columns.iter().map(|c| c.name.as_str()).collect();
+                writeln!(out, "{}", names.join(" ")).context(WRITING_STDOUT)?;
+                Ok(())
+            },
+            ("type", _) => self.set_types(&args),
+            ("salt", n) if n <= 1 => self.set_salt(args.first().cloned()),
+            ("encoding", n) if n <= 1 => self.set_encoding(args.first().cloned()),
+            ("help", 0) => {
+                out.write_all(COMMANDS.as_bytes()).context(WRITING_STDOUT)?;
+                Ok(())
+            },
+            ("quit", 0) => return Ok(false),
+            ("salt", _) | ("encoding", _) | ("help", _) | ("quit", _) => {
+                Err(format!("too many arguments for `:{}`", name))
+            },
+            _ => Err(format!("unknown command `:{}`. type `:help` for the list of commands", name)),
+        };
+
+        if let Err(message) = set {
+            out.flush().context(WRITING_STDOUT)?;
+            let _ = writeln!(io::stderr(), "{}: {}", program, message);
+        }
+        Ok(true)
+    }
+
+    fn run(&mut self, program: &str) -> Result<()> {
+        let stdin = io::stdin();
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+
+        let prompt = || {
+            let _ = write!(io::stderr(), "> ");
+        };
+
+        prompt();
+        for (i, line) in Records::new(stdin.lock(), b'\n').enumerate() {
+            let line = line.with_context(|| format!("reading standard input at line {}", i + 1))?;
+
+            if line.starts_with(b":") && !line.starts_with(b"::") {
+                let command = String::from_utf8_lossy(&line[1..]).into_owned();
+                if !self.command(program, &command, &mut stdout)? {
+                    return Ok(());
+                }
+            } else if !line.is_empty() {
+                let password = if line.starts_with(b"::") { &line[1..] } else { &line[..] };
+                writeln!(stdout, "{}", self.tripcodes(password)).context(WRITING_STDOUT)?;
+            }
+
+            stdout.flush().context(WRITING_STDOUT)?;
+            prompt();
+        }
+
+        // End the prompt line at EOF.
+        let _ = writeln!(io::stderr());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands() {
+        let mut repl = Repl::new();
+        repl.set_types(&["4", "2ch12", "2ch-raw"]).unwrap();
+        assert_eq!(format!("4chan: ozOtJW9BFA  2ch12: {}  2ch-raw: (invalid)", Mona12::generate("password")),
+            repl.tripcodes(b"password"));
+
+        let mut out = Vec::new();
+        assert!(repl.command("tripcode", "type sc-katakana", &mut out).unwrap());
+        assert!(repl.command("tripcode", "salt as", &mut out).unwrap());
+        assert!(repl.command("tripcode", "encoding euc-jp", &mut out).unwrap());
+        assert!(repl.command("tripcode", "type", &mut out).unwrap());
+        assert_eq!(b"sc-katakana\n", &out[..]);
+        // `$｡1008343131` in EUC-JP, which is transcoded into Shift-JIS for 2ch.sc and UTF-8 for DES.
+        assert_eq!(format!("sc-katakana: ﾃｽﾄ!ｹﾏﾜｬｴ･ｧﾎﾖｲﾎ  des: {}", Des::generate("$｡1008343131", b'a', b's')),
+            repl.tripcodes(b"$\x8E\xA11008343131"));
+
+        assert!(repl.command("tripcode", "type no-such-type", &mut out).unwrap());
+        assert!(repl.command("tripcode", "salt", &mut out).unwrap());
+        assert!(repl.command("tripcode", "encoding", &mut out).unwrap());
+        assert_eq!("sc-katakana: (not valid in the encoding)", {
+            repl.set_encoding(Some("utf-8")).unwrap();
+            repl.tripcodes(b"\xFF")
+        });
+        assert!(!repl.command("tripcode", "quit", &mut out).unwrap());
+    }
+}
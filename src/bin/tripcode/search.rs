@@ -0,0 +1,281 @@
+// Copyright 2016 Huton. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The `search` subcommand.
+
+use getopts::Options;
+use std::io::{self, Write};
+use std::result;
+use std::str::FromStr;
+use std::sync::mpsc::RecvTimeoutError;
+use std::time::{Duration, Instant};
+use tripcode::*;
+use tripcode::estimate;
+use tripcode::hash::TripcodeAlphabets;
+use tripcode::search::{Pattern, Regex, Search};
+use error::{Context, Error, Result, WRITING_STDOUT};
+use TYPE_DESC;
+
+/// How long `--estimate` runs the search to measure the hash rate.
+const MEASURE_TIME: Duration = Duration::from_secs(2);
+
+/// Runs the `search` subcommand, which fails with `Error::Failed` if nothing was found.
+pub fn main(program: &str, args: &[String]) -> Result<()> {
+    let brief = format!("Usage: {} search [options] \
+                         (--prefix <string> | --contains <string> | --regex <regex>)...",
+        program);
+
+    let mut opts = Options::new();
+    opts.optopt(  "t", "type",       TYPE_DESC, "<type>")
+        .optmulti("",  "prefix",     "search for tripcodes that start with <string>", "<string>")
+        .optmulti("",  "contains",   "search for tripcodes that contain <string>", "<string>")
+        .optmulti("",  "regex",      "search for tripcodes that contain a match of <regex>",
+                                     "<regex>")
+        .optopt(  "c", "charset",    "characters to make passwords from, e.g. `a-z0-9`. \
+                                      defaults to `./0-9A-Za-z`", "<chars>")
+        .optopt(  "l", "length",     "length of passwords in bytes. defaults to 8", "<n>")
+        .optopt(  "j", "threads",    "number of threads. defaults to the number of CPUs", "<n>")
+        .optopt(  "n", "limit",      "exit after finding <n> tripcodes", "<n>")
+        .optopt(  "",  "time-limit", "exit after <secs> seconds", "<secs>")
+        .optflag( "",  "estimate",   "print how long the search is expected to take, instead of \
+                                      searching")
+        .optflag( "h", "help",       "print this help message and exit");
+
+    macro_rules! fail {
+        ($($arg:tt)*) => (usage_error!(program, opts, &brief, $($arg)*));
+    }
+
+    let matches = match opts.parse(args) {
+        Ok(m) => m,
+        Err(f) => fail!("{}", f),
+    };
+
+    if matches.opt_present("h") {
+        return io::stdout().write_all(opts.usage(&brief).as_bytes()).context(WRITING_STDOUT);
+    }
+
+    macro_rules! parse_opt {
+        ($name:expr) => {
+            match matches.opt_str($name).map(|s| FromStr::from_str(&s)) {
+                Some(Ok(v)) => Some(v),
+                Some(Err(_)) => fail!("invalid value for `--{}`", $name),
+                None => None,
+            }
+        };
+    }
+
+    let mut patterns: Vec<_> = matches.opt_strs("prefix").into_iter().map(Pattern::Prefix).collect();
+    patterns.extend(matches.opt_strs("contains").into_iter().map(Pattern::Contains));
+    for regex in matches.opt_strs("regex") {
+        match Regex::new(&regex) {
+            Ok(r) => patterns.push(Pattern::Regex(r)),
+            Err(e) => fail!("invalid regex `{}`: {}", regex, e),
+        }
+    }
+    if patterns.is_empty() {
+        fail!("no patterns given");
+    }
+
+    let mut search = Search::new(patterns);
+    if let Some(charset) = matches.opt_str("charset") {
+        match expand_charset(charset.as_bytes()) {
+            Some(c) => search = search.charset(&c),
+            None => fail!("invalid charset `{}`", charset),
+        }
+    }
+    if let Some(len) = parse_opt!("length") {
+        search = search.password_len(len);
+    }
+    if let Some(threads) = parse_opt!("threads") {
+        search = search.threads(threads);
+    }
+    let limit: Option<u64> = parse_opt!("limit");
+    let time_limit = match parse_opt!("time-limit").map(Duration::try_from_secs_f64) {
+        Some(Ok(t)) => Some(t),
+        Some(Err(_)) => fail!("invalid value for `--time-limit`"),
+        None => None,
+    };
+
+    let code_type = matches.opt_str("t").unwrap_or_else(|| "4chan".to_owned());
+    if matches.opt_present("estimate") {
+        for name in &["limit", "time-limit"] {
+            if matches.opt_present(name) {
+                fail!("`--{}` is not valid with `--estimate`", name);
+            }
+        }
+        let estimate = with_generator!(code_type.as_str(), estimate::<_>(&search),
+            fail!("unknown or unsupported tripcode type `{}`", code_type));
+        return match estimate {
+            Ok(()) => Ok(()),
+            Err(EstimateError::Search(e)) => Err(e),
+            Err(EstimateError::Build(e)) => fail!("cannot estimate the patterns: {}", e),
+        };
+    }
+
+    let found = with_generator!(code_type.as_str(), run::<_>(search, limit, time_limit),
+        fail!("unknown or unsupported tripcode type `{}`", code_type))?;
+
+    if found > 0 { Ok(()) } else { Err(Error::Failed) }
+}
+
+/// Searches until the limits are reached or the keyspace is exhausted, printing each hit
+/// as `<password> #<tripcode>`. Returns the number of hits.
+fn run<G>(search: Search, limit: Option<u64>, time_limit: Option<Duration>) -> Result<u64>
+    where G: TripcodeGenerator + 'static
+{
+    let deadline = time_limit.map(|t| Instant::now() + t);
+    let searching = search.start::<G>();
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    let mut found = 0;
+
+    while limit.is_none_or(|l| found < l) {
+        let hit = match deadline {
+            Some(deadline) => {
+                let now = Instant::now();
+                if now >= deadline {
+                    break;
+                }
+                match searching.next_hit_timeout(deadline - now) {
+                    Ok(hit) => hit,
+                    Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => break,
+                }
+            },
+            None => match searching.next_hit() {
+                Some(hit) => hit,
+                None => break,
+            },
+        };
+
+        let written = stdout.write_all(&hit.password)
+            .and_then(|()| stdout.write_all(b" #"))
+            .and_then(|()| stdout.write_all(hit.tripcode.as_bytes()))
+            .and_then(|()| stdout.write_all(b"\n"))
+            .and_then(|()| stdout.flush());
+        if let Err(e) = written {
+            searching.stop();
+            return Err(e).context(WRITING_STDOUT);
+        }
+        found += 1;
+    }
+
+    searching.stop();
+    Ok(found)
+}
+
+/// Error of `estimate()`.
+enum EstimateError {
+    Search(Error),
+    Build(estimate::BuildError),
+}
+
+/// Measures the hash rate of `search` and prints the probability that a password matches,
+/// the expected time until the first hit and the times within which 50%, 90% and 99% of
+/// searches find one.
+///
+/// Fails with `Error::Failed` if no tripcode can match the patterns.
+fn estimate<G>(search: &Search) -> result::Result<(), EstimateError>
+    where G: TripcodeGenerator + 'static, G::Hash: TripcodeAlphabets
+{
+    let estimate = search.estimate::<G>().map_err(EstimateError::Build)?;
+    let p = estimate.probability();
+    if p == 0.0 {
+        let _ = writeln!(io::stderr(), "no tripcode can match the patterns");
+        return Err(EstimateError::Search(Error::Failed));
+    }
+    let rate = estimate::measure_rate::<G>(search.clone(), MEASURE_TIME);
+
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    let mut write = || -> io::Result<()> {
+        writeln!(stdout, "probability:      {:.3e} (1 in {:.0})", p, 1.0 / p)?;
+        writeln!(stdout, "hash rate:        {:.0} tripcodes/s", rate)?;
+        writeln!(stdout, "expected time:    {}", format_duration(estimate.expected_time(rate)))?;
+        for &q in &[0.5, 0.9, 0.99] {
+            writeln!(stdout, "{:>3}% within:      {}", q * 100.0,
+                format_duration(estimate.time_within(q, rate)))?;
+        }
+        if let Some(len) = search.keyspace_len() {
+            writeln!(stdout, "hits in keyspace: {:.3e} expected out of {}", estimate.expected_hits(len), len)?;
+        }
+        Ok(())
+    };
+    write().context(WRITING_STDOUT).map_err(EstimateError::Search)
+}
+
+/// Formats a duration for humans, in the two largest units, e.g. `3h 25m`.
+fn format_duration(duration: Option<Duration>) -> String {
+    const UNITS: [(&str, f64); 5] = [
+        ("y", 365.25 * 86400.0), ("d", 86400.0), ("h", 3600.0), ("m", 60.0), ("s", 1.0),
+    ];
+
+    let secs = match duration {
+        Some(d) => d.as_secs_f64(),
+        None => return "forever".to_owned(),
+    };
+    if secs < 1e-3 {
+        return format!("{:.1}µs", secs * 1e6);
+    }
+    if secs < 1.0 {
+        return format!("{:.1}ms", secs * 1e3);
+    }
+    if secs < 60.0 {
+        return format!("{:.2}s", secs);
+    }
+    if secs >= 1e6 * UNITS[0].1 {
+        return format!("{:.3e}y", secs / UNITS[0].1);
+    }
+    let i = UNITS.iter().position(|&(_, u)| secs >= u).unwrap();
+    let (major, minor) = (UNITS[i], UNITS[i + 1]);
+    let n = (secs / major.1).floor();
+    format!("{}{} {}{}", n, major.0, ((secs - n * major.1) / minor.1).floor(), minor.0)
+}
+
+/// Expands ranges such as `a-z` in a charset. A `-` at either end is taken literally.
+///
+/// Returns `None` if the charset is empty or contains a reversed range.
+pub fn expand_charset(spec: &[u8]) -> Option<Vec<u8>> {
+    let mut charset = Vec::new();
+    let mut i = 0;
+
+    while i < spec.len() {
+        if i + 2 < spec.len() && spec[i+1] == b'-' {
+            let (start, end) = (spec[i], spec[i+2]);
+            if start > end {
+                return None;
+            }
+            charset.extend(start..=end);
+            i += 3;
+        } else {
+            charset.push(spec[i]);
+            i += 1;
+        }
+    }
+
+    if charset.is_empty() { None } else { Some(charset) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations() {
+        let f = |secs: f64| format_duration(Some(Duration::from_secs_f64(secs)));
+        assert_eq!("12.0µs", f(12e-6));
+        assert_eq!("3.5ms", f(3.5e-3));
+        assert_eq!("59.50s", f(59.5));
+        assert_eq!("1m 0s", f(60.0));
+        assert_eq!("2h 5m", f(7500.0));
+        assert_eq!("3d 4h", f(3.0 * 86400.0 + 4.5 * 3600.0));
+        assert_eq!("2y 0d", f(2.0 * 365.25 * 86400.0 + 1.0));
+        assert_eq!("1.000e6y", f(1e6 * 365.25 * 86400.0));
+        assert_eq!("forever", format_duration(None));
+    }
+}
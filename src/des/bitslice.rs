@@ -0,0 +1,206 @@
+// Copyright 2016 Huton. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Bitsliced DES, which computes as many ciphers at once as a vector has bits.
+//!
+//! Each bit of the cipher state is held in a vector whose `j`th bit belongs to the `j`th
+//! cipher, so the permutations of DES cost nothing and the S-boxes are evaluated as boolean
+//! circuits. The tables below use the 1-based bit numbering of FIPS 46-3.
+
+use alloc::vec::Vec;
+use core::mem;
+use super::DES_ROUNDS;
+use super::sbox::*;
+
+/// Vector of bits that the ciphers are computed on.
+///
+/// # Safety
+///
+/// The methods may use instructions of target features that the CPU does not support, so they
+/// are `unsafe` to call: callers must make sure that the CPU supports the target features of
+/// the implementation. Implementations must not require anything else of the callers, and
+/// `LANES` must be a multiple of 64 no greater than `MAX_LANES`.
+pub unsafe trait Bits: Copy {
+    /// Number of bits in a vector, which is the number of ciphers computed at once.
+    const LANES: usize;
+
+    unsafe fn zero() -> Self;
+    unsafe fn ones() -> Self;
+    unsafe fn and(self, other: Self) -> Self;
+    unsafe fn or(self, other: Self) -> Self;
+    unsafe fn xor(self, other: Self) -> Self;
+    /// Returns `self & !other`.
+    unsafe fn andnot(self, other: Self) -> Self;
+
+    unsafe fn not(self) -> Self {
+        self.xor(Self::ones())
+    }
+
+    /// Loads a vector from `LANES / 64` words, where the `j`th bit of the vector is bit `j % 64`
+    /// of the `j / 64`th word.
+    unsafe fn load(words: &[u64]) -> Self;
+    /// Stores the vector into `LANES / 64` words in the layout that `load` reads.
+    unsafe fn store(self, words: &mut [u64]);
+}
+
+/// Largest `Bits::LANES` of the implementations.
+const MAX_LANES: usize = 512;
+
+const PC1: [u8; 56] = [
+    57, 49, 41, 33, 25, 17,  9,  1, 58, 50, 42, 34, 26, 18,
+    10,  2, 59, 51, 43, 35, 27, 19, 11,  3, 60, 52, 44, 36,
+    63, 55, 47, 39, 31, 23, 15,  7, 62, 54, 46, 38, 30, 22,
+    14,  6, 61, 53, 45, 37, 29, 21, 13,  5, 28, 20, 12,  4,
+];
+
+const PC2: [u8; 48] = [
+    14, 17, 11, 24,  1,  5,  3, 28, 15,  6, 21, 10,
+    23, 19, 12,  4, 26,  8, 16,  7, 27, 20, 13,  2,
+    41, 52, 31, 37, 47, 55, 30, 40, 51, 45, 33, 48,
+    44, 49, 39, 56, 34, 53, 46, 42, 50, 36, 29, 32,
+];
+
+const SHIFTS: [usize; 16] = [1, 1, 2, 2, 2, 2, 2, 2, 1, 2, 2, 2, 2, 2, 2, 1];
+
+const E: [u8; 48] = [
+    32,  1,  2,  3,  4,  5,  4,  5,  6,  7,  8,  9,
+     8,  9, 10, 11, 12, 13, 12, 13, 14, 15, 16, 17,
+    16, 17, 18, 19, 20, 21, 20, 21, 22, 23, 24, 25,
+    24, 25, 26, 27, 28, 29, 28, 29, 30, 31, 32,  1,
+];
+
+const P: [u8; 32] = [
+    16,  7, 20, 21, 29, 12, 28, 17,  1, 15, 23, 26,  5, 18, 31, 10,
+     2,  8, 24, 14, 32, 27,  3,  9, 19, 13, 30,  6, 22, 11,  4, 25,
+];
+
+const FP: [u8; 64] = [
+    40,  8, 48, 16, 56, 24, 64, 32, 39,  7, 47, 15, 55, 23, 63, 31,
+    38,  6, 46, 14, 54, 22, 62, 30, 37,  5, 45, 13, 53, 21, 61, 29,
+    36,  4, 44, 12, 52, 20, 60, 28, 35,  3, 43, 11, 51, 19, 59, 27,
+    34,  2, 42, 10, 50, 18, 58, 26, 33,  1, 41,  9, 49, 17, 57, 25,
+];
+
+/// The bit of the keyword, counted from 0, that each bit of each round key is.
+const KS: [[u8; 48]; 16] = {
+    let mut ks = [[0u8; 48]; 16];
+    let mut shift = 0;
+    let mut i = 0;
+    while i < 16 {
+        shift += SHIFTS[i];
+        let mut j = 0;
+        while j < 48 {
+            // The halves C and D of the permuted key rotate separately.
+            let b = PC2[j] as usize - 1;
+            let half = b / 28 * 28;
+            ks[i][j] = PC1[half + (b - half + shift) % 28] - 1;
+            j += 1;
+        }
+        i += 1;
+    }
+    ks
+};
+
+/// Computes `zero_cipher_58` for `V::LANES` pairs of keyword and salt at once and appends
+/// the results to `dst`.
+///
+/// # Safety
+///
+/// The CPU must support the target features of `V`.
+#[inline(always)]
+pub unsafe fn zero_cipher_58<V: Bits>(keys: &[(u64, u32)], dst: &mut Vec<u64>) {
+    debug_assert_eq!(V::LANES, keys.len());
+    let words = V::LANES / 64;
+    let mut buf = [0u64; MAX_LANES / 64];
+
+    let mut key = [V::zero(); 64];
+    for (i, key) in key.iter_mut().enumerate() {
+        // The parity bits are not used.
+        if i % 8 == 7 {
+            continue;
+        }
+        buf = [0; MAX_LANES / 64];
+        for (j, &(k, _)) in keys.iter().enumerate() {
+            buf[j / 64] |= (k >> (63 - i) & 1) << (j % 64);
+        }
+        *key = V::load(&buf[..words]);
+    }
+
+    // Bit `i` of the 12-bit salt swaps bits `i` and `i + 24` of the expanded half block.
+    // `decode_salt` puts the first salt character in bits 26 to 31 and the second in 18 to 23.
+    let mut salt = [V::zero(); 12];
+    let mut salted = 0u32;
+    for (i, salt) in salt.iter_mut().enumerate() {
+        let bit = if i < 6 { 26 + i } else { 12 + i };
+        buf = [0; MAX_LANES / 64];
+        for (j, &(_, s)) in keys.iter().enumerate() {
+            buf[j / 64] |= (s as u64 >> bit & 1) << (j % 64);
+            salted |= (s >> bit & 1) << i;
+        }
+        *salt = V::load(&buf[..words]);
+    }
+
+    let mut l = [V::zero(); 32];
+    let mut r = [V::zero(); 32];
+    for _ in 0..DES_ROUNDS {
+        for i in 0..8 {
+            round(&mut l, &r, &key, &KS[i << 1], &salt, salted);
+            round(&mut r, &l, &key, &KS[(i << 1) + 1], &salt, salted);
+        }
+        mem::swap(&mut l, &mut r);
+    }
+
+    // The bits of the cipher block, shifted left by 6 bits.
+    let mut ciphers = [0u64; MAX_LANES];
+    for (i, &b) in FP.iter().enumerate().skip(6) {
+        let b = b as usize - 1;
+        let v = if b < 32 { l[b] } else { r[b - 32] };
+        v.store(&mut buf[..words]);
+        for (j, c) in ciphers[..V::LANES].iter_mut().enumerate() {
+            *c |= (buf[j / 64] >> (j % 64) & 1) << (69 - i);
+        }
+    }
+    dst.extend_from_slice(&ciphers[..V::LANES]);
+}
+
+// One half-round of the cipher: mixes `r` with the round key of bits `ks` and folds the S-box
+// output into `l`. `salted` has the bits of the salt that are set in any of the ciphers.
+//
+// The CPU must support the target features of `V`.
+#[inline(always)]
+unsafe fn round<V: Bits>(l: &mut [V; 32], r: &[V; 32], key: &[V; 64], ks: &[u8; 48], salt: &[V; 12], salted: u32) {
+    let mut e = [V::zero(); 48];
+    for (e, &b) in e.iter_mut().zip(&E) {
+        *e = r[b as usize - 1];
+    }
+    for (i, salt) in salt.iter().enumerate() {
+        if salted >> i & 1 != 0 {
+            let t = e[i].xor(e[i + 24]).and(*salt);
+            e[i] = e[i].xor(t);
+            e[i + 24] = e[i + 24].xor(t);
+        }
+    }
+    for (e, &k) in e.iter_mut().zip(ks) {
+        *e = e.xor(key[k as usize]);
+    }
+
+    let mut s = [V::zero(); 32];
+    macro_rules! sbox {
+        ($($i:expr => $f:ident),*) => {$({
+            let e = &e[6 * $i..];
+            let o = $f(e[0], e[1], e[2], e[3], e[4], e[5]);
+            s[4 * $i..4 * $i + 4].copy_from_slice(&o);
+        })*};
+    }
+    sbox!(0 => s1, 1 => s2, 2 => s3, 3 => s4, 4 => s5, 5 => s6, 6 => s7, 7 => s8);
+
+    for (l, &b) in l.iter_mut().zip(&P) {
+        *l = l.xor(s[b as usize - 1]);
+    }
+}
@@ -31,6 +31,56 @@
 // ---
 // Modified in August 2016 by Huton.
 
+//! The DES-based `crypt(3)` that the 10-character tripcodes are made of.
+//!
+//! The functions take the key as a `keyword`, made of the first 8 bytes of a password by
+//! `secret_to_key`, and the 12-bit salt as made of 2 salt characters by `decode_salt`.
+//! They return the 64-bit cipher block shifted left by 6 bits, whose upper 58 bits are what
+//! tripcodes encode.
+//!
+//! ```
+//! use tripcode::des::*;
+//! use tripcode::{Des, TripcodeGenerator};
+//! use tripcode::hash::FourchanHash;
+//!
+//! let mut schedule = KeySchedule::new(secret_to_key(b"password"));
+//! let salt = decode_salt(b'a', b's');
+//! assert_eq!(FourchanHash(zero_cipher_58_with_schedule(&schedule, salt)), Des::hash("password", b'a', b's'));
+//!
+//! // Only the round keys that depend on the last byte are updated.
+//! schedule.set_byte(7, b'e' << 1);
+//! assert_eq!(FourchanHash(zero_cipher_58_with_schedule(&schedule, salt)), Des::hash("passwore", b'a', b's'));
+//! ```
+//!
+//! # Backends
+//!
+//! `zero_cipher_58_many` runs on one of several implementations, the `Backend`s. Besides the
+//! table-driven one that the other functions use, there are bitsliced ones that compute 128, 256
+//! or 512 ciphers at once with SSE2, AVX2 or AVX-512 on x86. The fastest one that the CPU supports
+//! is selected on first use, unless the `TRIPCODE_DES_BACKEND` environment variable names
+//! another supported one (only with the `std` feature). `set_backend` overrides the selection.
+//!
+//! ```
+//! use tripcode::des::*;
+//!
+//! let keys = [(secret_to_key(b"password"), decode_salt(b'a', b's'))];
+//! let mut ciphers = Vec::new();
+//! for &backend in Backend::ALL.iter().filter(|b| b.is_supported()) {
+//!     zero_cipher_58_many_with_backend(backend, keys.iter().cloned(), &mut ciphers);
+//! }
+//! assert!(ciphers.iter().all(|&c| c == zero_cipher_58(keys[0].0, keys[0].1)));
+//! ```
+
+use alloc::vec::Vec;
+use core::mem;
+use core::sync::atomic::{AtomicUsize, Ordering};
+pub use util::{decode_salt, secret_to_key};
+
+mod bitslice;
+mod sbox;
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+mod x86;
+
 const PC1ROT: [[u64; 16]; 16] = [
     [ 0x0000000000000000, 0x0000000000000000, 0x0000010000000000, 0x0000010000000000,
       0x0000000100000000, 0x0000000100000000, 0x0000010100000000, 0x0000010100000000,
@@ -434,71 +484,419 @@ const SPE: [[u64; 64]; 8] = [
 
 const DES_ROUNDS: u32 = 25;
 
-#[allow(non_snake_case)]
+/// Number of ciphers computed side by side by `zero_cipher_58_lanes`.
+pub const LANES: usize = 4;
+
+const fn perm6464(mut c: u64, p: &[[u64; 16]; 16]) -> u64 {
+       p[14][{         c & 0xF} as usize]
+     | p[15][{c >>= 4; c & 0xF} as usize]
+     | p[12][{c >>= 4; c & 0xF} as usize]
+     | p[13][{c >>= 4; c & 0xF} as usize]
+     | p[10][{c >>= 4; c & 0xF} as usize]
+     | p[11][{c >>= 4; c & 0xF} as usize]
+     | p[ 8][{c >>= 4; c & 0xF} as usize]
+     | p[ 9][{c >>= 4; c & 0xF} as usize]
+     | p[ 6][{c >>= 4; c & 0xF} as usize]
+     | p[ 7][{c >>= 4; c & 0xF} as usize]
+     | p[ 4][{c >>= 4; c & 0xF} as usize]
+     | p[ 5][{c >>= 4; c & 0xF} as usize]
+     | p[ 2][{c >>= 4; c & 0xF} as usize]
+     | p[ 3][{c >>= 4; c & 0xF} as usize]
+     | p[ 0][{c >>= 4; c & 0xF} as usize]
+     | p[ 1][{c >>= 4; c      } as usize]
+}
+
+/// Computes the 16 round keys for `keyword`.
+const fn key_schedule(keyword: u64) -> [u64; 16] {
+    let mut ks = [0u64; 16];
+    let mut k = perm6464(keyword, &PC1ROT);
+
+    ks[0] = k & !0x0303030300000000;
+    let mut i = 1;
+    while i < 16 {
+        k = perm6464(k, &PC2ROT[Rotates[i]]);
+        ks[i] = k & !0x0303030300000000;
+        i += 1;
+    }
+
+    ks
+}
+
+/// The round keys of each bit of the keyword alone, where the `i`th element is for `1 << i`.
+///
+/// The key schedule only permutes and masks bits, so the round keys of a keyword are
+/// the exclusive or of the round keys of its bits.
+const BIT_SCHEDULES: [[u64; 16]; 64] = {
+    let mut ret = [[0u64; 16]; 64];
+    let mut i = 0;
+    while i < 64 {
+        ret[i] = key_schedule(1 << i);
+        i += 1;
+    }
+    ret
+};
+
+/// The 16 round keys of a keyword, which can be updated incrementally.
+///
+/// Updating the keyword only recomputes the part of the round keys that depends on the changed
+/// bits, which is much cheaper than computing them from scratch when consecutive passwords share
+/// all but the last few bytes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct KeySchedule {
+    keyword: u64,
+    keys: [u64; 16],
+}
+
+impl KeySchedule {
+    /// Computes the round keys of `keyword`.
+    pub fn new(keyword: u64) -> Self {
+        KeySchedule { keyword, keys: key_schedule(keyword) }
+    }
+
+    /// Returns the keyword that the round keys are computed from.
+    pub fn keyword(&self) -> u64 {
+        self.keyword
+    }
+
+    /// Replaces the keyword with `keyword`, updating the round keys of the changed bits.
+    pub fn set_keyword(&mut self, keyword: u64) {
+        let mut changed = self.keyword ^ keyword;
+        while changed != 0 {
+            let bit = &BIT_SCHEDULES[changed.trailing_zeros() as usize];
+            for (k, b) in self.keys.iter_mut().zip(bit) {
+                *k ^= b;
+            }
+            changed &= changed - 1;
+        }
+        self.keyword = keyword;
+    }
+
+    /// Replaces the `i`th byte of the keyword, counted from the most significant byte, with
+    /// `byte`, which holds a character of the password shifted left by one bit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is not less than 8.
+    pub fn set_byte(&mut self, i: usize, byte: u8) {
+        assert!(i < 8, "byte index out of range: {}", i);
+        let shift = 56 - 8 * i;
+        let keyword = self.keyword & !(0xFF << shift) | (byte as u64) << shift;
+        self.set_keyword(keyword);
+    }
+}
+
+// One half-round of the cipher: mixes `$r` with round key `$kp` and folds the S-box output
+// into `$l`.
+macro_rules! f {
+    ($l:expr, $r:expr, $kp:expr, $salt:expr) => {{
+        let mut k = (($r >> 32) ^ $r) & $salt as u64;
+        k |= k << 32;
+        let b = k ^ $r ^ $kp;
+        $l ^=  SPE[0][ (b >> 58)         as usize]
+             ^ SPE[1][((b >> 50) & 0x3F) as usize]
+             ^ SPE[2][((b >> 42) & 0x3F) as usize]
+             ^ SPE[3][((b >> 34) & 0x3F) as usize]
+             ^ SPE[4][((b >> 26) & 0x3F) as usize]
+             ^ SPE[5][((b >> 18) & 0x3F) as usize]
+             ^ SPE[6][((b >> 10) & 0x3F) as usize]
+             ^ SPE[7][((b >>  2) & 0x3F) as usize];
+    }};
+}
+
+// Applies the final permutation to the cipher block.
+fn finish(l: u64, r: u64) -> u64 {
+    let l = (((l >> 35) & 0x0F0F0F0F) | (((l & 0xFFFFFFFF) << 1) & 0xF0F0F0F0)) << 32
+          | (((r >> 35) & 0x0F0F0F0F) | (((r & 0xFFFFFFFF) << 1) & 0xF0F0F0F0));
+
+    perm6464(l, &CF6464) << 6
+}
+
+/// Encrypts a zero block 25 times with `keyword` and `salt`, as `crypt(3)` does.
 pub fn zero_cipher_58(keyword: u64, salt: u32) -> u64 {
-	let mut KS = [0u64; 16];
-	let mut K = perm6464(keyword, &PC1ROT);
-
-    KS[0] = K & !0x0303030300000000;
-	for i in 1..16 {
-	    K = perm6464(K, &PC2ROT[Rotates[i]]);
-	    KS[i] = K & !0x0303030300000000;
-	}
-
-    fn perm6464(mut c: u64, p: &[[u64; 16]; 16]) -> u64 {
-           p[14][{         c & 0xF} as usize]
-         | p[15][{c >>= 4; c & 0xF} as usize]
-         | p[12][{c >>= 4; c & 0xF} as usize]
-         | p[13][{c >>= 4; c & 0xF} as usize]
-         | p[10][{c >>= 4; c & 0xF} as usize]
-         | p[11][{c >>= 4; c & 0xF} as usize]
-         | p[ 8][{c >>= 4; c & 0xF} as usize]
-         | p[ 9][{c >>= 4; c & 0xF} as usize]
-         | p[ 6][{c >>= 4; c & 0xF} as usize]
-         | p[ 7][{c >>= 4; c & 0xF} as usize]
-         | p[ 4][{c >>= 4; c & 0xF} as usize]
-         | p[ 5][{c >>= 4; c & 0xF} as usize]
-         | p[ 2][{c >>= 4; c & 0xF} as usize]
-         | p[ 3][{c >>= 4; c & 0xF} as usize]
-         | p[ 0][{c >>= 4; c & 0xF} as usize]
-         | p[ 1][{c >>= 4; c      } as usize]
+    zero_cipher_58_with_schedule(&KeySchedule::new(keyword), salt)
+}
+
+/// Same as `zero_cipher_58` but with precomputed round keys.
+pub fn zero_cipher_58_with_schedule(schedule: &KeySchedule, salt: u32) -> u64 {
+    let ks = &schedule.keys;
+
+    let mut r = 0u64;
+    let mut l = 0u64;
+    for _ in 0..DES_ROUNDS {
+        for i in 0..8 {
+            f!(l, r, ks[i << 1], salt);
+            f!(r, l, ks[(i << 1) + 1], salt);
+        }
+        mem::swap(&mut l, &mut r);
+    }
+
+    finish(l, r)
+}
+
+/// Same as `zero_cipher_58` but computes `LANES` ciphers at once.
+///
+/// The lanes are independent of each other, so interleaving them lets the CPU overlap the
+/// table lookups of one lane with those of the others.
+pub fn zero_cipher_58_lanes(keywords: &[u64; LANES], salts: &[u32; LANES]) -> [u64; LANES] {
+    let mut ks = [[0u64; 16]; LANES];
+    for (ks, &k) in ks.iter_mut().zip(keywords) {
+        *ks = key_schedule(k);
     }
 
-    let mut R = 0u64;
-    let mut L = 0u64;
+    let mut r = [0u64; LANES];
+    let mut l = [0u64; LANES];
     for _ in 0..DES_ROUNDS {
-    	for loop_count in 0..8 {
-            let kp = KS[loop_count << 1];
-    	    let mut k = ((R >> 32) ^ R) & salt as u64;
-    	    k |= k << 32;
-    	    let B = k ^ R ^ kp;
-    	    L ^=  SPE[0][ (B >> 58)         as usize]
-                ^ SPE[1][((B >> 50) & 0x3F) as usize]
-                ^ SPE[2][((B >> 42) & 0x3F) as usize]
-                ^ SPE[3][((B >> 34) & 0x3F) as usize]
-                ^ SPE[4][((B >> 26) & 0x3F) as usize]
-                ^ SPE[5][((B >> 18) & 0x3F) as usize]
-                ^ SPE[6][((B >> 10) & 0x3F) as usize]
-                ^ SPE[7][((B >>  2) & 0x3F) as usize];
-    	    let kp = KS[(loop_count << 1) + 1];
-    	    k = ((L >> 32) ^ L) & salt as u64;
-    	    k |= k << 32;
-    	    let B = k ^ L ^ kp;
-    	    R ^=  SPE[0][ (B >> 58)         as usize]
-                ^ SPE[1][((B >> 50) & 0x3F) as usize]
-                ^ SPE[2][((B >> 42) & 0x3F) as usize]
-                ^ SPE[3][((B >> 34) & 0x3F) as usize]
-                ^ SPE[4][((B >> 26) & 0x3F) as usize]
-                ^ SPE[5][((B >> 18) & 0x3F) as usize]
-                ^ SPE[6][((B >> 10) & 0x3F) as usize]
-                ^ SPE[7][((B >>  2) & 0x3F) as usize];
-    	}
-    	L ^= R;
-    	R ^= L;
-    	L ^= R;
+        for i in 0..8 {
+            for j in 0..LANES {
+                f!(l[j], r[j], ks[j][i << 1], salts[j]);
+            }
+            for j in 0..LANES {
+                f!(r[j], l[j], ks[j][(i << 1) + 1], salts[j]);
+            }
+        }
+        mem::swap(&mut l, &mut r);
+    }
+
+    let mut ret = [0u64; LANES];
+    for j in 0..LANES {
+        ret[j] = finish(l[j], r[j]);
+    }
+    ret
+}
+
+/// Computes `zero_cipher_58` for each pair of keyword and salt and appends the results to `dst`,
+/// with the selected backend.
+pub fn zero_cipher_58_many<I>(keys: I, dst: &mut Vec<u64>) where I: IntoIterator<Item=(u64, u32)> {
+    zero_cipher_58_many_with_backend(backend(), keys, dst);
+}
+
+/// Same as `zero_cipher_58_many` but with `backend`.
+///
+/// Keys that do not fill the vectors of a bitsliced backend are handled by the slower ones.
+///
+/// # Panics
+///
+/// Panics if the CPU does not support `backend`.
+pub fn zero_cipher_58_many_with_backend<I>(backend: Backend, keys: I, dst: &mut Vec<u64>)
+    where I: IntoIterator<Item=(u64, u32)>
+{
+    assert!(backend.is_supported(), "unsupported DES backend: {}", backend.name());
+
+    // Long enough to fill the vectors of every backend.
+    const BATCH_LEN: usize = 512;
+
+    let mut keys = keys.into_iter();
+    let mut batch = [(0u64, 0u32); BATCH_LEN];
+    loop {
+        let mut n = 0;
+        for (b, k) in batch.iter_mut().zip(keys.by_ref()) {
+            *b = k;
+            n += 1;
+        }
+
+        let mut rest = &batch[..n];
+        for &b in Backend::ALL[..backend as usize + 1].iter().rev() {
+            if b.is_supported() {
+                rest = b.zero_cipher_58_batch(rest, dst);
+            }
+        }
+        debug_assert!(rest.is_empty());
+
+        if n < BATCH_LEN {
+            return;
+        }
+    }
+}
+
+// The table-driven batch routine, which interleaves `LANES` ciphers.
+fn zero_cipher_58_table(keys: &[(u64, u32)], dst: &mut Vec<u64>) {
+    let mut chunks = keys.chunks_exact(LANES);
+    for chunk in &mut chunks {
+        let (mut keywords, mut salts) = ([0u64; LANES], [0u32; LANES]);
+        for (j, &(k, s)) in chunk.iter().enumerate() {
+            keywords[j] = k;
+            salts[j] = s;
+        }
+        dst.extend_from_slice(&zero_cipher_58_lanes(&keywords, &salts));
+    }
+    dst.extend(chunks.remainder().iter().map(|&(k, s)| zero_cipher_58(k, s)));
+}
+
+/// An implementation of `zero_cipher_58_many`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Backend {
+    /// The table-driven implementation, which runs on any CPU.
+    Table,
+    /// Bitsliced implementation with SSE2, which computes 128 ciphers at once.
+    Sse2,
+    /// Bitsliced implementation with AVX2, which computes 256 ciphers at once.
+    Avx2,
+    /// Bitsliced implementation with AVX-512 (AVX-512F), which computes 512 ciphers at once.
+    Avx512,
+}
+
+impl Backend {
+    /// All the backends, from the slowest to the fastest.
+    pub const ALL: [Backend; 4] = [Backend::Table, Backend::Sse2, Backend::Avx2, Backend::Avx512];
+
+    /// Returns the name of the backend, which is what `TRIPCODE_DES_BACKEND` takes.
+    pub fn name(self) -> &'static str {
+        match self {
+            Backend::Table  => "table",
+            Backend::Sse2   => "sse2",
+            Backend::Avx2   => "avx2",
+            Backend::Avx512 => "avx512",
+        }
+    }
+
+    /// Returns the backend of the name returned by `name()`, ignoring ASCII case.
+    pub fn from_name(name: &str) -> Option<Backend> {
+        Backend::ALL.iter().cloned().find(|b| b.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Returns `true` if the CPU supports the backend.
+    ///
+    /// Without the `std` feature, only the CPU features enabled at compile time are detected.
+    pub fn is_supported(self) -> bool {
+        match self {
+            Backend::Table => true,
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Backend::Sse2 => x86::has_sse2(),
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Backend::Avx2 => x86::has_avx2(),
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Backend::Avx512 => x86::has_avx512(),
+            #[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+            _ => false,
+        }
+    }
+
+    /// Returns the fastest backend that the CPU supports.
+    pub fn fastest() -> Backend {
+        *Backend::ALL.iter().rev().find(|b| b.is_supported()).unwrap()
     }
-    L = (((L >> 35) & 0x0F0F0F0F) | (((L & 0xFFFFFFFF) << 1) & 0xF0F0F0F0)) << 32
-      | (((R >> 35) & 0x0F0F0F0F) | (((R & 0xFFFFFFFF) << 1) & 0xF0F0F0F0));
 
-    perm6464(L, &CF6464) << 6
+    // Computes the ciphers of as many of `keys` as the backend handles at once, appends them to
+    // `dst` and returns the rest of the keys. The backend must be supported.
+    fn zero_cipher_58_batch<'a>(self, keys: &'a [(u64, u32)], dst: &mut Vec<u64>) -> &'a [(u64, u32)] {
+        match self {
+            Backend::Table => {
+                zero_cipher_58_table(keys, dst);
+                &[]
+            },
+            // Safe because the callers have checked that the CPU supports the backend.
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Backend::Sse2 => unsafe { x86::zero_cipher_58_sse2(keys, dst) },
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Backend::Avx2 => unsafe { x86::zero_cipher_58_avx2(keys, dst) },
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Backend::Avx512 => unsafe { x86::zero_cipher_58_avx512(keys, dst) },
+            #[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+            _ => keys,
+        }
+    }
+}
+
+// One more than the index of the selected backend in `Backend::ALL`, or 0 until one is selected.
+static SELECTED: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns the backend that `zero_cipher_58_many` runs on.
+///
+/// On first use, this selects the backend named by the `TRIPCODE_DES_BACKEND` environment
+/// variable if the CPU supports it, and the fastest supported one otherwise.
+pub fn backend() -> Backend {
+    match SELECTED.load(Ordering::Relaxed) {
+        0 => {
+            let backend = default_backend();
+            SELECTED.store(backend as usize + 1, Ordering::Relaxed);
+            backend
+        },
+        i => Backend::ALL[i - 1],
+    }
+}
+
+/// Makes `zero_cipher_58_many` run on `backend`.
+///
+/// # Panics
+///
+/// Panics if the CPU does not support `backend`.
+pub fn set_backend(backend: Backend) {
+    assert!(backend.is_supported(), "unsupported DES backend: {}", backend.name());
+    SELECTED.store(backend as usize + 1, Ordering::Relaxed);
+}
+
+#[cfg(feature = "std")]
+fn default_backend() -> Backend {
+    std::env::var("TRIPCODE_DES_BACKEND").ok()
+        .and_then(|name| Backend::from_name(&name))
+        .filter(|b| b.is_supported())
+        .unwrap_or_else(Backend::fastest)
+}
+
+#[cfg(not(feature = "std"))]
+fn default_backend() -> Backend {
+    Backend::fastest()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_schedule_updates() {
+        let mut schedule = KeySchedule::new(0);
+        let mut keyword = 0u64;
+        // Walks through keywords like an enumeration of passwords does, and then jumps around.
+        for n in 0..0x300u64 {
+            keyword = keyword & !0xFFFF | (n << 1 & 0xFE) | (n >> 7 << 9 & 0xFE00);
+            schedule.set_byte(7, (n << 1) as u8);
+            schedule.set_byte(6, (n >> 7 << 1) as u8);
+            assert_eq!(KeySchedule::new(keyword), schedule);
+        }
+        for &k in &[0xFEFE_FEFE_FEFE_FEFE, 0x1234_5678_9ABC_DEF0, 0] {
+            schedule.set_keyword(k);
+            assert_eq!(KeySchedule::new(k), schedule);
+            assert_eq!(zero_cipher_58(k, 0x0A4C_0000), zero_cipher_58_with_schedule(&schedule, 0x0A4C_0000));
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn backends() {
+        // Pseudorandom keywords and salts, where the salts take every 12-bit value.
+        let mut x = 0x0123_4567_89AB_CDEFu64;
+        let keys: Vec<(u64, u32)> = (0..4096u32).map(|i| {
+            x = x.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (x & 0xFEFE_FEFE_FEFE_FEFE, (i & 0x3F) << 26 | (i >> 6) << 18)
+        }).collect();
+        let expected: Vec<u64> = keys.iter().map(|&(k, s)| zero_cipher_58(k, s)).collect();
+
+        for &backend in Backend::ALL.iter().filter(|b| b.is_supported()) {
+            let mut ciphers = Vec::new();
+            zero_cipher_58_many_with_backend(backend, keys.iter().cloned(), &mut ciphers);
+            assert!(expected == ciphers, "{:?}", backend);
+
+            // Covers the keys left over for the slower backends, and a batch of a single salt.
+            for &len in &[0, 1, 3, 129, 383, 513, 1000] {
+                let mut ciphers = Vec::new();
+                zero_cipher_58_many_with_backend(backend, keys[..len].iter().cloned(), &mut ciphers);
+                assert!(expected[..len] == ciphers[..], "{:?} with {} keys", backend, len);
+            }
+            let salt = decode_salt(b'a', b's');
+            let mut ciphers = Vec::new();
+            zero_cipher_58_many_with_backend(backend, keys[..600].iter().map(|&(k, _)| (k, salt)), &mut ciphers);
+            assert!(keys[..600].iter().zip(ciphers).all(|(&(k, _), c)| zero_cipher_58(k, salt) == c), "{:?}", backend);
+        }
+    }
+
+    #[test]
+    fn backend_names() {
+        for &backend in &Backend::ALL {
+            assert_eq!(Some(backend), Backend::from_name(backend.name()));
+        }
+        assert_eq!(Some(Backend::Avx512), Backend::from_name("AVX512"));
+        assert_eq!(None, Backend::from_name("avx"));
+        assert!(Backend::fastest().is_supported());
+        assert!(backend().is_supported());
+    }
 }
@@ -0,0 +1,657 @@
+// Copyright 2016 Huton. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The S-boxes of DES as boolean circuits for the bitsliced implementation.
+//!
+//! Each function takes the 6 input bits of an S-box, most significant first, and returns its
+//! 4 output bits in the same order. The circuits were generated from the S-box tables by
+//! Shannon expansion, reusing the subfunctions that the outputs have in common, with the order
+//! of the variables that needs the fewest gates.
+//!
+//! Like the methods of `Bits`, the functions are `unsafe` to call because the CPU must support
+//! the target features of `V`.
+
+use super::bitslice::Bits;
+
+#[inline(always)]
+pub unsafe fn s1<V: Bits>(a1: V, a2: V, a3: V, a4: V, a5: V, a6: V) -> [V; 4] {
+    let x0 = a6.not();
+    let x1 = x0.xor(a2);
+    let x2 = x1.xor(a5);
+    let x3 = a6.xor(x1);
+    let x4 = x3.and(a3);
+    let x5 = x2.xor(x4);
+    let x6 = a6.and(a5);
+    let x7 = x2.and(a3);
+    let x8 = x6.xor(x7);
+    let x9 = x8.and(a4);
+    let x10 = x5.xor(x9);
+    let x11 = x0.or(x3);
+    let x12 = x11.and(a5);
+    let x13 = a2.xor(x12);
+    let x14 = x13.andnot(a3);
+    let x15 = x3.xor(x14);
+    let x16 = x0.andnot(a2);
+    let x17 = a2.and(a5);
+    let x18 = x16.xor(x17);
+    let x19 = a5.xor(x16);
+    let x20 = x19.and(a3);
+    let x21 = x18.xor(x20);
+    let x22 = x21.and(a4);
+    let x23 = x15.xor(x22);
+    let x24 = x23.and(a1);
+    let x25 = x10.xor(x24);
+    let x26 = x0.xor(x16);
+    let x27 = a2.xor(a6);
+    let x28 = x27.and(a5);
+    let x29 = x26.xor(x28);
+    let x30 = x13.xor(x26);
+    let x31 = x30.and(a3);
+    let x32 = x29.xor(x31);
+    let x33 = x11.xor(x17);
+    let x34 = x33.and(a4);
+    let x35 = x32.xor(x34);
+    let x36 = x16.and(a5);
+    let x37 = a6.xor(x36);
+    let x38 = x3.xor(x12);
+    let x39 = x38.and(a3);
+    let x40 = x37.xor(x39);
+    let x41 = x6.xor(x19);
+    let x42 = x41.andnot(a3);
+    let x43 = x29.xor(x42);
+    let x44 = x43.and(a4);
+    let x45 = x40.xor(x44);
+    let x46 = x45.and(a1);
+    let x47 = x35.xor(x46);
+    let x48 = a5.xor(x41);
+    let x49 = x27.xor(x41);
+    let x50 = x49.and(a3);
+    let x51 = x48.xor(x50);
+    let x52 = x2.xor(x33);
+    let x53 = a6.xor(x6);
+    let x54 = x53.and(a3);
+    let x55 = x52.xor(x54);
+    let x56 = x55.and(a4);
+    let x57 = x51.xor(x56);
+    let x58 = x19.xor(x37);
+    let x59 = x58.and(a3);
+    let x60 = x52.xor(x59);
+    let x61 = a6.xor(x48);
+    let x62 = x61.and(a3);
+    let x63 = x12.xor(x62);
+    let x64 = x63.and(a4);
+    let x65 = x60.xor(x64);
+    let x66 = x65.and(a1);
+    let x67 = x57.xor(x66);
+    let x68 = x52.andnot(a3);
+    let x69 = x3.xor(x68);
+    let x70 = x12.xor(x61);
+    let x71 = x16.and(a3);
+    let x72 = x70.xor(x71);
+    let x73 = x72.and(a4);
+    let x74 = x69.xor(x73);
+    let x75 = x26.andnot(a5);
+    let x76 = x75.and(a3);
+    let x77 = x70.xor(x76);
+    let x78 = x62.xor(x75);
+    let x79 = x78.and(a4);
+    let x80 = x77.xor(x79);
+    let x81 = x80.and(a1);
+    let x82 = x74.xor(x81);
+    [x25, x67, x82, x47]
+}
+
+#[inline(always)]
+pub unsafe fn s2<V: Bits>(a1: V, a2: V, a3: V, a4: V, a5: V, a6: V) -> [V; 4] {
+    let x0 = a4.not();
+    let x1 = a4.andnot(a6);
+    let x2 = x1.and(a3);
+    let x3 = x0.xor(x2);
+    let x4 = x0.xor(x1);
+    let x5 = a6.and(a3);
+    let x6 = x4.xor(x5);
+    let x7 = x6.and(a2);
+    let x8 = x3.xor(x7);
+    let x9 = a3.not();
+    let x10 = x1.or(x9);
+    let x11 = x0.andnot(a6);
+    let x12 = x0.and(a3);
+    let x13 = x11.xor(x12);
+    let x14 = x13.and(a2);
+    let x15 = x10.xor(x14);
+    let x16 = x15.and(a1);
+    let x17 = x8.xor(x16);
+    let x18 = x12.not();
+    let x19 = x0.xor(x11);
+    let x20 = x19.and(a2);
+    let x21 = x18.xor(x20);
+    let x22 = x4.xor(x13);
+    let x23 = a3.xor(x6);
+    let x24 = x23.and(a2);
+    let x25 = x22.xor(x24);
+    let x26 = x25.and(a1);
+    let x27 = x21.xor(x26);
+    let x28 = x27.and(a5);
+    let x29 = x17.xor(x28);
+    let x30 = a6.xor(x9);
+    let x31 = x11.xor(x23);
+    let x32 = x31.and(a2);
+    let x33 = x30.xor(x32);
+    let x34 = x0.xor(x31);
+    let x35 = a2.not();
+    let x36 = x34.or(x35);
+    let x37 = x36.and(a1);
+    let x38 = x33.xor(x37);
+    let x39 = x0.or(a2);
+    let x40 = a4.xor(x19);
+    let x41 = x40.andnot(a2);
+    let x42 = x5.xor(x41);
+    let x43 = x42.and(a1);
+    let x44 = x39.xor(x43);
+    let x45 = x44.and(a5);
+    let x46 = x38.xor(x45);
+    let x47 = x1.xor(x23);
+    let x48 = a6.and(a2);
+    let x49 = x47.xor(x48);
+    let x50 = x7.xor(x34);
+    let x51 = x50.and(a1);
+    let x52 = x49.xor(x51);
+    let x53 = x4.xor(x9);
+    let x54 = x0.xor(x23);
+    let x55 = x54.and(a2);
+    let x56 = x53.xor(x55);
+    let x57 = x1.xor(x31);
+    let x58 = a3.xor(x34);
+    let x59 = x58.and(a2);
+    let x60 = x57.xor(x59);
+    let x61 = x60.and(a1);
+    let x62 = x56.xor(x61);
+    let x63 = x62.and(a5);
+    let x64 = x52.xor(x63);
+    let x65 = x0.xor(x53);
+    let x66 = x65.andnot(a2);
+    let x67 = x57.xor(x66);
+    let x68 = x67.xor(a1);
+    let x69 = x4.or(a3);
+    let x70 = x55.and(a1);
+    let x71 = x69.xor(x70);
+    let x72 = x71.and(a5);
+    let x73 = x68.xor(x72);
+    [x46, x73, x29, x64]
+}
+
+#[inline(always)]
+pub unsafe fn s3<V: Bits>(a1: V, a2: V, a3: V, a4: V, a5: V, a6: V) -> [V; 4] {
+    let x0 = a5.not();
+    let x1 = x0.xor(a2);
+    let x2 = a6.or(a5);
+    let x3 = a6.or(x0);
+    let x4 = x3.and(a2);
+    let x5 = x2.xor(x4);
+    let x6 = x5.and(a4);
+    let x7 = x1.xor(x6);
+    let x8 = x1.xor(x4);
+    let x9 = a5.xor(x3);
+    let x10 = x9.xor(a2);
+    let x11 = x10.and(a4);
+    let x12 = x8.xor(x11);
+    let x13 = x12.and(a3);
+    let x14 = x7.xor(x13);
+    let x15 = a2.xor(a6);
+    let x16 = x5.not();
+    let x17 = x16.and(a4);
+    let x18 = x15.xor(x17);
+    let x19 = x10.xor(x11);
+    let x20 = x19.and(a3);
+    let x21 = x18.xor(x20);
+    let x22 = x21.and(a1);
+    let x23 = x14.xor(x22);
+    let x24 = x2.and(a2);
+    let x25 = a6.xor(x24);
+    let x26 = x5.xor(x24);
+    let x27 = x26.and(a4);
+    let x28 = x25.xor(x27);
+    let x29 = x1.xor(x24);
+    let x30 = a2.and(a4);
+    let x31 = x29.xor(x30);
+    let x32 = x31.and(a3);
+    let x33 = x28.xor(x32);
+    let x34 = a5.xor(x29);
+    let x35 = x1.xor(x3);
+    let x36 = x35.and(a4);
+    let x37 = x34.xor(x36);
+    let x38 = x37.or(a3);
+    let x39 = x38.and(a1);
+    let x40 = x33.xor(x39);
+    let x41 = x5.xor(x9);
+    let x42 = x0.andnot(a2);
+    let x43 = x2.xor(x42);
+    let x44 = x43.and(a4);
+    let x45 = x41.xor(x44);
+    let x46 = x26.or(a4);
+    let x47 = x46.and(a3);
+    let x48 = x45.xor(x47);
+    let x49 = x15.xor(x34);
+    let x50 = a6.xor(x9);
+    let x51 = x50.andnot(a2);
+    let x52 = a5.xor(x51);
+    let x53 = x52.and(a4);
+    let x54 = x49.xor(x53);
+    let x55 = a6.xor(x2);
+    let x56 = a5.xor(x26);
+    let x57 = x56.and(a4);
+    let x58 = x55.xor(x57);
+    let x59 = x58.and(a3);
+    let x60 = x54.xor(x59);
+    let x61 = x60.and(a1);
+    let x62 = x48.xor(x61);
+    let x63 = x0.and(a4);
+    let x64 = x15.xor(x63);
+    let x65 = a5.and(a3);
+    let x66 = x64.xor(x65);
+    let x67 = a5.xor(a6);
+    let x68 = x67.andnot(a4);
+    let x69 = x34.xor(x68);
+    let x70 = a6.and(a2);
+    let x71 = x70.andnot(a4);
+    let x72 = x42.xor(x71);
+    let x73 = x72.and(a3);
+    let x74 = x69.xor(x73);
+    let x75 = x74.and(a1);
+    let x76 = x66.xor(x75);
+    [x23, x40, x62, x76]
+}
+
+#[inline(always)]
+pub unsafe fn s4<V: Bits>(a1: V, a2: V, a3: V, a4: V, a5: V, a6: V) -> [V; 4] {
+    let x0 = a3.not();
+    let x1 = a5.or(x0);
+    let x2 = x1.and(a1);
+    let x3 = x0.xor(x2);
+    let x4 = a3.xor(a5);
+    let x5 = x4.or(a1);
+    let x6 = x5.and(a2);
+    let x7 = x3.xor(x6);
+    let x8 = x0.xor(x4);
+    let x9 = a3.xor(x5);
+    let x10 = x9.and(a2);
+    let x11 = x8.xor(x10);
+    let x12 = x11.and(a4);
+    let x13 = x7.xor(x12);
+    let x14 = a3.xor(x8);
+    let x15 = x1.not();
+    let x16 = x15.and(a1);
+    let x17 = x14.xor(x16);
+    let x18 = x17.and(a2);
+    let x19 = x9.xor(x18);
+    let x20 = x1.xor(x4);
+    let x21 = x20.andnot(a1);
+    let x22 = a5.xor(x21);
+    let x23 = x4.and(a2);
+    let x24 = x22.xor(x23);
+    let x25 = x24.and(a4);
+    let x26 = x19.xor(x25);
+    let x27 = x26.and(a6);
+    let x28 = x13.xor(x27);
+    let x29 = a3.xor(x22);
+    let x30 = x0.and(a2);
+    let x31 = x29.xor(x30);
+    let x32 = a5.xor(x5);
+    let x33 = x32.and(a2);
+    let x34 = a5.xor(x33);
+    let x35 = x34.and(a4);
+    let x36 = x31.xor(x35);
+    let x37 = a1.xor(x21);
+    let x38 = x37.andnot(a2);
+    let x39 = x1.xor(x38);
+    let x40 = x4.andnot(a2);
+    let x41 = x3.xor(x40);
+    let x42 = x41.and(a4);
+    let x43 = x39.xor(x42);
+    let x44 = x43.and(a6);
+    let x45 = x36.xor(x44);
+    let x46 = x43.not();
+    let x47 = x46.andnot(a6);
+    let x48 = x36.xor(x47);
+    let x49 = x13.xor(x26);
+    let x50 = x26.not();
+    let x51 = x50.and(a6);
+    let x52 = x49.xor(x51);
+    [x48, x45, x52, x28]
+}
+
+#[inline(always)]
+pub unsafe fn s5<V: Bits>(a1: V, a2: V, a3: V, a4: V, a5: V, a6: V) -> [V; 4] {
+    let x0 = a6.or(a3);
+    let x1 = a6.not();
+    let x2 = a3.not();
+    let x3 = x1.or(x2);
+    let x4 = x3.and(a4);
+    let x5 = x0.xor(x4);
+    let x6 = x3.not();
+    let x7 = x6.or(a4);
+    let x8 = x7.and(a2);
+    let x9 = x5.xor(x8);
+    let x10 = x3.or(a4);
+    let x11 = x10.and(a5);
+    let x12 = x9.xor(x11);
+    let x13 = x0.not();
+    let x14 = x13.or(a4);
+    let x15 = a2.not();
+    let x16 = x14.or(x15);
+    let x17 = a3.xor(x1);
+    let x18 = x17.and(a4);
+    let x19 = a6.xor(x18);
+    let x20 = x19.and(a5);
+    let x21 = x16.xor(x20);
+    let x22 = x21.and(a1);
+    let x23 = x12.xor(x22);
+    let x24 = x2.xor(x14);
+    let x25 = x1.and(a4);
+    let x26 = x3.xor(x25);
+    let x27 = x26.and(a2);
+    let x28 = x24.xor(x27);
+    let x29 = x24.xor(x26);
+    let x30 = a4.xor(x6);
+    let x31 = x30.and(a2);
+    let x32 = x29.xor(x31);
+    let x33 = x32.and(a5);
+    let x34 = x28.xor(x33);
+    let x35 = a6.andnot(a4);
+    let x36 = x0.xor(x35);
+    let x37 = x6.xor(x18);
+    let x38 = x37.and(a2);
+    let x39 = x36.xor(x38);
+    let x40 = x7.xor(x29);
+    let x41 = a4.xor(x35);
+    let x42 = x41.and(a2);
+    let x43 = x40.xor(x42);
+    let x44 = x43.and(a5);
+    let x45 = x39.xor(x44);
+    let x46 = x45.and(a1);
+    let x47 = x34.xor(x46);
+    let x48 = x0.xor(x14);
+    let x49 = x48.andnot(a2);
+    let x50 = x30.xor(x49);
+    let x51 = a3.xor(x29);
+    let x52 = a4.xor(x13);
+    let x53 = x52.and(a2);
+    let x54 = x51.xor(x53);
+    let x55 = x54.and(a5);
+    let x56 = x50.xor(x55);
+    let x57 = x2.xor(x29);
+    let x58 = x57.andnot(a2);
+    let x59 = x3.xor(x58);
+    let x60 = a4.xor(x26);
+    let x61 = x60.and(a2);
+    let x62 = x57.xor(x61);
+    let x63 = x62.and(a5);
+    let x64 = x59.xor(x63);
+    let x65 = x64.and(a1);
+    let x66 = x56.xor(x65);
+    let x67 = x2.xor(x10);
+    let x68 = x41.and(a2);
+    let x69 = x67.xor(x68);
+    let x70 = x3.xor(x51);
+    let x71 = x0.xor(x60);
+    let x72 = x71.and(a2);
+    let x73 = x70.xor(x72);
+    let x74 = x73.and(a5);
+    let x75 = x69.xor(x74);
+    let x76 = x10.xor(x57);
+    let x77 = x76.and(a2);
+    let x78 = x5.xor(x77);
+    let x79 = x17.xor(x54);
+    let x80 = x79.and(a5);
+    let x81 = x78.xor(x80);
+    let x82 = x81.and(a1);
+    let x83 = x75.xor(x82);
+    [x47, x23, x66, x83]
+}
+
+#[inline(always)]
+pub unsafe fn s6<V: Bits>(a1: V, a2: V, a3: V, a4: V, a5: V, a6: V) -> [V; 4] {
+    let x0 = a1.xor(a3);
+    let x1 = a3.not();
+    let x2 = x0.xor(x1);
+    let x3 = x1.or(x2);
+    let x4 = x3.and(a5);
+    let x5 = x0.xor(x4);
+    let x6 = a3.or(a1);
+    let x7 = x6.andnot(a5);
+    let x8 = a1.xor(x7);
+    let x9 = x8.and(a4);
+    let x10 = x5.xor(x9);
+    let x11 = a5.xor(x8);
+    let x12 = x11.and(a4);
+    let x13 = a1.xor(x12);
+    let x14 = x13.and(a6);
+    let x15 = x10.xor(x14);
+    let x16 = a3.or(a4);
+    let x17 = a3.xor(x6);
+    let x18 = x2.and(a5);
+    let x19 = x0.xor(x18);
+    let x20 = x19.and(a4);
+    let x21 = x17.xor(x20);
+    let x22 = x21.and(a6);
+    let x23 = x16.xor(x22);
+    let x24 = x23.and(a2);
+    let x25 = x15.xor(x24);
+    let x26 = x1.andnot(a5);
+    let x27 = x17.xor(x26);
+    let x28 = x0.xor(x3);
+    let x29 = a5.not();
+    let x30 = x28.or(x29);
+    let x31 = x30.and(a4);
+    let x32 = x27.xor(x31);
+    let x33 = x3.or(a5);
+    let x34 = a5.xor(x18);
+    let x35 = x34.and(a4);
+    let x36 = x33.xor(x35);
+    let x37 = x36.and(a6);
+    let x38 = x32.xor(x37);
+    let x39 = a4.xor(x36);
+    let x40 = x3.xor(x29);
+    let x41 = x40.andnot(a4);
+    let x42 = x4.xor(x41);
+    let x43 = x42.and(a6);
+    let x44 = x39.xor(x43);
+    let x45 = x44.and(a2);
+    let x46 = x38.xor(x45);
+    let x47 = x8.xor(x26);
+    let x48 = x26.not();
+    let x49 = x48.and(a4);
+    let x50 = x47.xor(x49);
+    let x51 = x5.xor(x30);
+    let x52 = x51.andnot(a4);
+    let x53 = x33.xor(x52);
+    let x54 = x53.and(a6);
+    let x55 = x50.xor(x54);
+    let x56 = x20.xor(x42);
+    let x57 = x56.and(a6);
+    let x58 = x1.xor(x57);
+    let x59 = x58.and(a2);
+    let x60 = x55.xor(x59);
+    let x61 = a3.xor(x8);
+    let x62 = x61.xor(a4);
+    let x63 = a1.xor(x33);
+    let x64 = x18.and(a4);
+    let x65 = x63.xor(x64);
+    let x66 = x65.and(a6);
+    let x67 = x62.xor(x66);
+    let x68 = x27.xor(x29);
+    let x69 = x18.and(a4);
+    let x70 = x68.xor(x69);
+    let x71 = x4.xor(x35);
+    let x72 = x71.and(a6);
+    let x73 = x70.xor(x72);
+    let x74 = x73.and(a2);
+    let x75 = x67.xor(x74);
+    [x60, x46, x75, x25]
+}
+
+#[inline(always)]
+pub unsafe fn s7<V: Bits>(a1: V, a2: V, a3: V, a4: V, a5: V, a6: V) -> [V; 4] {
+    let x0 = a2.xor(a4);
+    let x1 = a4.not();
+    let x2 = x1.or(a2);
+    let x3 = x2.and(a5);
+    let x4 = x0.xor(x3);
+    let x5 = x4.xor(a3);
+    let x6 = a4.and(a5);
+    let x7 = x2.xor(x6);
+    let x8 = x7.and(a3);
+    let x9 = x3.xor(x8);
+    let x10 = x9.and(a6);
+    let x11 = x5.xor(x10);
+    let x12 = x1.xor(x2);
+    let x13 = x12.or(a5);
+    let x14 = x0.xor(x1);
+    let x15 = x14.andnot(a5);
+    let x16 = x15.and(a3);
+    let x17 = x13.xor(x16);
+    let x18 = a5.not();
+    let x19 = x7.xor(x15);
+    let x20 = x19.and(a3);
+    let x21 = x18.xor(x20);
+    let x22 = x21.and(a6);
+    let x23 = x17.xor(x22);
+    let x24 = x23.and(a1);
+    let x25 = x11.xor(x24);
+    let x26 = a5.xor(x12);
+    let x27 = x0.xor(x7);
+    let x28 = x27.and(a3);
+    let x29 = x26.xor(x28);
+    let x30 = a3.not();
+    let x31 = x27.or(x30);
+    let x32 = x31.and(a6);
+    let x33 = x29.xor(x32);
+    let x34 = x0.xor(x13);
+    let x35 = a4.xor(x19);
+    let x36 = x35.and(a3);
+    let x37 = x34.xor(x36);
+    let x38 = x6.xor(x18);
+    let x39 = x27.and(a3);
+    let x40 = x38.xor(x39);
+    let x41 = x40.and(a6);
+    let x42 = x37.xor(x41);
+    let x43 = x42.and(a1);
+    let x44 = x33.xor(x43);
+    let x45 = x14.xor(x38);
+    let x46 = a4.andnot(a5);
+    let x47 = x14.xor(x46);
+    let x48 = x47.and(a3);
+    let x49 = x45.xor(x48);
+    let x50 = x13.xor(x18);
+    let x51 = x50.and(a6);
+    let x52 = x49.xor(x51);
+    let x53 = x0.and(a5);
+    let x54 = x2.xor(x53);
+    let x55 = a2.xor(x46);
+    let x56 = x55.and(a3);
+    let x57 = x54.xor(x56);
+    let x58 = a6.not();
+    let x59 = x57.or(x58);
+    let x60 = x59.and(a1);
+    let x61 = x52.xor(x60);
+    let x62 = x7.xor(x45);
+    let x63 = a2.and(a3);
+    let x64 = x62.xor(x63);
+    let x65 = x3.xor(x45);
+    let x66 = x6.and(a3);
+    let x67 = x65.xor(x66);
+    let x68 = x67.and(a6);
+    let x69 = x64.xor(x68);
+    let x70 = x29.xor(x64);
+    let x71 = x50.xor(x63);
+    let x72 = x71.and(a6);
+    let x73 = x70.xor(x72);
+    let x74 = x73.and(a1);
+    let x75 = x69.xor(x74);
+    [x44, x75, x25, x61]
+}
+
+#[inline(always)]
+pub unsafe fn s8<V: Bits>(a1: V, a2: V, a3: V, a4: V, a5: V, a6: V) -> [V; 4] {
+    let x0 = a5.not();
+    let x1 = x0.andnot(a4);
+    let x2 = x1.xor(a3);
+    let x3 = a4.not();
+    let x4 = x0.or(x3);
+    let x5 = x4.and(a2);
+    let x6 = x2.xor(x5);
+    let x7 = a4.xor(a5);
+    let x8 = x1.not();
+    let x9 = x8.and(a3);
+    let x10 = x7.xor(x9);
+    let x11 = a3.xor(a5);
+    let x12 = x11.and(a2);
+    let x13 = x10.xor(x12);
+    let x14 = x13.and(a6);
+    let x15 = x6.xor(x14);
+    let x16 = x0.andnot(a3);
+    let x17 = x1.xor(x16);
+    let x18 = x17.andnot(a2);
+    let x19 = x8.xor(x18);
+    let x20 = x7.andnot(a3);
+    let x21 = x20.andnot(a2);
+    let x22 = x1.xor(x21);
+    let x23 = x22.and(a6);
+    let x24 = x19.xor(x23);
+    let x25 = x24.and(a1);
+    let x26 = x15.xor(x25);
+    let x27 = a5.andnot(a3);
+    let x28 = x3.xor(x27);
+    let x29 = x2.and(a2);
+    let x30 = x28.xor(x29);
+    let x31 = x30.xor(a6);
+    let x32 = x11.xor(x20);
+    let x33 = x3.and(a3);
+    let x34 = x8.xor(x33);
+    let x35 = x34.and(a2);
+    let x36 = x32.xor(x35);
+    let x37 = a3.xor(x33);
+    let x38 = x37.and(a2);
+    let x39 = x20.xor(x38);
+    let x40 = x39.and(a6);
+    let x41 = x36.xor(x40);
+    let x42 = x41.and(a1);
+    let x43 = x31.xor(x42);
+    let x44 = x0.xor(x33);
+    let x45 = a3.xor(x34);
+    let x46 = x45.and(a2);
+    let x47 = x44.xor(x46);
+    let x48 = x0.xor(x45);
+    let x49 = x4.xor(x45);
+    let x50 = x49.and(a2);
+    let x51 = x48.xor(x50);
+    let x52 = x51.and(a6);
+    let x53 = x47.xor(x52);
+    let x54 = x17.xor(x37);
+    let x55 = x54.andnot(a2);
+    let x56 = x28.xor(x55);
+    let x57 = x56.andnot(a6);
+    let x58 = x19.xor(x57);
+    let x59 = x58.and(a1);
+    let x60 = x53.xor(x59);
+    let x61 = x4.xor(x16);
+    let x62 = x61.xor(a2);
+    let x63 = a2.xor(x35);
+    let x64 = x63.and(a6);
+    let x65 = x62.xor(x64);
+    let x66 = x36.xor(x56);
+    let x67 = x1.xor(x28);
+    let x68 = x67.and(a2);
+    let x69 = x8.xor(x68);
+    let x70 = x69.and(a6);
+    let x71 = x66.xor(x70);
+    let x72 = x71.and(a1);
+    let x73 = x65.xor(x72);
+    [x60, x43, x73, x26]
+}
@@ -0,0 +1,151 @@
+// Copyright 2016 Huton. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! SSE2, AVX2 and AVX-512 vectors for the bitsliced DES, which compute 128, 256 and 512 ciphers
+//! at once.
+
+#[cfg(target_arch = "x86")]
+use core::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64::*;
+use alloc::vec::Vec;
+use super::bitslice::{self, Bits};
+
+#[cfg(feature = "std")]
+pub fn has_sse2() -> bool {
+    is_x86_feature_detected!("sse2")
+}
+
+#[cfg(feature = "std")]
+pub fn has_avx2() -> bool {
+    is_x86_feature_detected!("avx2")
+}
+
+#[cfg(feature = "std")]
+pub fn has_avx512() -> bool {
+    is_x86_feature_detected!("avx512f")
+}
+
+// Without `std`, the CPU features cannot be detected at runtime, so only the ones enabled
+// at compile time are used.
+
+#[cfg(not(feature = "std"))]
+pub fn has_sse2() -> bool {
+    cfg!(target_feature = "sse2")
+}
+
+#[cfg(not(feature = "std"))]
+pub fn has_avx2() -> bool {
+    cfg!(target_feature = "avx2")
+}
+
+#[cfg(not(feature = "std"))]
+pub fn has_avx512() -> bool {
+    cfg!(target_feature = "avx512f")
+}
+
+// Implements `Bits` for a vector type with the intrinsics that follow, which need the target
+// feature of the vector type.
+//
+// The methods are only called from the functions below that enable the target feature, after
+// the dispatcher has checked that the CPU supports it, and are inlined into them.
+macro_rules! impl_bits {
+    ($t:ident, $v:ty, $lanes:expr,
+        $setzero:ident, $set1:ident, $loadu:ident, $storeu:ident,
+        $and:ident, $or:ident, $xor:ident, $andnot:ident
+    ) => {
+        #[derive(Copy, Clone)]
+        pub struct $t($v);
+
+        unsafe impl Bits for $t {
+            const LANES: usize = $lanes;
+
+            #[inline(always)]
+            unsafe fn zero() -> Self {
+                $t($setzero())
+            }
+
+            #[inline(always)]
+            unsafe fn ones() -> Self {
+                $t($set1(-1))
+            }
+
+            #[inline(always)]
+            unsafe fn and(self, other: Self) -> Self {
+                $t($and(self.0, other.0))
+            }
+
+            #[inline(always)]
+            unsafe fn or(self, other: Self) -> Self {
+                $t($or(self.0, other.0))
+            }
+
+            #[inline(always)]
+            unsafe fn xor(self, other: Self) -> Self {
+                $t($xor(self.0, other.0))
+            }
+
+            #[inline(always)]
+            unsafe fn andnot(self, other: Self) -> Self {
+                // The intrinsics negate their first operand.
+                $t($andnot(other.0, self.0))
+            }
+
+            #[inline(always)]
+            unsafe fn load(words: &[u64]) -> Self {
+                assert_eq!($lanes / 64, words.len());
+                $t($loadu(words.as_ptr() as *const _))
+            }
+
+            #[inline(always)]
+            unsafe fn store(self, words: &mut [u64]) {
+                assert_eq!($lanes / 64, words.len());
+                $storeu(words.as_mut_ptr() as *mut _, self.0)
+            }
+        }
+    };
+}
+
+impl_bits!(Sse2, __m128i, 128,
+    _mm_setzero_si128, _mm_set1_epi64x, _mm_loadu_si128, _mm_storeu_si128,
+    _mm_and_si128, _mm_or_si128, _mm_xor_si128, _mm_andnot_si128);
+
+impl_bits!(Avx2, __m256i, 256,
+    _mm256_setzero_si256, _mm256_set1_epi64x, _mm256_loadu_si256, _mm256_storeu_si256,
+    _mm256_and_si256, _mm256_or_si256, _mm256_xor_si256, _mm256_andnot_si256);
+
+impl_bits!(Avx512, __m512i, 512,
+    _mm512_setzero_si512, _mm512_set1_epi64, _mm512_loadu_si512, _mm512_storeu_si512,
+    _mm512_and_si512, _mm512_or_si512, _mm512_xor_si512, _mm512_andnot_si512);
+
+// Computes the ciphers of as many of `keys` as fill whole vectors of type `$t` in a function
+// that enables `$feature`, appends them to `dst` and returns the rest of the keys.
+macro_rules! zero_cipher_58_fn {
+    ($name:ident, $t:ident, $feature:tt) => {
+        /// # Safety
+        ///
+        /// The CPU must support the target feature of the vectors.
+        pub unsafe fn $name<'a>(keys: &'a [(u64, u32)], dst: &mut Vec<u64>) -> &'a [(u64, u32)] {
+            #[target_feature(enable = $feature)]
+            unsafe fn run(keys: &[(u64, u32)], dst: &mut Vec<u64>) {
+                bitslice::zero_cipher_58::<$t>(keys, dst);
+            }
+
+            let mut chunks = keys.chunks_exact($t::LANES);
+            for chunk in &mut chunks {
+                run(chunk, dst);
+            }
+            chunks.remainder()
+        }
+    };
+}
+
+zero_cipher_58_fn!(zero_cipher_58_sse2, Sse2, "sse2");
+zero_cipher_58_fn!(zero_cipher_58_avx2, Avx2, "avx2");
+zero_cipher_58_fn!(zero_cipher_58_avx512, Avx512, "avx512f");
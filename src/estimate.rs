@@ -0,0 +1,322 @@
+// Copyright 2016 Huton. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+Estimates of how long a [`search`](../search/index.html) takes to find a tripcode.
+
+The probability that the tripcode of a password matches any of the patterns is computed
+exactly, assuming that each character of the tripcode is equally likely to be any of the
+[`Alphabet`](../hash/enum.Alphabet.html) of its position, e.g. the last character of
+a 10-character tripcode is one of only 16 characters. The patterns are compiled into a DFA, and
+the probability of each of its states is carried through the positions of the tripcode.
+
+The number of passwords that a search tests before the first hit then follows a geometric
+distribution, which, combined with a hash rate that `measure_rate()` measures, tells the
+expected time and the time within which a hit is found with a given probability.
+
+This module is only available with the `regex` feature.
+
+# Examples
+
+```
+use std::time::Duration;
+use tripcode::*;
+use tripcode::estimate::{self, Estimate};
+use tripcode::search::{Pattern, Search};
+
+let patterns = vec![Pattern::Prefix("ab".to_owned())];
+let estimate = Estimate::new::<Fourchan>(&patterns, 8).unwrap();
+assert_eq!(1.0 / 4096.0, estimate.probability());
+assert_eq!(4096.0, estimate.expected_candidates());
+
+let rate = estimate::measure_rate::<Fourchan>(Search::new(patterns), Duration::from_millis(100));
+if let (Some(expected), Some(worst)) = (estimate.expected_time(rate), estimate.time_within(0.99, rate)) {
+    println!("{:?} on average, {:?} for 99% of searches", expected, worst);
+}
+```
+*/
+
+use std::{error, fmt};
+use std::boxed::Box;
+use std::collections::BTreeMap;
+use std::string::String;
+use std::time::{Duration, Instant};
+use std::vec::Vec;
+use regex_automata::Anchored;
+use regex_automata::dfa::Automaton;
+use regex_automata::dfa::dense::{self, DFA};
+use regex_automata::util::start;
+use hash::{Alphabet, TripcodeAlphabets};
+use search::{Pattern, Search};
+use TripcodeGenerator;
+
+/// Error that occurs when the patterns cannot be compiled into a DFA, e.g. when a regular
+/// expression has a Unicode word boundary or makes too large a DFA.
+#[derive(Clone, Debug)]
+pub struct BuildError(Box<dense::BuildError>);
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl error::Error for BuildError {}
+
+/// Probability that the tripcode of a password matches the patterns of a search.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Estimate {
+    probability: f64,
+}
+
+impl Estimate {
+    /// Estimates a search for `patterns` with the generator `G` and passwords of
+    /// `password_len` bytes.
+    pub fn new<G>(patterns: &[Pattern], password_len: usize) -> Result<Estimate, BuildError>
+        where G: TripcodeGenerator, G::Hash: TripcodeAlphabets
+    {
+        Estimate::from_alphabets(patterns, &G::Hash::alphabets(password_len))
+    }
+
+    /// Estimates a search for `patterns` among tripcodes of which the `i`th character is
+    /// one of `alphabets[i]`.
+    pub fn from_alphabets(patterns: &[Pattern], alphabets: &[Alphabet]) -> Result<Estimate, BuildError> {
+        let sources: Vec<_> = patterns.iter().map(regex_source).collect();
+        let dfa = DFA::new_many(&sources).map_err(|e| BuildError(Box::new(e)))?;
+        let start = dfa.start_state(&start::Config::new().anchored(Anchored::No))
+            .expect("no start state");
+
+        // Probabilities of the states that no match has been found in yet. They are summed in the
+        // order of the states, so that the estimate is the same on every run.
+        let mut states = BTreeMap::new();
+        states.insert(start, 1.0);
+        let mut matched = 0.0;
+
+        for alphabet in alphabets {
+            let chars = alphabet.chars();
+            let p = 1.0 / chars.len() as f64;
+            let mut next = BTreeMap::new();
+            for (&s, &q) in &states {
+                'chars: for c in &chars {
+                    let mut t = s;
+                    for &b in *c {
+                        t = dfa.next_state(t, b);
+                        if dfa.is_match_state(t) {
+                            matched += q * p;
+                            continue 'chars;
+                        }
+                    }
+                    if !dfa.is_dead_state(t) {
+                        *next.entry(t).or_insert(0.0) += q * p;
+                    }
+                }
+            }
+            states = next;
+        }
+
+        for (&s, &q) in &states {
+            if dfa.is_match_state(dfa.next_eoi_state(s)) {
+                matched += q;
+            }
+        }
+
+        Ok(Estimate { probability: matched })
+    }
+
+    /// Returns the probability that the tripcode of a password matches the patterns.
+    pub fn probability(&self) -> f64 {
+        self.probability
+    }
+
+    /// Returns the expected number of passwords tested before the first hit, including it.
+    ///
+    /// This is infinite if no tripcode can match the patterns.
+    pub fn expected_candidates(&self) -> f64 {
+        1.0 / self.probability
+    }
+
+    /// Returns the number of passwords within which a hit is found with probability `p`,
+    /// which is in `[0, 1]`.
+    pub fn candidates_within(&self, p: f64) -> f64 {
+        if p <= 0.0 {
+            0.0
+        } else if p >= 1.0 || self.probability <= 0.0 {
+            f64::INFINITY
+        } else {
+            ((-p).ln_1p() / (-self.probability).ln_1p()).ceil().max(1.0)
+        }
+    }
+
+    /// Returns the expected number of hits among `keyspace_len` passwords.
+    pub fn expected_hits(&self, keyspace_len: u64) -> f64 {
+        self.probability * keyspace_len as f64
+    }
+
+    /// Returns the expected time until the first hit at `rate` passwords per second, or `None`
+    /// if it is too long to be a `Duration`, e.g. because no tripcode can match the patterns.
+    pub fn expected_time(&self, rate: f64) -> Option<Duration> {
+        Duration::try_from_secs_f64(self.expected_candidates() / rate).ok()
+    }
+
+    /// Returns the time within which a hit is found with probability `p` at `rate` passwords
+    /// per second, or `None` if it is too long to be a `Duration`.
+    pub fn time_within(&self, p: f64, rate: f64) -> Option<Duration> {
+        Duration::try_from_secs_f64(self.candidates_within(p) / rate).ok()
+    }
+}
+
+/// Runs `search` with the generator `G` for `duration` and returns the number of passwords that
+/// it tested per second. The hits are discarded.
+pub fn measure_rate<G: TripcodeGenerator + 'static>(search: Search, duration: Duration) -> f64 {
+    let start = Instant::now();
+    let searching = search.start::<G>();
+
+    // Receiving the hits keeps them from piling up in the channel.
+    loop {
+        let elapsed = start.elapsed();
+        if elapsed >= duration {
+            break;
+        }
+        // The keyspace may be exhausted before the time is up.
+        if searching.next_hit_timeout(duration - elapsed).is_err() {
+            break;
+        }
+    }
+
+    let tested = searching.tested();
+    let elapsed = start.elapsed();
+    searching.stop();
+    tested as f64 / elapsed.as_secs_f64()
+}
+
+/// Translates a pattern into a regular expression.
+fn regex_source(pattern: &Pattern) -> String {
+    match *pattern {
+        Pattern::Prefix(ref s) => {
+            let mut r = String::from("^");
+            escape(s, &mut r);
+            r
+        },
+        Pattern::Contains(ref s) => {
+            let mut r = String::new();
+            escape(s, &mut r);
+            r
+        },
+        Pattern::Regex(ref r) => String::from(r.as_str()),
+    }
+}
+
+/// Appends `s` to `dst`, escaping the characters that have special meanings in regular
+/// expressions.
+fn escape(s: &str, dst: &mut String) {
+    for c in s.chars() {
+        if "\\.+*?()|[]{}^$#&-~".contains(c) {
+            dst.push('\\');
+        }
+        dst.push(c);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::borrow::ToOwned;
+    use search::Regex;
+    use *;
+
+    fn probability<G>(patterns: &[Pattern], password_len: usize) -> f64
+        where G: TripcodeGenerator, G::Hash: TripcodeAlphabets
+    {
+        Estimate::new::<G>(patterns, password_len).unwrap().probability()
+    }
+
+    fn approx_eq(a: f64, b: f64) -> bool {
+        (a - b).abs() <= a.abs() * 1e-9
+    }
+
+    #[test]
+    fn probability_of_patterns() {
+        let prefix = |s: &str| Pattern::Prefix(s.to_owned());
+        let contains = |s: &str| Pattern::Contains(s.to_owned());
+        let regex = |s: &str| Pattern::Regex(Regex::new(s).unwrap());
+
+        assert_eq!(0.0, probability::<Fourchan>(&[], 8));
+        assert_eq!(1.0, probability::<Fourchan>(&[contains("")], 8));
+        assert_eq!(1.0 / 64.0, probability::<Fourchan>(&[prefix("a")], 8));
+        assert_eq!(1.0 / 64.0 / 64.0, probability::<Mona12>(&[prefix("+/")], 12));
+        assert_eq!(0.0, probability::<Fourchan>(&[prefix("a+")], 8));
+
+        // The last character is one of 16.
+        assert_eq!(1.0 / 16.0, probability::<Fourchan>(&[regex("A$")], 8));
+        assert_eq!(0.0, probability::<Fourchan>(&[regex("B$")], 8));
+        assert_eq!(1.0 / 64.0, probability::<Mona>(&[regex("B$")], 12));
+        assert_eq!(0.0, probability::<Fourchan>(&[prefix("abcdefghijk")], 8));
+
+        // `x` in any of 10 positions, by inclusion-exclusion.
+        let p = 1.0 - (63.0f64 / 64.0).powi(9);
+        assert!(approx_eq(p, probability::<Fourchan>(&[contains("x")], 8)));
+        let p = 1.0 - (63.0f64 / 64.0).powi(9) * 15.0 / 16.0;
+        assert!(approx_eq(p, probability::<Fourchan>(&[contains("A")], 8)));
+
+        // Overlapping patterns are not counted twice.
+        let p = 1.0 / 64.0;
+        assert!(approx_eq(p, probability::<Fourchan>(&[prefix("a"), prefix("ab")], 8)));
+        let p = 2.0 / 64.0 - 1.0 / 64.0 / 64.0;
+        assert!(approx_eq(p, probability::<Fourchan>(&[prefix("a"), regex("^.b")], 8)));
+
+        assert_eq!(1.0 / 64.0, probability::<Sc15>(&[prefix("!")], 16));
+        assert_eq!(1.0 / 64.0, probability::<ScKatakana>(&[prefix("ｱ")], 16));
+        assert_eq!(1.0 / 64.0, probability::<ScKatakana>(&[regex("^.ｱ")], 16));
+    }
+
+    #[test]
+    fn sampled() {
+        // The estimate must agree with the fraction of matching tripcodes.
+        let patterns = [Pattern::Contains("a".to_owned()), Pattern::Regex(Regex::new("[A-F]$").unwrap())];
+        let p = probability::<Fourchan>(&patterns, 3);
+        let n = 4000;
+        let mut matched = 0;
+        let mut password = [0u8; 3];
+        for i in 0..n {
+            password.copy_from_slice(&[b'a' + (i / 676) as u8, b'a' + (i / 26 % 26) as u8, b'a' + (i % 26) as u8]);
+            if patterns.iter().any(|pattern| pattern.matches(&Fourchan::generate(password))) {
+                matched += 1;
+            }
+        }
+        let q = matched as f64 / n as f64;
+        assert!((p - q).abs() < 0.03, "estimated {}, sampled {}", p, q);
+    }
+
+    #[test]
+    fn quantiles() {
+        let estimate = Estimate { probability: 0.5 };
+        assert_eq!(2.0, estimate.expected_candidates());
+        assert_eq!(0.0, estimate.candidates_within(0.0));
+        assert_eq!(1.0, estimate.candidates_within(0.5));
+        assert_eq!(7.0, estimate.candidates_within(0.99));
+        assert_eq!(Some(Duration::from_secs(1)), estimate.expected_time(2.0));
+        assert_eq!(Some(Duration::from_secs(7)), estimate.time_within(0.99, 1.0));
+        assert_eq!(50.0, estimate.expected_hits(100));
+
+        let estimate = Estimate { probability: 1.0 / 4096.0 };
+        let median = estimate.candidates_within(0.5);
+        assert!((2838.0..=2840.0).contains(&median));
+
+        let never = Estimate { probability: 0.0 };
+        assert_eq!(None, never.expected_time(1e9));
+        assert_eq!(None, never.time_within(0.5, 1e9));
+        assert_eq!(None, estimate.time_within(1.0, 1e9));
+    }
+
+    #[test]
+    fn measure() {
+        let search = Search::new(vec![Pattern::Contains(String::new())]).threads(1);
+        assert!(measure_rate::<Fourchan>(search, Duration::from_millis(200)) > 0.0);
+    }
+}
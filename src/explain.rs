@@ -0,0 +1,222 @@
+// Copyright 2016 Huton. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+Step-by-step explanations of how tripcodes are generated, for finding out why a tripcode differs
+from the one that a site gives for the same password.
+
+# Examples
+
+```
+use tripcode::*;
+use tripcode::explain::{Branch, Salt};
+
+let explanation = Mona::explain("a<b");
+
+// The password is HTML-escaped before hashing.
+assert_eq!(b"a&lt;b", &explanation.escaped[..]);
+// `&` is not a valid salt character, so it is reinterpreted as `.`.
+assert_eq!(Some(Salt { chars: *b"&l", reinterpreted: *b".l" }), explanation.salt);
+// The escaped password is shorter than 12 bytes, so a 10-character tripcode is generated.
+assert_eq!(Branch::Ten, explanation.branch);
+assert_eq!(Mona::hash("a<b"), explanation.hash);
+```
+*/
+
+use alloc::vec::Vec;
+use util::*;
+use {Fourchan, FourchanNonescaping, Mona, MonaNonescaping, Mona10, Mona12, Mona12Nonescaping};
+use {Sc, ScSjis, Sc15, ScKatakana, TripcodeGenerator};
+
+/// Kind of tripcode that a generator produces for a password.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Branch {
+    /// 10-character tripcode, hashed with DES.
+    Ten,
+    /// _Nama key_ tripcode (生キートリップ), hashed with DES using a key given in hexadecimal.
+    /// The hash value is `Error` if the key is malformed.
+    NamaKey,
+    /// 12-character tripcode, hashed with SHA-1.
+    Twelve,
+    /// _2ch.sc_'s 15-character tripcode, hashed with SHA-1.
+    Fifteen,
+    /// _2ch.sc_'s katakana tripcode, hashed with SHA-1.
+    Katakana,
+    /// 2channel's tripcode for a password of 12 or greater bytes long that begins with `'$'`,
+    /// which is undefined and yields `Error`.
+    Undefined,
+}
+
+/// Salt characters of a DES-based tripcode.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Salt {
+    /// The salt characters picked from the password.
+    pub chars: [u8; 2],
+    /// The characters that `chars` are reinterpreted as, as per the table in the documentation
+    /// of `Des`.
+    pub reinterpreted: [u8; 2],
+}
+
+/// Explanation of how a tripcode is generated from a password.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Explanation<H> {
+    /// The password after HTML escaping, which is the same as the original password for
+    /// generators that do not escape passwords.
+    pub escaped: Vec<u8>,
+    /// The salt characters, if the tripcode is hashed with DES.
+    pub salt: Option<Salt>,
+    /// The kind of the tripcode.
+    pub branch: Branch,
+    /// The resulting hash value.
+    pub hash: H,
+}
+
+/// Trait for generators that can explain how they generate tripcodes.
+pub trait Explain: TripcodeGenerator {
+    /// Explains how the tripcode for `password` is generated.
+    fn explain<P: AsRef<[u8]>>(password: P) -> Explanation<Self::Hash>;
+}
+
+// Escapes HTML special characters in a password into a `Vec<u8>`.
+macro_rules! escaped {
+    // `$escaper`: `fourchan_escape` or `mona_escape`.
+    ($password:expr, $escaper:ident) => {{
+        let mut escaped = Vec::with_capacity($password.len());
+        for &c in $password {
+            $escaper!(c, |e| escaped.extend_from_slice(e), || escaped.push(c));
+        }
+        escaped
+    }};
+    ($password:expr) => ($password.to_vec());
+}
+
+macro_rules! impl_explain {
+    ($(
+        $(#[$attr:meta])*
+        impl for $generator:ty { escape: $($escaper:ident)*, branch: |$p:ident| $branch:expr }
+    )*) => {$(
+        $(#[$attr])*
+        impl Explain for $generator {
+            fn explain<P: AsRef<[u8]>>(password: P) -> Explanation<Self::Hash> {
+                let $p = password.as_ref();
+                explanation(
+                    $p, escaped!($p $(, $escaper)*), $branch, <$generator as TripcodeGenerator>::hash($p)
+                )
+            }
+        }
+    )*};
+}
+
+impl_explain! {
+    impl for Fourchan { escape: fourchan_escape, branch: |_p| Branch::Ten }
+    impl for FourchanNonescaping { escape: , branch: |_p| Branch::Ten }
+    impl for Mona10 { escape: mona_escape, branch: |_p| Branch::Ten }
+
+    impl for Mona { escape: mona_escape, branch: |p| ::mona_branch(p, true) }
+    impl for MonaNonescaping { escape: , branch: |p| ::mona_branch(p, false) }
+    impl for Mona12 { escape: mona_escape, branch: |_p| Branch::Twelve }
+    impl for Mona12Nonescaping { escape: , branch: |_p| Branch::Twelve }
+    impl for Sc { escape: , branch: |p| ::sc_branch(p, sc_password_starts_with_katakana) }
+    impl for ScSjis { escape: , branch: |p| ::sc_branch(p, ::sc_sjis_password_starts_with_katakana) }
+    impl for Sc15 { escape: , branch: |_p| Branch::Fifteen }
+    impl for ScKatakana { escape: , branch: |_p| Branch::Katakana }
+}
+
+/// Builds an explanation, picking the salt characters as the branch does.
+fn explanation<H>(password: &[u8], escaped: Vec<u8>, branch: Branch, hash: H) -> Explanation<H> {
+    let salt = match branch {
+        Branch::Ten => {
+            let (salt1, salt2) = ::salt_chars(&escaped);
+            Some(Salt {
+                chars: [salt1, salt2],
+                reinterpreted: [reinterpret_salt_char(salt1), reinterpret_salt_char(salt2)],
+            })
+        },
+        Branch::NamaKey => {
+            // Missing salt characters of a nama key default to `.`.
+            let chars = match password.len() {
+                17 => Some([b'.', b'.']),
+                18 => Some([password[17], b'.']),
+                19 => Some([password[17], password[18]]),
+                _  => None,
+            };
+            chars.and_then(|c| decode_salt_strict(c[0], c[1]).map(|_| Salt { chars: c, reinterpreted: c }))
+        },
+        _ => None,
+    };
+
+    Explanation { escaped, salt, branch, hash }
+}
+
+/// Returns the character that `decode_salt()` reinterprets a salt character as.
+fn reinterpret_salt_char(c: u8) -> u8 {
+    const SALT_CHARS: &[u8; 64] =
+        b"./0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+    SALT_CHARS[(decode_salt(c, b'.') >> 26) as usize]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hash::MonaHash;
+    use Des;
+
+    macro_rules! assert_explains {
+        ($generator:ty, $password:expr) => {{
+            let explanation = <$generator>::explain($password);
+            assert_eq!(<$generator>::hash($password), explanation.hash);
+            explanation
+        }};
+    }
+
+    #[test]
+    fn des() {
+        let e = assert_explains!(Fourchan, "a&\"");
+        assert_eq!(b"a&amp;&quot;", &e.escaped[..]);
+        assert_eq!(Some(Salt { chars: *b"&a", reinterpreted: *b".a" }), e.salt);
+        assert_eq!(Branch::Ten, e.branch);
+
+        let e = assert_explains!(Mona10, "a&\"");
+        assert_eq!(b"a&&quot;", &e.escaped[..]);
+        assert_eq!(Some(Salt { chars: *b"&&", reinterpreted: *b".." }), e.salt);
+
+        let e = assert_explains!(FourchanNonescaping, "p:");
+        assert_eq!(b"p:", &e.escaped[..]);
+        assert_eq!(Some(Salt { chars: *b":H", reinterpreted: *b"AH" }), e.salt);
+
+        for &p in &["", "a", "pa", "password", "}~\x7F"] {
+            let salt = assert_explains!(FourchanNonescaping, p).salt.unwrap();
+            assert_eq!(FourchanNonescaping::hash(p), Des::hash(p, salt.reinterpreted[0], salt.reinterpreted[1]));
+        }
+    }
+
+    #[test]
+    fn branches() {
+        let e = assert_explains!(Mona, "<<<<");
+        assert_eq!(b"&lt;&lt;&lt;&lt;", &e.escaped[..]);
+        assert_eq!((Branch::Twelve, None), (e.branch, e.salt));
+        assert_eq!(Branch::Ten, assert_explains!(MonaNonescaping, "<<<<").branch);
+
+        let e = assert_explains!(Mona, "#0123456789abcdef./");
+        assert_eq!(Some(Salt { chars: *b"./", reinterpreted: *b"./" }), e.salt);
+        assert_eq!(Branch::NamaKey, e.branch);
+        let e = assert_explains!(Mona, "#0123456789abcdef!");
+        assert_eq!((Branch::NamaKey, None, MonaHash::Error), (e.branch, e.salt, e.hash));
+
+        assert_eq!(Branch::Undefined, assert_explains!(Mona, "$23456789012").branch);
+        assert_eq!(Branch::Twelve, assert_explains!(Mona12, "a").branch);
+
+        assert_eq!(Branch::Fifteen, assert_explains!(Sc, "$0123456789a").branch);
+        assert_eq!(Branch::Katakana, assert_explains!(Sc, "$ｶ123456789a").branch);
+        assert_eq!(Branch::Fifteen, assert_explains!(ScSjis, "$ｶ123456789a").branch);
+        assert_eq!(Branch::Katakana, assert_explains!(ScSjis, b"$\xB6123456789a").branch);
+        assert_eq!(b"<<<<<<<<<<<<", &assert_explains!(Sc, "<<<<<<<<<<<<").escaped[..]);
+        assert_eq!(Branch::Ten, assert_explains!(Sc, "<<<<").branch);
+    }
+}
@@ -32,8 +32,12 @@ assert_eq!(decoded, hash);
 mod enc_dec;
 
 use self::enc_dec::EncoderDecoder;
-use std::{io, mem};
-use std::io::Write;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::{fmt, str};
+use Tripcode;
+#[cfg(feature = "std")]
+use std::io::{self, Write};
 
 /// 58-bit hash value that represents a 10-character tripcode
 /// i.e. 4chan's tripcode or 2channel's 10-character tripcode (10桁トリップ).
@@ -54,6 +58,14 @@ pub struct Sc15Hash(pub u64, pub u32);
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct ScKatakanaHash(pub Sc15Hash);
 
+// Lengths of the tripcodes in bytes, which `max_len()` and `max_len_sjis()` return and which
+// `Tripcode::CAPACITY` is derived from.
+pub(crate) const TEN_LEN: usize = 10;
+pub(crate) const TWELVE_LEN: usize = 12;
+pub(crate) const FIFTEEN_LEN: usize = 15;
+// 15 half-width katakanas, each of which takes 3 bytes in UTF-8 and 1 byte in Shift-JIS.
+pub(crate) const KATAKANA_LEN: usize = 3 * FIFTEEN_LEN;
+
 /// Hash value that represents a 2channel tripcode.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum MonaHash {
@@ -105,8 +117,33 @@ pub trait TripcodeHash : Sized {
     /// Encodes `self` into a tripcode and appends it on a `String`.
     fn append(self, &mut String);
 
+    #[cfg(feature = "std")]
+    #[inline]
     /// Encodes `self` into a tripcode and writes it to a `Write`.
-    fn write<W: Write>(self, &mut W) -> io::Result<()>;
+    ///
+    /// This method is only available with the `std` feature.
+    fn write<W: Write>(self, dst: &mut W) -> io::Result<()> {
+        dst.write_all(self.encode().as_bytes())
+    }
+
+    #[inline]
+    /// Encodes `self` into a tripcode and writes it to a `fmt::Write`.
+    fn write_fmt_to<W: fmt::Write>(self, dst: &mut W) -> fmt::Result {
+        dst.write_str(&self.encode())
+    }
+
+    #[inline]
+    /// Encodes `self` into a tripcode stored in a fixed-capacity `Tripcode` without allocating.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tripcode is longer than `Tripcode::CAPACITY`, which cannot happen with
+    /// the hash types in this crate.
+    fn encode_inline(self) -> Tripcode {
+        let mut tripcode = Tripcode::new();
+        self.write_fmt_to(&mut tripcode).expect("tripcode exceeds the capacity of `Tripcode`");
+        tripcode
+    }
 
     /// Decodes a Shift-JIS-encoded tripcode.
     fn decode_from_sjis(&[u8]) -> Option<Self>;
@@ -114,8 +151,14 @@ pub trait TripcodeHash : Sized {
     /// Encodes `self` into a Shift-JIS-encoded tripcode and appends it on a `Vec<u8>`.
     fn append_sjis(self, &mut Vec<u8>);
 
+    #[cfg(feature = "std")]
+    #[inline]
     /// Encodes `self` into a Shift-JIS-encoded tripcode and writes it to a `Write`.
-    fn write_sjis<W: Write>(self, &mut W) -> io::Result<()>;
+    ///
+    /// This method is only available with the `std` feature.
+    fn write_sjis<W: Write>(self, dst: &mut W) -> io::Result<()> {
+        dst.write_all(&self.encode_to_sjis())
+    }
 
     #[inline]
     /// Encodes `self` into a tripcode.
@@ -148,8 +191,19 @@ pub trait AsciiTripcodeHash : Sized {
     /// Encodes `self` into an ASCII-encoded tripcode and writes it on a `String`.
     fn append_ascii(self, &mut Vec<u8>);
 
+    #[cfg(feature = "std")]
+    #[inline]
     /// Encodes `self` into an ASCII-encoded tripcode and writes it to a `Write`.
-    fn write_ascii<W: Write>(self, &mut W) -> io::Result<()>;
+    fn write_ascii<W: Write>(self, dst: &mut W) -> io::Result<()> {
+        dst.write_all(&self.encode_to_ascii())
+    }
+
+    #[inline]
+    /// Encodes `self` into an ASCII-encoded tripcode and writes it to a `fmt::Write`.
+    fn write_ascii_fmt<W: fmt::Write>(self, dst: &mut W) -> fmt::Result {
+        // ASCII-encoded tripcodes are always valid UTF-8.
+        dst.write_str(str::from_utf8(&self.encode_to_ascii()).unwrap())
+    }
 
     #[inline]
     /// Encodes `self` into an ASCII-encoded tripcode.
@@ -183,11 +237,17 @@ impl<T> TripcodeHash for T where T: AsciiTripcodeHash {
         }
     }
 
+    #[cfg(feature = "std")]
     #[inline]
     fn write<W: Write>(self, dst: &mut W) -> io::Result<()> {
         self.write_ascii(dst)
     }
 
+    #[inline]
+    fn write_fmt_to<W: fmt::Write>(self, dst: &mut W) -> fmt::Result {
+        self.write_ascii_fmt(dst)
+    }
+
     #[inline]
     fn decode_from_sjis(tripcode: &[u8]) -> Option<Self> {
         Self::decode_from_ascii(tripcode)
@@ -198,6 +258,7 @@ impl<T> TripcodeHash for T where T: AsciiTripcodeHash {
         self.append_ascii(dst);
     }
 
+    #[cfg(feature = "std")]
     #[inline]
     fn write_sjis<W: Write>(self, dst: &mut W) -> io::Result<()> {
         self.write_ascii(dst)
@@ -205,9 +266,8 @@ impl<T> TripcodeHash for T where T: AsciiTripcodeHash {
 
     #[inline]
     fn encode(self) -> String {
-        unsafe {
-            mem::transmute(self.encode_to_ascii())
-        }
+        // ASCII-encoded tripcodes are always valid UTF-8.
+        unsafe { String::from_utf8_unchecked(self.encode_to_ascii()) }
     }
 
     #[inline]
@@ -230,7 +290,7 @@ impl AsciiTripcodeHash for MonaHash {
 
     #[inline]
     fn max_len() -> usize {
-        12
+        TWELVE_LEN
     }
 
     fn append_ascii(self, dst: &mut Vec<u8>) {
@@ -243,6 +303,7 @@ impl AsciiTripcodeHash for MonaHash {
         }
     }
 
+    #[cfg(feature = "std")]
     fn write_ascii<W: Write>(self, dst: &mut W) -> io::Result<()> {
         use self::MonaHash::*;
 
@@ -253,6 +314,16 @@ impl AsciiTripcodeHash for MonaHash {
         }
     }
 
+    fn write_ascii_fmt<W: fmt::Write>(self, dst: &mut W) -> fmt::Result {
+        use self::MonaHash::*;
+
+        match self {
+            Twelve(h) => h.write_ascii_fmt(dst),
+            Ten(h) => h.write_ascii_fmt(dst),
+            Error => dst.write_str("???"),
+        }
+    }
+
     fn encode_to_ascii(self) -> Vec<u8> {
         use hash::MonaHash::*;
 
@@ -282,14 +353,12 @@ impl AsciiTripcodeHash for FourchanHash {
 
     #[inline]
     fn max_len() -> usize {
-        10
+        TEN_LEN
     }
 
     fn append_ascii(mut self, dst: &mut Vec<u8>) {
         let len = dst.len();
-        dst.reserve(10);
-
-        unsafe { dst.set_len(len + 10); }
+        dst.resize(len + 10, 0);
 
         for b in dst.iter_mut().skip(len) {
             *b = enc_dec::Crypt::encode((self.0 >> 58) as usize);
@@ -297,15 +366,26 @@ impl AsciiTripcodeHash for FourchanHash {
         }
     }
 
-    fn write_ascii<W: Write>(mut self, dst: &mut W) -> io::Result<()> {
-        let mut buf: [u8; 10] = unsafe { mem::uninitialized() };
+    #[cfg(feature = "std")]
+    fn write_ascii<W: Write>(self, dst: &mut W) -> io::Result<()> {
+        dst.write_all(&self.encode_to_array())
+    }
+
+    fn write_ascii_fmt<W: fmt::Write>(self, dst: &mut W) -> fmt::Result {
+        dst.write_str(str::from_utf8(&self.encode_to_array()).unwrap())
+    }
+}
+
+impl FourchanHash {
+    fn encode_to_array(mut self) -> [u8; 10] {
+        let mut buf = [0u8; 10];
 
         for b in &mut buf {
             *b = enc_dec::Crypt::encode((self.0 >> 58) as usize);
             self.0 <<= 6;
         }
 
-        dst.write_all(&buf)
+        buf
     }
 }
 
@@ -341,20 +421,30 @@ impl AsciiTripcodeHash for Mona12Hash {
 
     #[inline]
     fn max_len() -> usize {
-        12
+        TWELVE_LEN
     }
 
     fn append_ascii(mut self, dst: &mut Vec<u8>) {
         let len = dst.len();
-        dst.reserve(12);
-        unsafe { dst.set_len(len+12); }
+        dst.resize(len + 12, 0);
         encode_mona_12_main!(self, dst[len..]);
     }
 
-    fn write_ascii<W: Write>(mut self, dst: &mut W) -> io::Result<()> {
-        let mut buf: [u8; 12] = unsafe { mem::uninitialized() };
+    #[cfg(feature = "std")]
+    fn write_ascii<W: Write>(self, dst: &mut W) -> io::Result<()> {
+        dst.write_all(&self.encode_to_array())
+    }
+
+    fn write_ascii_fmt<W: fmt::Write>(self, dst: &mut W) -> fmt::Result {
+        dst.write_str(str::from_utf8(&self.encode_to_array()).unwrap())
+    }
+}
+
+impl Mona12Hash {
+    fn encode_to_array(mut self) -> [u8; 12] {
+        let mut buf = [0u8; 12];
         encode_mona_12_main!(self, buf);
-        dst.write_all(&buf)
+        buf
     }
 }
 
@@ -375,12 +465,12 @@ impl TripcodeHash for ScHash {
 
     #[inline]
     fn max_len() -> usize {
-        45
+        KATAKANA_LEN
     }
 
     #[inline]
     fn max_len_sjis() -> usize {
-        15
+        FIFTEEN_LEN
     }
 
     fn append(self, dst: &mut String) {
@@ -395,6 +485,7 @@ impl TripcodeHash for ScHash {
         }
     }
 
+    #[cfg(feature = "std")]
     fn write<W: Write>(self, dst: &mut W) -> io::Result<()> {
         use self::ScHash::*;
 
@@ -407,6 +498,18 @@ impl TripcodeHash for ScHash {
         }
     }
 
+    fn write_fmt_to<W: fmt::Write>(self, dst: &mut W) -> fmt::Result {
+        use self::ScHash::*;
+
+        match self {
+            Ten(h)      => h.write_fmt_to(dst),
+            Twelve(h)   => h.write_fmt_to(dst),
+            Fifteen(h)  => h.write_fmt_to(dst),
+            Katakana(h) => h.write_fmt_to(dst),
+            Error       => dst.write_str("???"),
+        }
+    }
+
     fn decode_from_sjis(tripcode: &[u8]) -> Option<Self> {
         use self::ScHash::*;
 
@@ -433,6 +536,7 @@ impl TripcodeHash for ScHash {
         }
     }
 
+    #[cfg(feature = "std")]
     fn write_sjis<W: Write>(self, dst: &mut W) -> io::Result<()> {
         use self::ScHash::*;
 
@@ -453,7 +557,7 @@ impl TripcodeHash for ScHash {
             Twelve(h)   => h.encode(),
             Fifteen(h)  => h.encode(),
             Katakana(h) => h.encode(),
-            Error       => "???".to_owned(),
+            Error       => String::from("???"),
         }
     }
 
@@ -513,20 +617,30 @@ impl AsciiTripcodeHash for Sc15Hash {
 
     #[inline]
     fn max_len() -> usize {
-        15
+        FIFTEEN_LEN
     }
 
     fn append_ascii(mut self, dst: &mut Vec<u8>) {
         let len = dst.len();
-        dst.reserve(15);
-        unsafe { dst.set_len(len+15); }
+        dst.resize(len + 15, 0);
         encode_sc_sha1_main!(enc_dec::Sc15, self, dst[len..]);
     }
 
-    fn write_ascii<W: Write>(mut self, dst: &mut W) -> io::Result<()> {
-        let mut buf: [u8; 15] = unsafe { mem::uninitialized() };
+    #[cfg(feature = "std")]
+    fn write_ascii<W: Write>(self, dst: &mut W) -> io::Result<()> {
+        dst.write_all(&self.encode_to_array())
+    }
+
+    fn write_ascii_fmt<W: fmt::Write>(self, dst: &mut W) -> fmt::Result {
+        dst.write_str(str::from_utf8(&self.encode_to_array()).unwrap())
+    }
+}
+
+impl Sc15Hash {
+    fn encode_to_array(mut self) -> [u8; 15] {
+        let mut buf = [0u8; 15];
         encode_sc_sha1_main!(enc_dec::Sc15, self, buf);
-        dst.write_all(&buf)
+        buf
     }
 }
 
@@ -614,12 +728,12 @@ impl TripcodeHash for ScKatakanaHash {
 
     #[inline]
     fn max_len() -> usize {
-        45
+        KATAKANA_LEN
     }
 
     #[inline]
     fn max_len_sjis() -> usize {
-        15
+        FIFTEEN_LEN
     }
 
     fn append(mut self, dst: &mut String) {
@@ -636,6 +750,7 @@ impl TripcodeHash for ScKatakanaHash {
         }
     }
 
+    #[cfg(feature = "std")]
     fn write<W: Write>(mut self, dst: &mut W) -> io::Result<()> {
         for _ in 0..10 {
             try!(dst.write_all(enc_dec::ScKatakana::encode(((self.0).0 >> 58) as usize)));
@@ -648,27 +763,131 @@ impl TripcodeHash for ScKatakanaHash {
         Ok(())
     }
 
+    fn write_fmt_to<W: fmt::Write>(mut self, dst: &mut W) -> fmt::Result {
+        // The encoding table only consists of UTF-8 characters.
+        for _ in 0..10 {
+            dst.write_str(str::from_utf8(enc_dec::ScKatakana::encode(((self.0).0 >> 58) as usize)).unwrap())?;
+            (self.0).0 <<= 6;
+        }
+        for _ in 10..15 {
+            dst.write_str(str::from_utf8(enc_dec::ScKatakana::encode(((self.0).1 >> 26) as usize)).unwrap())?;
+            (self.0).1 <<= 6;
+        }
+        Ok(())
+    }
+
     fn decode_from_sjis(tripcode: &[u8]) -> Option<Self> {
         decode_sc_sha1_internal::<enc_dec::ScSjisKatakana, _, _>(tripcode, ScKatakanaHash)
     }
 
     fn append_sjis(mut self, dst: &mut Vec<u8>) {
         let len = dst.len();
-        dst.reserve(15);
-        unsafe { dst.set_len(len+15) };
+        dst.resize(len + 15, 0);
         encode_sc_sha1_main!(enc_dec::ScSjisKatakana, self.0, dst[len..]);
     }
 
+    #[cfg(feature = "std")]
     fn write_sjis<W: Write>(mut self, dst: &mut W) -> io::Result<()> {
-        let mut buf: [u8; 15] = unsafe { mem::uninitialized() };
+        let mut buf = [0u8; 15];
         encode_sc_sha1_main!(enc_dec::ScSjisKatakana, self.0, buf);
         dst.write_all(&buf)
     }
 }
 
+/// Set of characters that can appear at a position of a tripcode.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Alphabet {
+    /// The 64 characters of the crypt(3) encoding, `./0-9A-Za-z`.
+    Crypt,
+    /// The 16 characters `.26AEIMQUYcgkosw` that the last character of a 10-character
+    /// tripcode is one of.
+    CryptLastChar,
+    /// The 64 characters of the base64 encoding, `A-Za-z0-9+/`.
+    Base64,
+    /// The 64 characters of _2ch.sc_'s 15-character tripcode, `A-Za-z0-9.!`.
+    Sc15,
+    /// The 63 half-width katakana and `!` of _2ch.sc_'s katakana tripcode.
+    ScKatakana,
+}
+
+impl Alphabet {
+    /// Returns the characters of the alphabet, each encoded in UTF-8.
+    pub fn chars(self) -> Vec<&'static [u8]> {
+        fn ascii<E: EncoderDecoder>() -> Vec<&'static [u8]> {
+            E::encoding_map().chunks(1).collect()
+        }
+
+        match self {
+            Alphabet::Crypt => ascii::<enc_dec::Crypt>(),
+            Alphabet::CryptLastChar => ascii::<enc_dec::Crypt>().into_iter()
+                .filter(|c| enc_dec::CryptLastChar::decode(c[0]) != 0x40)
+                .collect(),
+            Alphabet::Base64 => ascii::<enc_dec::Base64>(),
+            Alphabet::Sc15 => ascii::<enc_dec::Sc15>(),
+            Alphabet::ScKatakana => (0..64).map(enc_dec::ScKatakana::encode).collect(),
+        }
+    }
+}
+
+/// Trait for hash values whose tripcodes consist of a fixed alphabet at each position.
+///
+/// A hash value of a uniformly random password is assumed to yield any character of the
+/// alphabet at each position with equal probability.
+pub trait TripcodeAlphabets: TripcodeHash {
+    /// Returns the alphabet of each position of the tripcode that a password of
+    /// `password_len` bytes yields.
+    ///
+    /// For `MonaHash` and `ScHash`, passwords are assumed neither to start with `#` or `$` nor
+    /// to be lengthened by escaping.
+    fn alphabets(password_len: usize) -> Vec<Alphabet>;
+}
+
+impl TripcodeAlphabets for FourchanHash {
+    fn alphabets(_: usize) -> Vec<Alphabet> {
+        let mut alphabets = alloc::vec![Alphabet::Crypt; 9];
+        alphabets.push(Alphabet::CryptLastChar);
+        alphabets
+    }
+}
+
+impl TripcodeAlphabets for Mona12Hash {
+    fn alphabets(_: usize) -> Vec<Alphabet> {
+        alloc::vec![Alphabet::Base64; 12]
+    }
+}
+
+impl TripcodeAlphabets for MonaHash {
+    fn alphabets(password_len: usize) -> Vec<Alphabet> {
+        if password_len >= 12 {
+            Mona12Hash::alphabets(password_len)
+        } else {
+            Mona10Hash::alphabets(password_len)
+        }
+    }
+}
+
+impl TripcodeAlphabets for ScHash {
+    fn alphabets(password_len: usize) -> Vec<Alphabet> {
+        MonaHash::alphabets(password_len)
+    }
+}
+
+impl TripcodeAlphabets for Sc15Hash {
+    fn alphabets(_: usize) -> Vec<Alphabet> {
+        alloc::vec![Alphabet::Sc15; 15]
+    }
+}
+
+impl TripcodeAlphabets for ScKatakanaHash {
+    fn alphabets(_: usize) -> Vec<Alphabet> {
+        alloc::vec![Alphabet::ScKatakana; 15]
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use core::cmp;
 
     macro_rules! test_dec_enc {
         ($h:ident, $t:expr) => (assert_eq!($t, &$h::decode($t).unwrap().encode()))
@@ -736,6 +955,73 @@ mod tests {
         assert_eq!(w, ScHash::decode_from_sjis(&w.encode_to_sjis()).unwrap());
     }
 
+    #[test]
+    fn alphabets() {
+        fn check<H: TripcodeAlphabets>(hash: H, password_len: usize) {
+            let tripcode = hash.encode();
+            let alphabets = H::alphabets(password_len);
+            let mut rest = tripcode.as_bytes();
+            for a in &alphabets {
+                let c = a.chars().into_iter().find(|c| rest.starts_with(c))
+                    .unwrap_or_else(|| panic!("{:?} does not match {:?} of {}", a, rest, tripcode));
+                rest = &rest[c.len()..];
+            }
+            assert!(rest.is_empty());
+        }
+
+        assert_eq!(16, Alphabet::CryptLastChar.chars().len());
+        assert_eq!(64, Alphabet::ScKatakana.chars().len());
+
+        let mut x = 0x0123_4567_89AB_CDEFu64;
+        for _ in 0..64 {
+            x = x.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            let h10 = FourchanHash(x & !0x3F);
+            let h12 = Mona12Hash(x, (x >> 7) as u8);
+            let h15 = Sc15Hash(x & !0xF, (x >> 32) as u32 & !0b11);
+            check(h10, 8);
+            check(h12, 12);
+            check(MonaHash::Ten(h10), 8);
+            check(MonaHash::Twelve(h12), 12);
+            check(ScHash::Twelve(h12), 16);
+            check(h15, 16);
+            check(ScKatakanaHash(h15), 16);
+        }
+    }
+
+    #[test]
+    fn write_fmt_to() {
+        fn max_len_of<H: TripcodeHash>(_: &H) -> usize {
+            cmp::max(H::max_len(), H::max_len_sjis())
+        }
+        let mut max_len = 0;
+
+        macro_rules! test_write_fmt_to {
+            ($($h:expr),*) => {$(
+                let mut tripcode = String::new();
+                $h.write_fmt_to(&mut tripcode).unwrap();
+                assert_eq!($h.encode(), tripcode);
+                assert_eq!(tripcode, &*$h.encode_inline());
+                max_len = cmp::max(max_len, max_len_of(&$h));
+            )*};
+        }
+
+        let h15 = Sc15Hash(0x0123456789ABCDE0, 0xFEDCBA98);
+        let hk = ScKatakanaHash(h15);
+        test_write_fmt_to!(
+            FourchanHash(0xD3F6B95622CD44C0),
+            Mona12Hash(0x0123456789ABCDEF, 0x55),
+            MonaHash::Ten(FourchanHash(0xD3F6B95622CD44C0)),
+            MonaHash::Twelve(Mona12Hash(0x0123456789ABCDEF, 0x55)),
+            MonaHash::Error,
+            h15,
+            hk,
+            ScHash::Fifteen(h15),
+            ScHash::Katakana(hk),
+            ScHash::Error
+        );
+        assert_eq!(Tripcode::CAPACITY, max_len);
+    }
+
     #[test]
     fn decode_fails() {
         assert!(Mona10Hash::decode("hocho.🔪").is_none());
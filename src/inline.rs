@@ -0,0 +1,217 @@
+// Copyright 2016 Huton. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use core::{cmp, fmt, hash, str};
+use core::borrow::Borrow;
+use core::ops::Deref;
+use hash::{TEN_LEN, TWELVE_LEN, FIFTEEN_LEN, KATAKANA_LEN};
+
+/// Tripcode stored in a fixed-capacity buffer instead of the heap.
+///
+/// The buffer is large enough to hold any tripcode generated by the generators in this crate
+/// (i.e. up to `TripcodeHash::max_len()` bytes), so creating a `Tripcode` never allocates.
+/// It dereferences to `&str`.
+///
+/// # Examples
+///
+/// ```
+/// use tripcode::*;
+///
+/// let tripcode = Fourchan::generate_inline("password");
+/// assert_eq!("ozOtJW9BFA", &*tripcode);
+/// assert!(tripcode.starts_with("ozOt"));
+/// ```
+#[derive(Copy, Clone)]
+pub struct Tripcode {
+    buf: [u8; Tripcode::CAPACITY],
+    len: u8,
+}
+
+impl Tripcode {
+    /// Capacity of the buffer, in bytes.
+    ///
+    /// This is the largest `TripcodeHash::max_len()` and `max_len_sjis()` of the hash types in
+    /// this crate, i.e. the length of _2ch.sc_'s katakana tripcodes in UTF-8.
+    pub const CAPACITY: usize = max(&[TEN_LEN, TWELVE_LEN, FIFTEEN_LEN, KATAKANA_LEN]);
+
+    #[inline]
+    /// Creates an empty `Tripcode`.
+    pub fn new() -> Self {
+        Tripcode { buf: [0; Tripcode::CAPACITY], len: 0 }
+    }
+
+    #[inline]
+    /// Extracts a string slice containing the entire tripcode.
+    pub fn as_str(&self) -> &str {
+        let bytes = &self.buf[..self.len as usize];
+        debug_assert!(str::from_utf8(bytes).is_ok());
+        // Safety: `buf[..len]` is only ever written by `fmt::Write::write_str()`, which appends
+        // whole `&str`s and leaves the tripcode unchanged when one does not fit, so it is always
+        // a concatenation of valid UTF-8 strings.
+        unsafe { str::from_utf8_unchecked(bytes) }
+    }
+}
+
+// The length is stored in a `u8`.
+const _: () = assert!(Tripcode::CAPACITY <= u8::MAX as usize);
+
+const fn max(lens: &[usize]) -> usize {
+    let mut max = 0;
+    let mut i = 0;
+    while i < lens.len() {
+        if lens[i] > max {
+            max = lens[i];
+        }
+        i += 1;
+    }
+    max
+}
+
+impl Default for Tripcode {
+    #[inline]
+    fn default() -> Self {
+        Tripcode::new()
+    }
+}
+
+impl fmt::Write for Tripcode {
+    /// Appends `s` to the tripcode.
+    ///
+    /// Returns an error if `s` does not fit in the rest of the buffer, in which case
+    /// the tripcode is left unchanged.
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let len = self.len as usize;
+        if s.len() > Tripcode::CAPACITY - len {
+            return Err(fmt::Error);
+        }
+        self.buf[len..len+s.len()].copy_from_slice(s.as_bytes());
+        self.len += s.len() as u8;
+        Ok(())
+    }
+}
+
+impl Deref for Tripcode {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for Tripcode {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<[u8]> for Tripcode {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        self.as_str().as_bytes()
+    }
+}
+
+impl Borrow<str> for Tripcode {
+    #[inline]
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Debug for Tripcode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for Tripcode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl PartialEq for Tripcode {
+    #[inline]
+    fn eq(&self, other: &Tripcode) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for Tripcode {}
+
+impl PartialEq<str> for Tripcode {
+    #[inline]
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for Tripcode {
+    #[inline]
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<Tripcode> for str {
+    #[inline]
+    fn eq(&self, other: &Tripcode) -> bool {
+        self == other.as_str()
+    }
+}
+
+impl PartialEq<Tripcode> for &str {
+    #[inline]
+    fn eq(&self, other: &Tripcode) -> bool {
+        *self == other.as_str()
+    }
+}
+
+impl PartialOrd for Tripcode {
+    #[inline]
+    fn partial_cmp(&self, other: &Tripcode) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Tripcode {
+    #[inline]
+    fn cmp(&self, other: &Tripcode) -> cmp::Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl hash::Hash for Tripcode {
+    #[inline]
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::fmt::Write;
+
+    #[test]
+    fn write_str() {
+        let mut tripcode = Tripcode::new();
+        assert_eq!("", &*tripcode);
+
+        tripcode.write_str("ｶﾀｶﾅﾄﾘｯﾌﾟ").unwrap();
+        tripcode.write_str("ﾃｽﾄﾃﾞｽ").unwrap();
+        assert_eq!("ｶﾀｶﾅﾄﾘｯﾌﾟﾃｽﾄﾃﾞｽ", tripcode);
+        assert_eq!(Tripcode::CAPACITY, tripcode.len());
+
+        assert!(tripcode.write_str("!").is_err());
+        assert_eq!("ｶﾀｶﾅﾄﾘｯﾌﾟﾃｽﾄﾃﾞｽ", tripcode);
+    }
+}
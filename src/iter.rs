@@ -0,0 +1,310 @@
+// Copyright 2016 Huton. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+Iterator adapters for generating tripcodes from many passwords.
+
+The adapters hash passwords in batches through `TripcodeGenerator::hash_many()`, so generators
+that have a bulk hashing routine run faster than calling `hash()` in a loop.
+
+A batch is filled before any of it is hashed: the first item of an adapter, and the first one
+after each batch, waits until 64 passwords have been pulled from the underlying iterator or it
+is exhausted. For passwords that arrive over time, e.g. lines read from a terminal or a pipe,
+`batch_len()` of the adapters makes the batches smaller, down to one password at a time.
+
+# Examples
+
+```
+use tripcode::*;
+
+let passwords = vec!["a", "b", "c"];
+
+// Yielding hash values.
+let hashes: Vec<_> = passwords.iter().hashes::<Fourchan>().collect();
+assert_eq!(hashes[0], Fourchan::hash("a"));
+
+// Yielding tripcodes borrowed from a buffer that is reused between iterations.
+let mut tripcodes = passwords.iter().tripcodes::<Fourchan>();
+assert_eq!(Some("ZnBI2EKkq."), tripcodes.next_tripcode());
+assert_eq!(Some((&"b", "taAZ7oPCCM")), tripcodes.next_with_password());
+assert_eq!(Some("wG1CV58ydQ"), tripcodes.next_tripcode());
+assert_eq!(None, tripcodes.next_tripcode());
+```
+*/
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use hash::TripcodeHash;
+use TripcodeGenerator;
+
+/// Maximum number of passwords passed to `TripcodeGenerator::hash_many()` at once by default.
+const BATCH_LEN: usize = 64;
+
+/// Extension trait that adds tripcode generating adapters to iterators over passwords.
+///
+/// This trait is implemented for every iterator whose items implement `AsRef<[u8]>`.
+pub trait TripcodeIterator : Iterator + Sized where Self::Item: AsRef<[u8]> {
+    #[inline]
+    /// Creates an iterator that yields the hash value for each password.
+    fn hashes<G: TripcodeGenerator>(self) -> Hashes<Self, G> {
+        Hashes { batch: Batch::new(self) }
+    }
+
+    #[inline]
+    /// Creates an iterator that yields each password along with its hash value.
+    fn hashes_with_passwords<G: TripcodeGenerator>(self) -> HashesWithPasswords<Self, G> {
+        HashesWithPasswords { batch: Batch::new(self) }
+    }
+
+    #[inline]
+    /// Creates an adapter that yields the tripcode for each password.
+    ///
+    /// Every tripcode is written into the same buffer, so the adapter does not allocate
+    /// once the buffer has grown to the maximum length of the tripcodes.
+    fn tripcodes<G: TripcodeGenerator>(self) -> Tripcodes<Self, G> {
+        Tripcodes {
+            batch: Batch::new(self),
+            buf: String::with_capacity(<G::Hash as TripcodeHash>::max_len()),
+        }
+    }
+}
+
+impl<I> TripcodeIterator for I where I: Iterator, I::Item: AsRef<[u8]> {}
+
+/// Iterator that yields the hash value for each password.
+///
+/// This struct is created by the `hashes()` method on `TripcodeIterator`.
+pub struct Hashes<I, G> where I: Iterator, I::Item: AsRef<[u8]>, G: TripcodeGenerator {
+    batch: Batch<I, G>,
+}
+
+impl<I, G> Hashes<I, G> where I: Iterator, I::Item: AsRef<[u8]>, G: TripcodeGenerator {
+    /// Sets the maximum number of passwords that are pulled from the underlying iterator and
+    /// hashed at once, which defaults to 64. See the [module documentation](index.html).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is 0.
+    pub fn batch_len(mut self, len: usize) -> Self {
+        self.batch.set_len(len);
+        self
+    }
+}
+
+impl<I, G> Iterator for Hashes<I, G> where I: Iterator, I::Item: AsRef<[u8]>, G: TripcodeGenerator {
+    type Item = G::Hash;
+
+    #[inline]
+    fn next(&mut self) -> Option<G::Hash> {
+        self.batch.next().map(|(_, h)| h)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.batch.size_hint()
+    }
+}
+
+/// Iterator that yields each password along with its hash value.
+///
+/// This struct is created by the `hashes_with_passwords()` method on `TripcodeIterator`.
+pub struct HashesWithPasswords<I, G> where I: Iterator, I::Item: AsRef<[u8]>, G: TripcodeGenerator {
+    batch: Batch<I, G>,
+}
+
+impl<I, G> HashesWithPasswords<I, G>
+    where I: Iterator, I::Item: AsRef<[u8]>, G: TripcodeGenerator
+{
+    /// Sets the maximum number of passwords that are pulled from the underlying iterator and
+    /// hashed at once, which defaults to 64. See the [module documentation](index.html).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is 0.
+    pub fn batch_len(mut self, len: usize) -> Self {
+        self.batch.set_len(len);
+        self
+    }
+}
+
+impl<I, G> Iterator for HashesWithPasswords<I, G>
+    where I: Iterator, I::Item: AsRef<[u8]>, G: TripcodeGenerator
+{
+    type Item = (I::Item, G::Hash);
+
+    #[inline]
+    fn next(&mut self) -> Option<(I::Item, G::Hash)> {
+        self.batch.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.batch.size_hint()
+    }
+}
+
+/// Adapter that yields the tripcode for each password.
+///
+/// Since the yielded tripcodes borrow the internal buffer of the adapter, this struct
+/// does not implement `Iterator`. Use `next_tripcode()` or `next_with_password()` instead.
+///
+/// This struct is created by the `tripcodes()` method on `TripcodeIterator`.
+pub struct Tripcodes<I, G> where I: Iterator, I::Item: AsRef<[u8]>, G: TripcodeGenerator {
+    batch: Batch<I, G>,
+    buf: String,
+}
+
+impl<I, G> Tripcodes<I, G> where I: Iterator, I::Item: AsRef<[u8]>, G: TripcodeGenerator {
+    /// Sets the maximum number of passwords that are pulled from the underlying iterator and
+    /// hashed at once, which defaults to 64. See the [module documentation](index.html).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is 0.
+    pub fn batch_len(mut self, len: usize) -> Self {
+        self.batch.set_len(len);
+        self
+    }
+
+    #[inline]
+    /// Generates the tripcode for the next password.
+    ///
+    /// Returns `None` when the passwords are exhausted.
+    pub fn next_tripcode(&mut self) -> Option<&str> {
+        self.next_with_password().map(|(_, t)| t)
+    }
+
+    /// Generates the tripcode for the next password and yields it along with the password.
+    ///
+    /// Returns `None` when the passwords are exhausted.
+    pub fn next_with_password(&mut self) -> Option<(I::Item, &str)> {
+        let (password, hash) = self.batch.next()?;
+
+        self.buf.clear();
+        hash.append(&mut self.buf);
+
+        Some((password, &self.buf))
+    }
+}
+
+/// Pulls passwords from an iterator and hashes them batch by batch.
+struct Batch<I, G> where I: Iterator, I::Item: AsRef<[u8]>, G: TripcodeGenerator {
+    iter: I,
+    /// Maximum number of passwords in a batch.
+    len: usize,
+    // Both of the buffers are stored in reverse order so that `pop()` yields the next item.
+    passwords: Vec<I::Item>,
+    hashes: Vec<G::Hash>,
+    _generator: PhantomData<G>,
+}
+
+impl<I, G> Batch<I, G> where I: Iterator, I::Item: AsRef<[u8]>, G: TripcodeGenerator {
+    fn new(iter: I) -> Self {
+        Batch {
+            iter,
+            len: BATCH_LEN,
+            passwords: Vec::new(),
+            hashes: Vec::new(),
+            _generator: PhantomData,
+        }
+    }
+
+    fn set_len(&mut self, len: usize) {
+        assert!(len > 0, "batch length must be positive");
+        self.len = len;
+    }
+
+    fn next(&mut self) -> Option<(I::Item, G::Hash)> {
+        if self.passwords.is_empty() {
+            // Fill the batch regardless of `size_hint()`, whose lower bound is 0 for most
+            // iterators of unknown length, e.g. over lines of an input.
+            self.passwords.extend(self.iter.by_ref().take(self.len));
+            G::hash_many(&self.passwords, &mut self.hashes);
+            self.passwords.reverse();
+            self.hashes.reverse();
+        }
+
+        match (self.passwords.pop(), self.hashes.pop()) {
+            (Some(p), Some(h)) => Some((p, h)),
+            _                  => None,
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.iter.size_hint();
+        let len = self.passwords.len();
+        (lower.saturating_add(len), upper.and_then(|u| u.checked_add(len)))
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+    use *;
+
+    #[test]
+    fn hashes() {
+        let passwords: Vec<String> = (0..200u32).map(|i| "pass".to_string() + &i.to_string()).collect();
+
+        macro_rules! test_hashes {
+            ($($generator:ty),*) => {$(
+                let expected: Vec<_> = passwords.iter().map(<$generator>::hash).collect();
+                assert_eq!(expected, passwords.iter().hashes::<$generator>().collect::<Vec<_>>());
+
+                let expected: Vec<_> = passwords.iter().map(|p| (p, <$generator>::hash(p))).collect();
+                assert_eq!(expected,
+                    passwords.iter().hashes_with_passwords::<$generator>().collect::<Vec<_>>());
+            )*};
+        }
+
+        test_hashes!(Fourchan, FourchanNonescaping, Mona, Mona10, Mona12, Sc, Sc15);
+    }
+
+    #[test]
+    fn tripcodes() {
+        let passwords = ["", "a", "&\"", "twelve bytes", "$0123456789a"];
+        let mut tripcodes = passwords.iter().tripcodes::<Sc>();
+
+        for p in &passwords {
+            assert_eq!((p, &Sc::generate(p) as &str), tripcodes.next_with_password().unwrap());
+        }
+        assert!(tripcodes.next_tripcode().is_none());
+    }
+
+    #[test]
+    fn unknown_len() {
+        // Passwords are hashed in full batches even if the iterator cannot tell how many of
+        // them are left.
+        let mut pulled = 0;
+        {
+            let passwords = (0u32..).map(|i| { pulled += 1; i.to_string() }).filter(|_| true);
+            assert_eq!(0, passwords.size_hint().0);
+            let mut tripcodes = passwords.tripcodes::<Fourchan>();
+            assert_eq!(Some(&Fourchan::generate("0") as &str), tripcodes.next_tripcode());
+            assert_eq!(Some(&Fourchan::generate("1") as &str), tripcodes.next_tripcode());
+        }
+        assert_eq!(BATCH_LEN, pulled);
+
+        // Smaller batches pull fewer passwords ahead.
+        pulled = 0;
+        {
+            let passwords = (0u32..).map(|i| { pulled += 1; i.to_string() });
+            let mut hashes = passwords.hashes::<Fourchan>().batch_len(1);
+            assert_eq!(Some(Fourchan::hash("0")), hashes.next());
+        }
+        assert_eq!(1, pulled);
+
+        let passwords: Vec<String> = (0..150u32).map(|i| i.to_string()).collect();
+        let expected: Vec<_> = passwords.iter().map(Fourchan::hash).collect();
+        let hashes: Vec<_> = passwords.iter().map_while(Some).hashes::<Fourchan>().collect();
+        assert_eq!(expected, hashes);
+    }
+}
@@ -141,24 +141,80 @@ let mut tripcode = [0u8; 10];
 Fourchan::write("Writing to stream", &mut (&mut tripcode as &mut [u8])).unwrap();
 assert_eq!("N5MkEeXGtk", String::from_utf8_lossy(&tripcode));
 ```
+
+The `write_fmt_to()` method writes to a `core::fmt::Write` instead, such as a `fmt::Formatter`:
+
+```
+use std::fmt;
+use tripcode::*;
+
+struct Signature<'a>(&'a str, &'a str);
+
+impl<'a> fmt::Display for Signature<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ◆", self.0)?;
+        Fourchan::write_fmt_to(self.1, f)
+    }
+}
+
+assert_eq!("Anonymous ◆ozOtJW9BFA", Signature("Anonymous", "password").to_string());
+```
+
+## `no_std` support
+
+This crate can be used without the standard library (but with `alloc`) by disabling
+the default features:
+
+```toml
+[dependencies]
+tripcode = { version = "0.2", default-features = false }
+```
+
+Without the `std` feature, the methods that write to `std::io::Write` are unavailable.
+Use `TripcodeHash::write_fmt_to()`, which writes to `core::fmt::Write`, instead.
 */
 
+#![no_std]
 #![warn(missing_docs)]
 
-extern crate crypto;
-
-mod des;
+#[cfg(feature = "std")]
+#[macro_use]
+extern crate std;
+extern crate alloc;
+#[cfg(feature = "regex")]
+extern crate regex_automata;
+
+pub mod des;
+mod inline;
+pub mod sha1;
 #[macro_use]
 mod util;
 
-use crypto::sha1::Sha1;
-use crypto::digest::Digest;
+use explain::Branch;
 use hash::*;
+use sha1::Sha1;
 use util::*;
-use std::{io, mem};
+pub use explain::Explain;
+pub use inline::Tripcode;
+pub use iter::TripcodeIterator;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+#[cfg(feature = "std")]
+use std::io;
+#[cfg(feature = "std")]
 use std::io::Write;
 
+pub mod audit;
+#[cfg(feature = "regex")]
+pub mod estimate;
+pub mod explain;
 pub mod hash;
+pub mod iter;
+#[cfg(feature = "std")]
+pub mod rainbow;
+#[cfg(feature = "std")]
+pub mod search;
 
 /// Generator for tripcodes on 4chan.
 pub struct Fourchan;
@@ -267,24 +323,48 @@ pub trait TripcodeGenerator {
     /// Generates a hash value that represents the tripcode for `password`.
     fn hash<P: AsRef<[u8]>>(password: P) -> Self::Hash;
 
+    /// Generates hash values for a batch of passwords and appends them to `dst`
+    /// in the same order as `passwords`.
+    ///
+    /// Generators that have a bulk hashing routine (the DES-based ones, and `Mona12`, `Sc15` and
+    /// their variants, which hash short passwords with SIMD SHA-1 where available) override
+    /// this method. The adapters in the [`iter`](iter/index.html) module call it so that they
+    /// can take advantage of such routines.
+    fn hash_many<P: AsRef<[u8]>>(passwords: &[P], dst: &mut Vec<Self::Hash>) {
+        dst.extend(passwords.iter().map(Self::hash));
+    }
+
     #[inline]
     /// Generates a tripcode from `password`.
     fn generate<P: AsRef<[u8]>>(password: P) -> String {
         Self::hash(&password).encode()
     }
 
+    #[inline]
+    /// Generates a tripcode from `password` into a fixed-capacity `Tripcode` without allocating.
+    fn generate_inline<P: AsRef<[u8]>>(password: P) -> Tripcode {
+        Self::hash(&password).encode_inline()
+    }
+
     #[inline]
     /// Generates a tripcode from `password` and appends it to a `String`.
     fn append<P: AsRef<[u8]>>(password: P, dst: &mut String) {
         Self::hash(&password).append(dst);
     }
 
+    #[cfg(feature = "std")]
     #[inline]
     /// Generates a tripcode into a `Write`.
     fn write<P, W>(password: P, dst: &mut W) -> io::Result<()> where P: AsRef<[u8]>, W: Write {
         Self::hash(&password).write(dst)
     }
 
+    #[inline]
+    /// Generates a tripcode into a `fmt::Write`.
+    fn write_fmt_to<P, W>(password: P, dst: &mut W) -> fmt::Result where P: AsRef<[u8]>, W: fmt::Write {
+        Self::hash(&password).write_fmt_to(dst)
+    }
+
     #[inline]
     /// Generates a tripcode in Shift-JIS encoding.
     fn generate_sjis<P: AsRef<[u8]>>(password: P) -> Vec<u8> {
@@ -297,6 +377,7 @@ pub trait TripcodeGenerator {
         Self::hash(&password).append_sjis(dst)
     }
 
+    #[cfg(feature = "std")]
     #[inline]
     /// Generates a Shift-JIS-encoded tripcode into a `Write`.
     fn write_sjis<P, W>(password: P, dst: &mut W) -> io::Result<()>
@@ -327,6 +408,14 @@ pub trait TripcodeGeneratorFailable {
         Self::try_hash(password).map(|h| h.encode())
     }
 
+    #[inline]
+    /// Attempts to generate a tripcode into a fixed-capacity `Tripcode` without allocating.
+    ///
+    /// Returns `None` when passed an invalid password.
+    fn try_generate_inline<P: AsRef<[u8]>>(password: P) -> Option<Tripcode> {
+        Self::try_hash(password).map(|h| h.encode_inline())
+    }
+
     #[inline]
     /// Attempts to generate a tripcode and append it to a `String`.
     ///
@@ -335,6 +424,7 @@ pub trait TripcodeGeneratorFailable {
         Self::try_hash(password).map(|h| h.append(dst))
     }
 
+    #[cfg(feature = "std")]
     #[inline]
     /// Attempts to generate a tripcode into a `Write`.
     ///
@@ -345,6 +435,16 @@ pub trait TripcodeGeneratorFailable {
         Self::try_hash(password).map(|h| h.write(dst))
     }
 
+    #[inline]
+    /// Attempts to generate a tripcode into a `fmt::Write`.
+    ///
+    /// Returns `None` when passed an invalid password.
+    fn try_write_fmt_to<P, W>(password: P, dst: &mut W) -> Option<fmt::Result>
+        where P: AsRef<[u8]>, W: fmt::Write
+    {
+        Self::try_hash(password).map(|h| h.write_fmt_to(dst))
+    }
+
     #[inline]
     /// Attempts to generate a tripcode in Shift-JIS encoding.
     ///
@@ -362,6 +462,7 @@ pub trait TripcodeGeneratorFailable {
         Self::try_hash(password).map(|h| h.append_sjis(dst))
     }
 
+    #[cfg(feature = "std")]
     #[inline]
     /// Attempts to generate a Shift-JIS-encoded tripcode into a `Write`.
     ///
@@ -382,8 +483,8 @@ impl<T> TripcodeGeneratorFailable for T where T: TripcodeGenerator {
     }
 }
 
-// Escapes HTML special characters in a password and yields a DES hash value of the password.
-macro_rules! des_cipher_escaped {
+// Escapes HTML special characters in a password and yields a pair of DES key and salt.
+macro_rules! des_key_escaped {
     // `$escaper`: `fourchan_escape` or `mona_escape`.
     ($password:expr, $escaper:ident) => {{
         let mut key = 0u64;
@@ -421,7 +522,16 @@ macro_rules! des_cipher_escaped {
         key = key << 1 & 0xFEFE_FEFE_FEFE_FEFE;
         let salt = decode_salt(salt1, salt2);
 
-        des::zero_cipher_58(key, salt)
+        (key, salt)
+    }};
+}
+
+// Hashes a batch of passwords with the bulk DES routine.
+macro_rules! des_hash_many {
+    ($passwords:expr, $dst:expr, |$p:ident| $key:expr) => {{
+        let mut ciphers = Vec::with_capacity($passwords.len());
+        des::zero_cipher_58_many($passwords.iter().map(|$p| $key), &mut ciphers);
+        $dst.extend(ciphers.into_iter().map(FourchanHash));
     }};
 }
 
@@ -429,7 +539,12 @@ impl TripcodeGenerator for Fourchan {
     type Hash = FourchanHash;
 
     fn hash<P: AsRef<[u8]>>(password: P) -> Self::Hash {
-        FourchanHash(des_cipher_escaped!(password.as_ref(), fourchan_escape))
+        let (key, salt) = des_key_escaped!(password.as_ref(), fourchan_escape);
+        FourchanHash(des::zero_cipher_58(key, salt))
+    }
+
+    fn hash_many<P: AsRef<[u8]>>(passwords: &[P], dst: &mut Vec<Self::Hash>) {
+        des_hash_many!(passwords, dst, |p| des_key_escaped!(p.as_ref(), fourchan_escape));
     }
 }
 
@@ -437,47 +552,65 @@ impl TripcodeGenerator for FourchanNonescaping {
     type Hash = FourchanHash;
 
     fn hash<P: AsRef<[u8]>>(password: P) -> Self::Hash {
-        let as_ref = password.as_ref();
-
-        let (salt1, salt2) = match as_ref.len() {
-            0 | 1 => (b'H', b'.'),
-            2 => (as_ref[1], b'H'),
-            _ => (as_ref[1], as_ref[2]),
-        };
+        let (key, salt) = nonescaping_key(password.as_ref());
+        FourchanHash(des::zero_cipher_58(key, salt))
+    }
 
-        Des::hash(&password, salt1, salt2)
+    fn hash_many<P: AsRef<[u8]>>(passwords: &[P], dst: &mut Vec<Self::Hash>) {
+        des_hash_many!(passwords, dst, |p| nonescaping_key(p.as_ref()));
     }
 }
 
-fn mona_internal<P, H, I>(password: P, escape: bool) -> MonaHash
-    where P: AsRef<[u8]>, H: TripcodeGenerator<Hash=Mona10Hash>, I: TripcodeGenerator<Hash=Mona12Hash>
-{
-    use hash::MonaHash::*;
+/// Yields a pair of DES key and salt from an unescaped password.
+fn nonescaping_key(password: &[u8]) -> (u64, u32) {
+    let (salt1, salt2) = salt_chars(password);
+    (secret_to_key(password), decode_salt(salt1, salt2))
+}
 
-    let as_ref = password.as_ref();
+/// Picks the salt characters of a 10-character tripcode from an (escaped) password.
+fn salt_chars(password: &[u8]) -> (u8, u8) {
+    match password.len() {
+        0 | 1 => (b'H', b'.'),
+        2 => (password[1], b'H'),
+        _ => (password[1], password[2]),
+    }
+}
 
+/// Determines which kind of tripcode `Mona` (or `MonaNonescaping` if `escape` is `false`)
+/// generates from `password`.
+fn mona_branch(password: &[u8], escape: bool) -> Branch {
     let len = if escape {
-        as_ref.into_iter()
+        password.into_iter()
             .map(|&c| mona_escape!(c, |escaped| escaped.len(), || 1 as usize))
             .sum()
     } else {
-        as_ref.len()
+        password.len()
     };
 
     if len >= 12 {
-        let sign = as_ref[0];
-        if sign == b'#' {
-            match MonaRaw::try_hash(&password) {
-                Some(h) => Ten(h),
-                None    => Error,
-            }
-        } else if sign == b'$' {
-            Error
-        } else {
-            Twelve(I::hash(&password))
+        match password[0] {
+            b'#' => Branch::NamaKey,
+            b'$' => Branch::Undefined,
+            _    => Branch::Twelve,
         }
     } else {
-        Ten(H::hash(&password))
+        Branch::Ten
+    }
+}
+
+fn mona_internal<P, H, I>(password: P, escape: bool) -> MonaHash
+    where P: AsRef<[u8]>, H: TripcodeGenerator<Hash=Mona10Hash>, I: TripcodeGenerator<Hash=Mona12Hash>
+{
+    use hash::MonaHash::*;
+
+    match mona_branch(password.as_ref(), escape) {
+        Branch::NamaKey => match MonaRaw::try_hash(&password) {
+            Some(h) => Ten(h),
+            None    => Error,
+        },
+        Branch::Undefined => Error,
+        Branch::Twelve => Twelve(I::hash(&password)),
+        _ => Ten(H::hash(&password)),
     }
 }
 
@@ -501,7 +634,12 @@ impl TripcodeGenerator for Mona10 {
     type Hash = Mona10Hash;
 
     fn hash<P: AsRef<[u8]>>(password: P) -> Self::Hash {
-        Mona10Hash(des_cipher_escaped!(password.as_ref(), mona_escape))
+        let (key, salt) = des_key_escaped!(password.as_ref(), mona_escape);
+        Mona10Hash(des::zero_cipher_58(key, salt))
+    }
+
+    fn hash_many<P: AsRef<[u8]>>(passwords: &[P], dst: &mut Vec<Self::Hash>) {
+        des_hash_many!(passwords, dst, |p| des_key_escaped!(p.as_ref(), mona_escape));
     }
 }
 
@@ -560,7 +698,6 @@ fn sha1_internal<T, F>(password: &[u8], escape: bool, result: F) -> T
     where F: Fn(&[u8; 20]) -> T
 {
     let mut sha1 = Sha1::new();
-    let mut digest: [u8; 20] = unsafe { mem::uninitialized() };
 
     if escape {
         let mut first = 0;
@@ -575,18 +712,64 @@ fn sha1_internal<T, F>(password: &[u8], escape: bool, result: F) -> T
     } else {
         sha1.input(password);
     }
-    sha1.result(&mut digest);
+    result(&sha1.result())
+}
 
-    result(&digest)
+/// Same as `sha1_internal` but for a batch of passwords, whose results are appended to `dst`.
+///
+/// Passwords that fit in a single SHA-1 block (after escaping) are digested several at a time.
+fn sha1_many<P, T, F>(passwords: &[P], escape: bool, result: F, dst: &mut Vec<T>)
+    where P: AsRef<[u8]>, F: Fn(&[u8; 20]) -> T
+{
+    let blocks: Vec<_> = passwords.iter().map(|p| sha1_block(p.as_ref(), escape)).collect();
+    let mut digests = Vec::with_capacity(blocks.len());
+    sha1::digest_blocks(&blocks.iter().filter_map(|&b| b).collect::<Vec<_>>(), &mut digests);
+
+    let mut digests = digests.iter();
+    dst.extend(passwords.iter().zip(&blocks).map(|(p, b)| match *b {
+        Some(_) => result(digests.next().unwrap()),
+        None    => sha1_internal(p.as_ref(), escape, &result),
+    }));
+}
+
+/// Escapes `password` if `escape` is `true` and pads it into a single SHA-1 block,
+/// or returns `None` if it does not fit in one.
+fn sha1_block(password: &[u8], escape: bool) -> Option<sha1::Block> {
+    if !escape {
+        return if password.len() <= sha1::MAX_BLOCK_MESSAGE_LEN {
+            Some(sha1::pad_block(password))
+        } else {
+            None
+        };
+    }
+
+    let mut buf = [0u8; sha1::MAX_BLOCK_MESSAGE_LEN];
+    let mut len = 0;
+    for c in password {
+        let bytes = mona_escape!(*c, |escaped| &escaped[..], || core::slice::from_ref(c));
+        if len + bytes.len() > buf.len() {
+            return None;
+        }
+        buf[len..len+bytes.len()].copy_from_slice(bytes);
+        len += bytes.len();
+    }
+    Some(sha1::pad_block(&buf[..len]))
+}
+
+/// Takes the 12-character tripcode out of a SHA-1 digest.
+fn mona12_hash(digest: &[u8; 20]) -> Mona12Hash {
+    Mona12Hash(pack_u64_be(digest), digest[8])
 }
 
 impl TripcodeGenerator for Mona12 {
     type Hash = Mona12Hash;
 
     fn hash<P: AsRef<[u8]>>(password: P) -> Mona12Hash {
-        sha1_internal(password.as_ref(), true, |d| unsafe {
-            Mona12Hash((*(d.as_ptr() as *const u64)).to_be(), d[8])
-        })
+        sha1_internal(password.as_ref(), true, mona12_hash)
+    }
+
+    fn hash_many<P: AsRef<[u8]>>(passwords: &[P], dst: &mut Vec<Mona12Hash>) {
+        sha1_many(passwords, true, mona12_hash, dst);
     }
 }
 
@@ -594,9 +777,26 @@ impl TripcodeGenerator for Mona12Nonescaping {
     type Hash = Mona12Hash;
 
     fn hash<P: AsRef<[u8]>>(password: P) -> Mona12Hash {
-        sha1_internal(password.as_ref(), false, |d| unsafe {
-            Mona12Hash((*(d.as_ptr() as *const u64)).to_be(), d[8])
-        })
+        sha1_internal(password.as_ref(), false, mona12_hash)
+    }
+
+    fn hash_many<P: AsRef<[u8]>>(passwords: &[P], dst: &mut Vec<Mona12Hash>) {
+        sha1_many(passwords, false, mona12_hash, dst);
+    }
+}
+
+/// Determines which kind of tripcode `Sc` generates from `password`, where `katakana` tells
+/// whether the password starts with `'$'` followed by a half-width katakana.
+fn sc_branch<F>(password: &[u8], katakana: F) -> Branch where F: Fn(&[u8]) -> bool {
+    if password.len() >= 12 {
+        match password[0] {
+            b'#' => Branch::NamaKey,
+            b'$' if katakana(password) => Branch::Katakana,
+            b'$' => Branch::Fifteen,
+            _    => Branch::Twelve,
+        }
+    } else {
+        Branch::Ten
     }
 }
 
@@ -605,30 +805,25 @@ fn sc_internal<P, F>(password: P, katakana: F) -> ScHash
 {
     use hash::ScHash::*;
 
-    let as_ref = password.as_ref();
-
-    if as_ref.len() >= 12 {
-        let sign = as_ref[0];
-        if sign == b'#' {
-            match MonaRaw::try_hash(&password) {
-                Some(h) => Ten(h),
-                None    => Error,
-            }
-        } else if sign == b'$' {
-            let h = Sc15::hash(&password);
-            if katakana(as_ref) {
-                Katakana(ScKatakanaHash(h))
-            } else {
-                Fifteen(h)
-            }
-        } else {
-            Twelve(Mona12Nonescaping::hash(&password))
-        }
-    } else {
-        Ten(FourchanNonescaping::hash(&password))
+    match sc_branch(password.as_ref(), katakana) {
+        Branch::NamaKey => match MonaRaw::try_hash(&password) {
+            Some(h) => Ten(h),
+            None    => Error,
+        },
+        Branch::Katakana => Katakana(ScKatakanaHash(Sc15::hash(&password))),
+        Branch::Fifteen => Fifteen(Sc15::hash(&password)),
+        Branch::Twelve => Twelve(Mona12Nonescaping::hash(&password)),
+        _ => Ten(FourchanNonescaping::hash(&password)),
     }
 }
 
+/// Returns `true` if the second byte (next to `'$'` sign) of a Shift-JIS-encoded `password`
+/// is a half-width katakana.
+fn sc_sjis_password_starts_with_katakana(password: &[u8]) -> bool {
+    let first = password[1];
+    0xA1 <= first && first <= 0xDF // [｡-ﾟ]
+}
+
 impl TripcodeGenerator for Sc {
     type Hash = ScHash;
 
@@ -641,32 +836,35 @@ impl TripcodeGenerator for ScSjis {
     type Hash = ScHash;
 
     fn hash<P: AsRef<[u8]>>(password: P) -> ScHash {
-        sc_internal(password, |slice| {
-                let first = slice[1];
-                0xA1 <= first && first <= 0xDF // [｡-ﾟ]
-            }
-        )
+        sc_internal(password, sc_sjis_password_starts_with_katakana)
     }
 }
 
+/// Takes the 15-character tripcode out of a SHA-1 digest.
+fn sc15_hash(digest: &[u8; 20]) -> Sc15Hash {
+    // 2ch.sc's tripcode uses 19-108th bits of SHA-1 digest.
+    // Sc15Hash(u64, u32) ->
+    // u64: 0b 11111111 11111111 11111111 11111111 11111111 11111111 11111111 11110000
+    //         ↑ 19th bit                                                        ↑ 78th bit
+    // u32: 0b 11111111 11111111 11111111 11111100
+    //         ↑ 79th bit                      ↑ 108th bit
+    Sc15Hash(
+        // 19-78th bits
+        pack_u64_be(&digest[2..]) << 2 & 0xFFFF_FFFF_FFFF_FFF0,
+        // 79-108th bits
+        (pack_u64_be(&digest[6..]) >> 2 & 0xFFFF_FFFC) as u32
+    )
+}
+
 impl TripcodeGenerator for Sc15 {
     type Hash = Sc15Hash;
 
     fn hash<P: AsRef<[u8]>>(password: P) -> Sc15Hash {
-        sha1_internal(&password.as_ref(), false, |d| unsafe {
-            // 2ch.sc's tripcode uses 19-108th bits of SHA-1 digest.
-            // Sc15Hash(u64, u32) ->
-            // u64: 0b 11111111 11111111 11111111 11111111 11111111 11111111 11111111 11110000
-            //         ↑ 19th bit                                                        ↑ 78th bit
-            // u32: 0b 11111111 11111111 11111111 11111100
-            //         ↑ 79th bit                      ↑ 108th bit
-            Sc15Hash(
-                // 19-78th bits
-                u64::from_be(*(d.as_ptr().offset(2) as *const u64)) << 2 & 0xFFFF_FFFF_FFFF_FFF0,
-                // 79-108th bits
-                (u64::from_be(*(d.as_ptr().offset(6) as *const u64)) >> 2 & 0xFFFF_FFFC) as u32
-            )
-        })
+        sha1_internal(password.as_ref(), false, sc15_hash)
+    }
+
+    fn hash_many<P: AsRef<[u8]>>(passwords: &[P], dst: &mut Vec<Sc15Hash>) {
+        sha1_many(passwords, false, sc15_hash, dst);
     }
 }
 
@@ -676,6 +874,10 @@ impl TripcodeGenerator for ScKatakana {
     fn hash<P: AsRef<[u8]>>(password: P) -> ScKatakanaHash {
         ScKatakanaHash(Sc15::hash(&password))
     }
+
+    fn hash_many<P: AsRef<[u8]>>(passwords: &[P], dst: &mut Vec<ScKatakanaHash>) {
+        sha1_many(passwords, false, |d| ScKatakanaHash(sc15_hash(d)), dst);
+    }
 }
 
 impl Des {
@@ -692,20 +894,35 @@ impl Des {
         Self::hash(password, salt1, salt2).encode()
     }
 
+    #[inline]
+    /// Generates a tripcode into a fixed-capacity `Tripcode` without allocating.
+    pub fn generate_inline<P: AsRef<[u8]>>(password: P, salt1: u8, salt2: u8) -> Tripcode {
+        Self::hash(password, salt1, salt2).encode_inline()
+    }
+
     #[inline]
     /// Generates a tripcode and appends it to a `String`.
     pub fn append<P: AsRef<[u8]>>(password: P, salt1: u8, salt2: u8, dst: &mut String) {
         Self::hash(password, salt1, salt2).append(dst);
     }
 
+    #[cfg(feature = "std")]
     #[inline]
     /// Generates a tripcode into a `Write`.
     pub fn write<P, W>(password: P, salt1: u8, salt2: u8, dst: &mut W) -> io::Result<()> where P: AsRef<[u8]>, W: Write {
         Self::hash(password, salt1, salt2).write(dst)
     }
+
+    #[inline]
+    /// Generates a tripcode into a `fmt::Write`.
+    pub fn write_fmt_to<P, W>(password: P, salt1: u8, salt2: u8, dst: &mut W) -> fmt::Result
+        where P: AsRef<[u8]>, W: fmt::Write
+    {
+        Self::hash(password, salt1, salt2).write_fmt_to(dst)
+    }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests {
     extern crate encoding;
 
@@ -726,9 +943,16 @@ mod tests {
             <$hasher>::append(&$password, &mut tripcode);
             assert_tripcode_eq!(cmp $expected, &tripcode, $password);
 
-            let mut tripcode = [0u8; 15];
+            let mut tripcode = [0u8; 45];
             <$hasher>::write(&$password, &mut (&mut tripcode as &mut [u8])).unwrap();
             assert_tripcode_eq!(cmp $expected, String::from_utf8_lossy(&tripcode[..$expected.len()]), $password);
+
+            let mut tripcode = String::new();
+            <$hasher>::write_fmt_to(&$password, &mut tripcode).unwrap();
+            assert_tripcode_eq!(cmp $expected, &tripcode, $password);
+
+            let tripcode = <$hasher>::generate_inline(&$password);
+            assert_tripcode_eq!(cmp $expected, &*tripcode, $password);
         }};
 
         (cmp $expected:expr, $tripcode:expr, $password:expr) => {
@@ -783,6 +1007,59 @@ mod tests {
         assert_tripcode_eq!("h3Si!7m4Qie8e.u", "$0123456789a", Sc);
     }
 
+    // Runs every generator through every output method, so that the whole path from hashing to
+    // encoding is exercised, e.g. under Miri.
+    #[test]
+    fn every_generator() {
+        let (p10, p12) = ("pa\"s<s&", "\"quoted\" & <tagged>");
+
+        assert_tripcode_eq!("VpsoOO5Ofg",      p10, Fourchan);
+        assert_tripcode_eq!("SgowOhr5wk",      p12, Fourchan);
+        assert_tripcode_eq!("P1v07Ukn4s",      p10, FourchanNonescaping);
+        assert_tripcode_eq!("9MhQwrVb5X7I",    p10, Mona);
+        assert_tripcode_eq!("Pw1qUzzX2bCq",    p12, Mona);
+        assert_tripcode_eq!("P1v07Ukn4s",      p10, MonaNonescaping);
+        assert_tripcode_eq!("8dQJ66bF0m1/",    p12, MonaNonescaping);
+        assert_tripcode_eq!("VpsoOO5Ofg",      p10, Mona10);
+        assert_tripcode_eq!("6FE0ehF3Ck",      p12, Mona10Nonescaping);
+        assert_tripcode_eq!("9MhQwrVb5X7I",    p10, Mona12);
+        assert_tripcode_eq!("DwAtqg2aHptj",    p10, Mona12Nonescaping);
+        assert_tripcode_eq!("P1v07Ukn4s",      p10, Sc);
+        assert_tripcode_eq!("8dQJ66bF0m1/",    p12, Sc);
+        assert_tripcode_eq!("Bo8.4y2YCDDKgeU", "$password12345", Sc);
+        assert_tripcode_eq!("ｹｴﾅｽﾞﾜ｣ﾂｫﾒｴﾝｬﾋﾄ", "$ｱpassword123", Sc);
+        assert_tripcode_eq!("8dQJ66bF0m1/",    p12, ScSjis);
+        assert_tripcode_eq!("OJaSzx2XAnJyBgZ", "$ｱpassword123", ScSjis);
+        assert_tripcode_eq!("tqg2aHptjF7ERBW", p10, Sc15);
+        assert_tripcode_eq!("J66bF0m1!CY.z9Z", p12, Sc15);
+        assert_tripcode_eq!("ﾘﾕﾋ｣ﾅｲﾔﾘﾎｰｨｯｼｬﾁ", p10, ScKatakana);
+
+        assert_eq!(Some("IP9Lda5FPc"), MonaRaw::try_generate("#0123456789abcdef./").as_deref());
+        assert_eq!(".uvfkf86cA", Des::generate(p10, b'x', b'.'));
+    }
+
+    #[test]
+    fn hash_many() {
+        // Passwords around the length that fits in a single SHA-1 block, with and without
+        // characters to be escaped.
+        let passwords: Vec<String> = (0..80).map(|n| match n % 3 {
+            0 => "a".repeat(n),
+            1 => "<\"".repeat(n / 2),
+            _ => format!("${}", "0123456789".repeat(n / 10 + 2)),
+        }).collect();
+
+        macro_rules! assert_hash_many {
+            ($($generator:ty),*) => {$({
+                let mut hashes = Vec::new();
+                <$generator>::hash_many(&passwords, &mut hashes);
+                let expected: Vec<_> = passwords.iter().map(<$generator>::hash).collect();
+                assert!(expected == hashes, "{}", stringify!($generator));
+            })*};
+        }
+
+        assert_hash_many!(Mona12, Mona12Nonescaping, Sc15, ScKatakana);
+    }
+
     #[test]
     fn html_escaping() {
         assert_tripcode_eq!("TIWS518hyaVm", "abc&quot;def");
@@ -814,6 +1091,28 @@ mod tests {
         assert_eq!("ozOtJW9BFA", &tripcode);
         let tripcode = Des::generate("", b'H', b'.');
         assert_eq!("jPpg5.obl6", &tripcode);
+
+        let mut tripcode = String::new();
+        Des::write_fmt_to("password", b'a', b's', &mut tripcode).unwrap();
+        assert_eq!("ozOtJW9BFA", &tripcode);
+        assert_eq!("ozOtJW9BFA", Des::generate_inline("password", b'a', b's'));
+    }
+
+    #[test]
+    fn write_fmt_to() {
+        let mut tripcode = String::new();
+
+        MonaRaw::try_write_fmt_to("#0123456789abcdef./", &mut tripcode).unwrap().unwrap();
+        assert_eq!("IP9Lda5FPc", &tripcode);
+        assert!(MonaRaw::try_write_fmt_to("#abcdefghijklmnop", &mut tripcode).is_none());
+        assert_eq!("IP9Lda5FPc", MonaRaw::try_generate_inline("#0123456789abcdef./").unwrap());
+        assert!(MonaRaw::try_generate_inline("#abcdefghijklmnop").is_none());
+
+        tripcode.clear();
+        let k = SJIS.encode("$｡1008343131", EncoderTrap::Strict).unwrap();
+        ScKatakana::write_fmt_to(&k, &mut tripcode).unwrap();
+        ScSjis::write_fmt_to(&k, &mut tripcode).unwrap();
+        assert_eq!("ﾃｽﾄ!ｹﾏﾜｬｴ･ｧﾎﾖｲﾎﾃｽﾄ!ｹﾏﾜｬｴ･ｧﾎﾖｲﾎ", &tripcode);
     }
 
     #[test]
@@ -0,0 +1,533 @@
+// Copyright 2016 Huton. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+Rainbow tables, which trade disk space for the time it takes to find the password of
+a 10-character tripcode.
+
+A table covers a keyspace of fixed-length passwords over a charset, enumerated as in
+[`search`](../search/index.html), for a generator whose hash value is `FourchanHash`, i.e.
+`Fourchan`, `Mona10` or their nonescaping variants. It consists of chains of passwords, each of
+which is hashed and mapped back into the keyspace by a reduction function that differs at each
+position of the chain, and only the first and last password of each chain are stored. Looking up
+a tripcode then takes about `chain_len² / 2` hashes instead of a search through the keyspace.
+
+Chains that merge with others are dropped when the table is built, so a table does not contain
+every password of its keyspace; building more chains covers more of it.
+
+This module is only available with the `std` feature.
+
+# Examples
+
+```
+use tripcode::*;
+use tripcode::rainbow::{Builder, Table};
+
+let table = Builder::new()
+    .charset(b"abcdef")
+    .password_len(4)
+    .chain_len(20)
+    .threads(2)
+    .build::<Fourchan>();
+
+// Tables can be saved to any `Write`, such as a file, and loaded from any `Read`.
+let mut file = Vec::new();
+table.write_to(&mut file).unwrap();
+let table = Table::<Fourchan>::read_from(&file[..]).unwrap();
+
+let tripcode = Fourchan::generate("face");
+if let Some(password) = table.lookup_tripcode(&tripcode) {
+    assert_eq!(tripcode, Fourchan::generate(&password));
+}
+```
+
+# File format
+
+All integers are little-endian.
+
+| Size               | Content                                                            |
+| ------------------ | ------------------------------------------------------------------ |
+| 8                  | `TRIPRAIN`                                                         |
+| 1                  | Version of the format, which is 1                                  |
+| 8                  | Hash value of `"&<>` with the generator, which tells them apart   |
+| 4                  | Length of passwords                                                |
+| 2                  | Length of the charset                                              |
+| charset length     | The charset                                                        |
+| 4                  | Length of chains                                                   |
+| 8                  | Number of chains                                                   |
+| 1                  | Width *w* of indices of passwords, the fewest bytes that hold them |
+| 2*w* × chains      | Index of the first and the last password of each chain, sorted by the last ones |
+*/
+
+use std::cmp;
+use std::io::{self, Read, Write};
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::vec::Vec;
+use hash::{FourchanHash, TripcodeHash};
+use search::Search;
+use util::nth_password;
+use TripcodeGenerator;
+
+const MAGIC: &[u8; 8] = b"TRIPRAIN";
+const VERSION: u8 = 1;
+
+/// Password whose hash value differs among the generators that escape it differently.
+const FINGERPRINT_PASSWORD: &[u8] = b"\"&<>";
+
+/// Largest keyspace that the reduction functions cover, which is the number of hash values.
+const MAX_KEYSPACE_LEN: u64 = 1 << 58;
+
+/// Number of chains that a worker thread claims and walks through at once.
+const BATCH_LEN: u64 = 1024;
+
+/// Configuration of a rainbow table.
+#[derive(Clone, Debug)]
+pub struct Builder {
+    charset: Vec<u8>,
+    password_len: usize,
+    chain_len: u32,
+    chains: Option<u64>,
+    threads: usize,
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Builder::new()
+    }
+}
+
+impl Builder {
+    /// Creates a configuration of a table.
+    ///
+    /// The table defaults to 4-byte passwords over `Search::DEFAULT_CHARSET`, chains of 1000
+    /// passwords, and as many threads as the available parallelism.
+    pub fn new() -> Self {
+        Builder {
+            charset: Search::DEFAULT_CHARSET.to_vec(),
+            password_len: 4,
+            chain_len: 1000,
+            chains: None,
+            threads: thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+        }
+    }
+
+    /// Sets the characters that passwords consist of.
+    ///
+    /// Duplicated characters are ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `charset` is empty.
+    pub fn charset(mut self, charset: &[u8]) -> Self {
+        assert!(!charset.is_empty(), "empty charset");
+        self.charset.clear();
+        for &c in charset {
+            if !self.charset.contains(&c) {
+                self.charset.push(c);
+            }
+        }
+        self
+    }
+
+    /// Sets the length of passwords, in bytes.
+    pub fn password_len(mut self, len: usize) -> Self {
+        self.password_len = len;
+        self
+    }
+
+    /// Sets the number of passwords in each chain. `0` is treated as `1`.
+    pub fn chain_len(mut self, len: u32) -> Self {
+        self.chain_len = cmp::max(len, 1);
+        self
+    }
+
+    /// Sets the number of chains to build, which is capped at the length of the keyspace.
+    ///
+    /// Defaults to twice the length of the keyspace divided by the length of chains. Each chain
+    /// takes 16 bytes of memory, so the default grows quickly with the length of passwords, e.g.
+    /// to terabytes for 8-character passwords over `Search::DEFAULT_CHARSET`.
+    pub fn chains(mut self, chains: u64) -> Self {
+        self.chains = Some(chains);
+        self
+    }
+
+    /// Sets the number of worker threads. `0` is treated as `1`.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = cmp::max(threads, 1);
+        self
+    }
+
+    /// Returns the number of passwords in the keyspace, or `None` if it exceeds `u64::MAX`.
+    pub fn keyspace_len(&self) -> Option<u64> {
+        keyspace_len(&self.charset, self.password_len)
+    }
+
+    /// Returns the number of chains that `build` computes, or `None` if the keyspace has more
+    /// than `u64::MAX` passwords.
+    ///
+    /// The table ends up with fewer chains if some of them merge.
+    pub fn table_len(&self) -> Option<u64> {
+        let keyspace_len = self.keyspace_len()?;
+        let len = self.chains.unwrap_or(keyspace_len.saturating_mul(2) / self.chain_len as u64);
+        Some(cmp::min(cmp::max(len, 1), keyspace_len))
+    }
+
+    /// Builds the table for the generator `G`.
+    ///
+    /// The table is built in memory, which takes 16 bytes for each of `table_len()` chains.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the keyspace has more than 2<sup>58</sup> passwords.
+    pub fn build<G>(self) -> Table<G> where G: TripcodeGenerator<Hash=FourchanHash> {
+        let keyspace_len = match self.keyspace_len() {
+            Some(len) if len <= MAX_KEYSPACE_LEN => len,
+            _ => panic!("keyspace too large"),
+        };
+        let len = self.table_len().unwrap();
+        let threads = self.threads;
+        let params = Params {
+            charset: self.charset,
+            password_len: self.password_len,
+            keyspace_len,
+            chain_len: self.chain_len,
+        };
+
+        // The chains start at the first passwords of the keyspace.
+        let next = AtomicU64::new(0);
+        let mut chains: Vec<Chain> = thread::scope(|s| {
+            let workers: Vec<_> = (0..threads).map(|_| s.spawn(|| {
+                let mut walker = Walker::new(&params);
+                let mut ret = Vec::new();
+                loop {
+                    let start = next.fetch_add(BATCH_LEN, Ordering::Relaxed);
+                    if start >= len {
+                        return ret;
+                    }
+                    let starts = start..cmp::min(start + BATCH_LEN, len);
+                    let mut indices: Vec<u64> = starts.clone().collect();
+                    for j in 0..params.chain_len {
+                        walker.step::<G>(&mut indices, j);
+                    }
+                    ret.extend(starts.zip(indices).map(|(start, end)| Chain { end, start }));
+                }
+            })).collect();
+            workers.into_iter().flat_map(|w| w.join().unwrap()).collect()
+        });
+
+        // Chains that end at the same password have merged, and only one of them is kept.
+        chains.sort_unstable();
+        chains.dedup_by_key(|c| c.end);
+
+        Table { params, chains, _generator: PhantomData }
+    }
+}
+
+/// A rainbow table for the generator `G`.
+pub struct Table<G> {
+    params: Params,
+    /// The chains, sorted by their unique ends.
+    chains: Vec<Chain>,
+    _generator: PhantomData<fn() -> G>,
+}
+
+impl<G> Table<G> where G: TripcodeGenerator<Hash=FourchanHash> {
+    /// Returns the characters that passwords consist of.
+    pub fn charset(&self) -> &[u8] {
+        &self.params.charset
+    }
+
+    /// Returns the length of passwords, in bytes.
+    pub fn password_len(&self) -> usize {
+        self.params.password_len
+    }
+
+    /// Returns the number of passwords in the keyspace.
+    pub fn keyspace_len(&self) -> u64 {
+        self.params.keyspace_len
+    }
+
+    /// Returns the number of passwords in each chain.
+    pub fn chain_len(&self) -> u32 {
+        self.params.chain_len
+    }
+
+    /// Returns the number of chains.
+    pub fn len(&self) -> usize {
+        self.chains.len()
+    }
+
+    /// Returns `true` if the table has no chains.
+    pub fn is_empty(&self) -> bool {
+        self.chains.is_empty()
+    }
+
+    /// Looks for a password in the table whose hash value is `hash`.
+    pub fn lookup(&self, hash: &FourchanHash) -> Option<Vec<u8>> {
+        // The `i`th candidate is where a chain ends if `hash` is of its `i`th password.
+        let mut candidates: Vec<u64> = (0..self.params.chain_len).map(|i| self.params.reduce(hash, i)).collect();
+        let mut walker = Walker::new(&self.params);
+        for j in 1..self.params.chain_len {
+            walker.step::<G>(&mut candidates[..j as usize], j);
+        }
+
+        candidates.iter().enumerate().filter_map(|(i, end)| {
+            let k = self.chains.binary_search_by_key(end, |c| c.end).ok()?;
+            self.find_in_chain(self.chains[k].start, i, hash)
+        }).next()
+    }
+
+    /// Looks for a password in the table that yields `tripcode`.
+    ///
+    /// Returns `None` if `tripcode` is not a valid 10-character tripcode, too.
+    pub fn lookup_tripcode(&self, tripcode: &str) -> Option<Vec<u8>> {
+        FourchanHash::decode(tripcode).and_then(|hash| self.lookup(&hash))
+    }
+
+    // Walks the chain from `start` through its `i`th password, looking for one whose hash value
+    // is `hash`. The chain may not have it even if it ends where expected, because chains
+    // merge with each other.
+    fn find_in_chain(&self, start: u64, i: usize, hash: &FourchanHash) -> Option<Vec<u8>> {
+        let mut password = vec![0; self.params.password_len];
+        let mut index = start;
+        for j in 0..=i as u32 {
+            nth_password(&self.params.charset, index, &mut password);
+            let h = G::hash(&password);
+            if h == *hash {
+                return Some(password);
+            }
+            index = self.params.reduce(&h, j);
+        }
+        None
+    }
+
+    /// Writes the table to `dst` in the format described in the [module documentation](index.html).
+    pub fn write_to<W: Write>(&self, mut dst: W) -> io::Result<()> {
+        let width = self.params.index_width();
+        dst.write_all(MAGIC)?;
+        dst.write_all(&[VERSION])?;
+        dst.write_all(&fingerprint::<G>().to_le_bytes())?;
+        dst.write_all(&(self.params.password_len as u32).to_le_bytes())?;
+        dst.write_all(&(self.params.charset.len() as u16).to_le_bytes())?;
+        dst.write_all(&self.params.charset)?;
+        dst.write_all(&self.params.chain_len.to_le_bytes())?;
+        dst.write_all(&(self.chains.len() as u64).to_le_bytes())?;
+        dst.write_all(&[width as u8])?;
+        for chain in &self.chains {
+            dst.write_all(&chain.start.to_le_bytes()[..width])?;
+            dst.write_all(&chain.end.to_le_bytes()[..width])?;
+        }
+        Ok(())
+    }
+
+    /// Reads a table written by `write_to()` from `src`.
+    ///
+    /// Fails with `io::ErrorKind::InvalidData` if the data is malformed or the table was built for
+    /// a generator that escapes passwords differently from `G`.
+    pub fn read_from<R: Read>(mut src: R) -> io::Result<Self> {
+        fn invalid(message: &str) -> io::Error {
+            io::Error::new(io::ErrorKind::InvalidData, message)
+        }
+
+        macro_rules! read {
+            ($n:expr) => {{
+                let mut buf = [0u8; $n];
+                src.read_exact(&mut buf)?;
+                buf
+            }};
+        }
+
+        if &read!(8) != MAGIC {
+            return Err(invalid("not a rainbow table"));
+        }
+        if read!(1)[0] != VERSION {
+            return Err(invalid("unsupported version of rainbow tables"));
+        }
+        if u64::from_le_bytes(read!(8)) != fingerprint::<G>() {
+            return Err(invalid("rainbow table for another generator"));
+        }
+        let password_len = u32::from_le_bytes(read!(4)) as usize;
+        let mut charset = vec![0; u16::from_le_bytes(read!(2)) as usize];
+        src.read_exact(&mut charset)?;
+        let chain_len = u32::from_le_bytes(read!(4));
+        let len = u64::from_le_bytes(read!(8));
+        let width = read!(1)[0] as usize;
+
+        let params = match keyspace_len(&charset, password_len) {
+            Some(keyspace_len) if keyspace_len <= MAX_KEYSPACE_LEN && chain_len > 0 && !charset.is_empty() &&
+                (1..charset.len()).all(|i| !charset[..i].contains(&charset[i])) =>
+                Params { charset, password_len, keyspace_len, chain_len },
+            _ => return Err(invalid("invalid parameters of rainbow table")),
+        };
+        if width != params.index_width() || len > params.keyspace_len {
+            return Err(invalid("invalid parameters of rainbow table"));
+        }
+
+        let mut chains = Vec::new();
+        let mut buf = [0u8; 16];
+        for _ in 0..len {
+            src.read_exact(&mut buf[..2 * width])?;
+            let mut start = [0u8; 8];
+            let mut end = [0u8; 8];
+            start[..width].copy_from_slice(&buf[..width]);
+            end[..width].copy_from_slice(&buf[width..2 * width]);
+            let chain = Chain { end: u64::from_le_bytes(end), start: u64::from_le_bytes(start) };
+
+            if chain.start >= params.keyspace_len || chain.end >= params.keyspace_len ||
+                chains.last().is_some_and(|c: &Chain| c.end >= chain.end)
+            {
+                return Err(invalid("invalid chain in rainbow table"));
+            }
+            chains.push(chain);
+        }
+
+        Ok(Table { params, chains, _generator: PhantomData })
+    }
+}
+
+/// Parameters that a table is built with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Params {
+    charset: Vec<u8>,
+    password_len: usize,
+    keyspace_len: u64,
+    chain_len: u32,
+}
+
+impl Params {
+    /// Maps `hash` of the `i`th password of a chain to the index of the next password.
+    fn reduce(&self, hash: &FourchanHash, i: u32) -> u64 {
+        ((hash.0 >> 6) ^ (i as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)) % self.keyspace_len
+    }
+
+    /// Returns the number of bytes that hold the index of any password.
+    fn index_width(&self) -> usize {
+        let bits = 64 - (self.keyspace_len - 1).leading_zeros() as usize;
+        cmp::max(bits.div_ceil(8), 1)
+    }
+}
+
+/// Buffers for walking through chains with `TripcodeGenerator::hash_many()`.
+struct Walker<'a> {
+    params: &'a Params,
+    passwords: Vec<Vec<u8>>,
+    hashes: Vec<FourchanHash>,
+}
+
+impl<'a> Walker<'a> {
+    fn new(params: &'a Params) -> Self {
+        Walker { params, passwords: Vec::new(), hashes: Vec::new() }
+    }
+
+    /// Advances each of `indices`, the indices of the `j`th passwords of chains, to the next
+    /// password.
+    fn step<G>(&mut self, indices: &mut [u64], j: u32) where G: TripcodeGenerator<Hash=FourchanHash> {
+        if self.passwords.len() < indices.len() {
+            self.passwords.resize(indices.len(), vec![0; self.params.password_len]);
+        }
+        for (password, &i) in self.passwords.iter_mut().zip(&*indices) {
+            nth_password(&self.params.charset, i, password);
+        }
+
+        G::hash_many(&self.passwords[..indices.len()], &mut self.hashes);
+        for (i, hash) in indices.iter_mut().zip(self.hashes.drain(..)) {
+            *i = self.params.reduce(&hash, j);
+        }
+    }
+}
+
+/// Indices of the first and the last password of a chain, ordered by the last one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Chain {
+    end: u64,
+    start: u64,
+}
+
+fn keyspace_len(charset: &[u8], password_len: usize) -> Option<u64> {
+    if password_len > u32::MAX as usize {
+        return None;
+    }
+    (charset.len() as u64).checked_pow(password_len as u32)
+}
+
+fn fingerprint<G>() -> u64 where G: TripcodeGenerator<Hash=FourchanHash> {
+    G::hash(FINGERPRINT_PASSWORD).0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use *;
+
+    fn table<G>(threads: usize) -> Table<G> where G: TripcodeGenerator<Hash=FourchanHash> {
+        Builder::new().charset(b"abcdefgh").password_len(4).chain_len(16).threads(threads).build::<G>()
+    }
+
+    #[test]
+    fn lookup() {
+        let table = table::<Fourchan>(2);
+        assert_eq!(4096, table.keyspace_len());
+        assert!(!table.is_empty());
+
+        // The first password of each chain must be found.
+        let mut password = [0u8; 4];
+        for chain in table.chains.iter().step_by(8) {
+            nth_password(b"abcdefgh", chain.start, &mut password);
+            let found = table.lookup(&Fourchan::hash(password)).unwrap();
+            assert_eq!(Fourchan::hash(password), Fourchan::hash(found));
+        }
+
+        let mut found = 0;
+        for n in (0..4096).step_by(64) {
+            nth_password(b"abcdefgh", n, &mut password);
+            let tripcode = Fourchan::generate(password);
+            if let Some(p) = table.lookup_tripcode(&tripcode) {
+                assert_eq!(tripcode, Fourchan::generate(p));
+                found += 1;
+            }
+        }
+        assert!(found > 32, "found {} out of 64", found);
+
+        assert!(table.lookup_tripcode("invalid").is_none());
+    }
+
+    #[test]
+    fn deterministic() {
+        assert_eq!(table::<Mona10>(1).chains, table::<Mona10>(3).chains);
+    }
+
+    #[test]
+    fn file_format() {
+        let table = table::<Fourchan>(1);
+        let mut file = Vec::new();
+        table.write_to(&mut file).unwrap();
+        // The indices of the passwords fit in 2 bytes.
+        assert_eq!(8 + 1 + 8 + 4 + 2 + 8 + 4 + 8 + 1 + 4 * table.len(), file.len());
+
+        let read = Table::<Fourchan>::read_from(&file[..]).unwrap();
+        assert_eq!(table.params, read.params);
+        assert_eq!(table.chains, read.chains);
+
+        // `Mona10` does not escape `&`.
+        let err = Table::<Mona10>::read_from(&file[..]).err().unwrap();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        assert!(Table::<FourchanNonescaping>::read_from(&file[..]).is_err());
+
+        let err = Table::<Fourchan>::read_from(&file[..file.len() - 1]).err().unwrap();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+
+        let mut unsorted = file.clone();
+        let n = unsorted.len();
+        unsorted[n - 2..].copy_from_slice(&[0, 0]);
+        let err = Table::<Fourchan>::read_from(&unsorted[..]).err().unwrap();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    }
+}
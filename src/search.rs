@@ -0,0 +1,475 @@
+// Copyright 2016 Huton. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+Multi-threaded search for passwords that yield tripcodes matching given patterns
+(so-called vanity tripcodes).
+
+The passwords are enumerated from a keyspace of fixed-length strings over a charset, in
+a deterministic order, and hashed in batches through `TripcodeGenerator::hash_many()`.
+
+This module is only available with the `std` feature.
+
+# Examples
+
+```
+use tripcode::*;
+use tripcode::search::{Pattern, Search};
+
+let search = Search::new(vec![Pattern::Prefix("ab".to_owned())])
+    .charset(b"0123456789")
+    .password_len(4)
+    .threads(2)
+    .start::<Fourchan>();
+
+// Hits are sent as soon as they are found, in no particular order.
+for hit in search.iter().take(2) {
+    assert!(hit.tripcode.starts_with("ab"));
+    assert_eq!(Fourchan::generate(&hit.password), hit.tripcode);
+}
+```
+*/
+
+use std::{cmp, thread};
+use std::marker::PhantomData;
+use std::string::{String, ToString};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender};
+use std::time::Duration;
+use std::vec::Vec;
+#[cfg(feature = "regex")]
+use std::{error, fmt};
+#[cfg(feature = "regex")]
+use std::boxed::Box;
+#[cfg(feature = "regex")]
+use regex_automata::meta;
+#[cfg(feature = "regex")]
+use estimate::{BuildError, Estimate};
+#[cfg(feature = "regex")]
+use hash::TripcodeAlphabets;
+use hash::TripcodeHash;
+use util::nth_password;
+use {Tripcode, TripcodeGenerator};
+
+/// Number of passwords that a worker thread claims from the keyspace at once.
+const CHUNK_LEN: u64 = 1024;
+
+/// Number of hits that the worker threads may queue before they block waiting for the receiver.
+const HIT_QUEUE_LEN: usize = 1024;
+
+/// Pattern that tripcodes are matched against.
+///
+/// The enum is non-exhaustive because the `Regex` variant depends on a feature, which another
+/// crate in the dependency graph may enable.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Pattern {
+    /// Matches tripcodes that start with the string.
+    Prefix(String),
+    /// Matches tripcodes that contain the string.
+    Contains(String),
+    /// Matches tripcodes that contain a match of the regular expression.
+    ///
+    /// This variant is only available with the `regex` feature.
+    #[cfg(feature = "regex")]
+    Regex(Regex),
+}
+
+impl Pattern {
+    #[inline]
+    /// Checks whether `tripcode` matches the pattern.
+    pub fn matches(&self, tripcode: &str) -> bool {
+        match *self {
+            Pattern::Prefix(ref s)   => tripcode.starts_with(s.as_str()),
+            Pattern::Contains(ref s) => tripcode.contains(s.as_str()),
+            #[cfg(feature = "regex")]
+            Pattern::Regex(ref r)    => r.regex.is_match(tripcode),
+        }
+    }
+}
+
+/// Error that occurs when a regular expression is invalid.
+///
+/// This type is only available with the `regex` feature.
+#[cfg(feature = "regex")]
+#[derive(Clone, Debug)]
+pub struct RegexError(Box<meta::BuildError>);
+
+#[cfg(feature = "regex")]
+impl fmt::Display for RegexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // The syntax error tells where the regular expression is wrong.
+        match self.0.syntax_error() {
+            Some(e) => e.fmt(f),
+            None => self.0.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "regex")]
+impl error::Error for RegexError {}
+
+/// Compiled regular expression for `Pattern::Regex`, in the syntax of the `regex` crate.
+///
+/// Two `Regex`es are equal if they were compiled from the same string.
+///
+/// This type is only available with the `regex` feature.
+#[cfg(feature = "regex")]
+#[derive(Clone, Debug)]
+pub struct Regex {
+    source: String,
+    regex: meta::Regex,
+}
+
+#[cfg(feature = "regex")]
+impl Regex {
+    /// Compiles `pattern`.
+    pub fn new(pattern: &str) -> Result<Regex, RegexError> {
+        Ok(Regex {
+            source: pattern.to_string(),
+            regex: meta::Regex::new(pattern).map_err(|e| RegexError(Box::new(e)))?,
+        })
+    }
+
+    /// Returns the string that the regular expression was compiled from.
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+}
+
+#[cfg(feature = "regex")]
+impl PartialEq for Regex {
+    fn eq(&self, other: &Regex) -> bool {
+        self.source == other.source
+    }
+}
+
+#[cfg(feature = "regex")]
+impl Eq for Regex {}
+
+/// Password that yields a tripcode matching one of the patterns.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Hit {
+    /// The password.
+    pub password: Vec<u8>,
+    /// The tripcode generated from `password`.
+    pub tripcode: String,
+}
+
+/// Configuration of a search.
+#[derive(Clone, Debug)]
+pub struct Search {
+    patterns: Vec<Pattern>,
+    charset: Vec<u8>,
+    password_len: usize,
+    threads: usize,
+}
+
+impl Search {
+    /// The default charset, consisting of the 64 characters used in tripcodes.
+    pub const DEFAULT_CHARSET: &'static [u8] =
+        b"./0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+    /// Creates a search for tripcodes matching any of `patterns`.
+    ///
+    /// The search defaults to 8-byte passwords over `DEFAULT_CHARSET` and as many threads as
+    /// the available parallelism.
+    pub fn new(patterns: Vec<Pattern>) -> Self {
+        Search {
+            patterns,
+            charset: Search::DEFAULT_CHARSET.to_vec(),
+            password_len: 8,
+            threads: thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+        }
+    }
+
+    /// Sets the characters that passwords consist of.
+    ///
+    /// Duplicated characters are ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `charset` is empty.
+    pub fn charset(mut self, charset: &[u8]) -> Self {
+        assert!(!charset.is_empty(), "empty charset");
+        self.charset.clear();
+        for &c in charset {
+            if !self.charset.contains(&c) {
+                self.charset.push(c);
+            }
+        }
+        self
+    }
+
+    /// Sets the length of passwords, in bytes.
+    pub fn password_len(mut self, len: usize) -> Self {
+        self.password_len = len;
+        self
+    }
+
+    /// Sets the number of worker threads. `0` is treated as `1`.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = cmp::max(threads, 1);
+        self
+    }
+
+    /// Returns the number of passwords in the keyspace, or `None` if it exceeds `u64::MAX`.
+    pub fn keyspace_len(&self) -> Option<u64> {
+        if self.password_len > u32::MAX as usize {
+            return None;
+        }
+        (self.charset.len() as u64).checked_pow(self.password_len as u32)
+    }
+
+    /// Estimates the probability that a password of the search yields a matching tripcode with
+    /// the generator `G`. See the [`estimate`](../estimate/index.html) module.
+    ///
+    /// This method is only available with the `regex` feature.
+    #[cfg(feature = "regex")]
+    pub fn estimate<G>(&self) -> Result<Estimate, BuildError>
+        where G: TripcodeGenerator, G::Hash: TripcodeAlphabets
+    {
+        Estimate::new::<G>(&self.patterns, self.password_len)
+    }
+
+    /// Spawns the worker threads and starts searching with the generator `G`.
+    pub fn start<G: TripcodeGenerator + 'static>(self) -> Searching {
+        let (sender, receiver) = mpsc::sync_channel(HIT_QUEUE_LEN);
+        let shared = Arc::new(Shared {
+            keyspace_len: self.keyspace_len().unwrap_or(u64::MAX),
+            next: AtomicU64::new(0),
+            tested: AtomicU64::new(0),
+            stop: AtomicBool::new(false),
+            search: self,
+        });
+
+        let workers = (0..shared.search.threads).map(|_| {
+            let worker = Worker::<G> {
+                shared: shared.clone(),
+                sender: sender.clone(),
+                _generator: PhantomData,
+            };
+            thread::spawn(move || worker.run())
+        }).collect();
+
+        Searching { shared, receiver, workers }
+    }
+}
+
+/// A running search.
+///
+/// The worker threads stop when the keyspace is exhausted, when `stop()` is called, or when
+/// this struct is dropped.
+pub struct Searching {
+    shared: Arc<Shared>,
+    receiver: Receiver<Hit>,
+    workers: Vec<thread::JoinHandle<()>>,
+}
+
+impl Searching {
+    /// Blocks until the next hit is found.
+    ///
+    /// Returns `None` when the keyspace has been exhausted and every hit has been received.
+    pub fn next_hit(&self) -> Option<Hit> {
+        self.receiver.recv().ok()
+    }
+
+    /// Blocks until the next hit is found or `timeout` elapses.
+    pub fn next_hit_timeout(&self, timeout: Duration) -> Result<Hit, RecvTimeoutError> {
+        self.receiver.recv_timeout(timeout)
+    }
+
+    /// Returns an iterator that blocks for each hit until the search is over.
+    pub fn iter(&self) -> mpsc::Iter<'_, Hit> {
+        self.receiver.iter()
+    }
+
+    /// Returns the number of passwords tested so far.
+    pub fn tested(&self) -> u64 {
+        self.shared.tested.load(Ordering::Relaxed)
+    }
+
+    /// Stops the worker threads and waits for them to exit.
+    pub fn stop(mut self) {
+        self.stop_workers();
+    }
+
+    fn stop_workers(&mut self) {
+        self.shared.stop.store(true, Ordering::Relaxed);
+        // Workers may be blocked on a full queue. Discard the pending hits until every worker
+        // has exited and dropped its sender.
+        while self.receiver.recv().is_ok() {}
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl Drop for Searching {
+    fn drop(&mut self) {
+        self.stop_workers();
+    }
+}
+
+/// State shared among the worker threads.
+struct Shared {
+    search: Search,
+    keyspace_len: u64,
+    /// Index of the first password that has not been claimed by any worker.
+    next: AtomicU64,
+    tested: AtomicU64,
+    stop: AtomicBool,
+}
+
+struct Worker<G> {
+    shared: Arc<Shared>,
+    sender: SyncSender<Hit>,
+    _generator: PhantomData<fn() -> G>,
+}
+
+impl<G: TripcodeGenerator> Worker<G> {
+    fn run(self) {
+        let search = &self.shared.search;
+        let mut passwords = vec![vec![0u8; search.password_len]; CHUNK_LEN as usize];
+        let mut hashes = Vec::with_capacity(CHUNK_LEN as usize);
+
+        while !self.shared.stop.load(Ordering::Relaxed) {
+            let start = self.shared.next.fetch_add(CHUNK_LEN, Ordering::Relaxed);
+            if start >= self.shared.keyspace_len {
+                break;
+            }
+            let len = cmp::min(CHUNK_LEN, self.shared.keyspace_len - start) as usize;
+
+            nth_password(&search.charset, start, &mut passwords[0]);
+            for i in 1..len {
+                let (prev, next) = passwords.split_at_mut(i);
+                next[0].copy_from_slice(&prev[i-1]);
+                next_password(&search.charset, &mut next[0]);
+            }
+
+            G::hash_many(&passwords[..len], &mut hashes);
+
+            for (password, hash) in passwords.iter().zip(hashes.drain(..)) {
+                let tripcode: Tripcode = hash.encode_inline();
+                if search.patterns.iter().any(|p| p.matches(&tripcode)) {
+                    let hit = Hit { password: password.clone(), tripcode: tripcode.to_string() };
+                    if self.sender.send(hit).is_err() {
+                        return;
+                    }
+                }
+            }
+
+            self.shared.tested.fetch_add(len as u64, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Advances `password` to the next password of the keyspace.
+fn next_password(charset: &[u8], password: &mut [u8]) {
+    for c in password.iter_mut().rev() {
+        let i = charset.iter().position(|&d| d == *c).unwrap();
+        if i + 1 < charset.len() {
+            *c = charset[i + 1];
+            return;
+        }
+        *c = charset[0];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::borrow::ToOwned;
+    use std::time::Instant;
+    use *;
+
+    #[test]
+    fn enumeration() {
+        let mut password = [0u8; 3];
+        let mut next = [0u8; 3];
+        nth_password(b"abc", 0, &mut next);
+        for n in 0..27 {
+            nth_password(b"abc", n, &mut password);
+            assert_eq!(password, next);
+            next_password(b"abc", &mut next);
+        }
+        assert_eq!(b"ccc", &password);
+        assert_eq!(b"aaa", &next);
+    }
+
+    #[test]
+    fn exhaustive() {
+        // Every matching password in the keyspace must be found exactly once.
+        let patterns = vec![Pattern::Prefix("a".to_owned()), Pattern::Contains("xyz".to_owned())];
+        let search = Search::new(patterns.clone()).charset(b"0123456789").password_len(4).threads(3);
+        assert_eq!(Some(10000), search.keyspace_len());
+
+        let mut hits: Vec<_> = search.start::<Mona>().iter().collect();
+        hits.sort_by(|a, b| a.password.cmp(&b.password));
+
+        let mut expected = Vec::new();
+        let mut password = [b'0'; 4];
+        for _ in 0..10000 {
+            let tripcode = Mona::generate(password);
+            if patterns.iter().any(|p| p.matches(&tripcode)) {
+                expected.push(Hit { password: password.to_vec(), tripcode });
+            }
+            next_password(b"0123456789", &mut password);
+        }
+
+        assert!(!expected.is_empty());
+        assert_eq!(expected, hits);
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn regex() {
+        let pattern = Pattern::Regex(Regex::new("^ab|[.]{2}").unwrap());
+        assert!(pattern.matches("abcdefghij"));
+        assert!(pattern.matches("xyz..ghij."));
+        assert!(!pattern.matches("xabcd.fgh."));
+        assert_eq!(pattern, Pattern::Regex(Regex::new("^ab|[.]{2}").unwrap()));
+        assert!(Regex::new("(").is_err());
+    }
+
+    #[test]
+    fn stop() {
+        let search = Search::new(vec![Pattern::Contains(String::new())]).threads(2).start::<Fourchan>();
+        assert!(search.next_hit().is_some());
+        search.stop();
+    }
+
+    #[test]
+    fn stop_with_full_queue() {
+        let search = Search::new(vec![Pattern::Contains(String::new())]).threads(2).start::<Fourchan>();
+        // Every password is a hit, so the workers soon fill the queue and block on it, after
+        // which the count of tested passwords stops changing.
+        let deadline = Instant::now() + Duration::from_secs(60);
+        let (mut tested, mut unchanged) = (search.tested(), 0);
+        while unchanged < 10 {
+            assert!(Instant::now() < deadline, "the workers did not block on the full queue");
+            thread::sleep(Duration::from_millis(10));
+            let t = search.tested();
+            unchanged = if t == tested { unchanged + 1 } else { 0 };
+            tested = t;
+        }
+        // A chunk is only counted once all of its hits have been queued.
+        assert!(tested <= CHUNK_LEN);
+        search.stop();
+    }
+
+    #[test]
+    fn keyspace_len() {
+        assert_eq!(Some(64), Search::new(vec![]).password_len(1).keyspace_len());
+        assert_eq!(Some(1), Search::new(vec![]).password_len(0).keyspace_len());
+        assert_eq!(None, Search::new(vec![]).password_len(11).keyspace_len());
+        assert_eq!(Some(3), Search::new(vec![]).charset(b"abcabc").password_len(1).keyspace_len());
+    }
+}
@@ -0,0 +1,466 @@
+// Copyright 2016 Huton. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Selection of the SHA-1 implementation that the 12 and 15-character tripcodes are hashed with.
+//!
+//! `hash_many()` of `Mona12`, `Sc15` and their variants digests the passwords that fit in
+//! a single SHA-1 block several at a time on one of several implementations, the `Backend`s:
+//! the portable one, which compresses 4 blocks side by side in the hope that the compiler
+//! vectorizes them, and ones that compress 4 or 8 blocks at once with SSE2 or AVX2 on x86. The
+//! fastest one that the CPU supports is selected on first use, unless the
+//! `TRIPCODE_SHA1_BACKEND` environment variable names another supported one (only with the
+//! `std` feature). `set_backend` overrides the selection.
+//!
+//! ```
+//! use tripcode::*;
+//! use tripcode::sha1::{self, Backend};
+//!
+//! let passwords = ["<12 bytes", "Tripcode+rs/"];
+//! let mut expected = Vec::new();
+//! Mona12::hash_many(&passwords, &mut expected);
+//! for &backend in Backend::ALL.iter().filter(|b| b.is_supported()) {
+//!     sha1::set_backend(backend);
+//!     assert_eq!(backend, sha1::backend());
+//!     let mut hashes = Vec::new();
+//!     Mona12::hash_many(&passwords, &mut hashes);
+//!     assert_eq!(expected, hashes);
+//! }
+//! ```
+
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+mod x86;
+
+const H: [u32; 5] = [0x6745_2301, 0xEFCD_AB89, 0x98BA_DCFE, 0x1032_5476, 0xC3D2_E1F0];
+const K: [u32; 4] = [0x5A82_7999, 0x6ED9_EBA1, 0x8F1B_BCDC, 0xCA62_C1D6];
+
+/// Message padded into a single block, as big-endian words.
+pub(crate) type Block = [u32; 16];
+
+/// Maximum length of messages that fit in a single block after padding.
+pub(crate) const MAX_BLOCK_MESSAGE_LEN: usize = 55;
+
+/// Number of blocks compressed side by side by the portable fallback of `digest_blocks`.
+const LANES: usize = 4;
+
+/// Incremental SHA-1 hasher, which accepts input in multiple parts.
+#[derive(Clone)]
+pub(crate) struct Sha1 {
+    state: [u32; 5],
+    /// Input that does not fill a block yet.
+    buf: [u8; 64],
+    buf_len: usize,
+    /// Length of the whole input in bytes.
+    len: u64,
+}
+
+impl Sha1 {
+    /// Creates a hasher with no input.
+    pub(crate) fn new() -> Self {
+        Sha1 { state: H, buf: [0; 64], buf_len: 0, len: 0 }
+    }
+
+    /// Appends `data` to the input.
+    pub(crate) fn input(&mut self, mut data: &[u8]) {
+        self.len = self.len.wrapping_add(data.len() as u64);
+
+        if self.buf_len > 0 {
+            let n = (64 - self.buf_len).min(data.len());
+            self.buf[self.buf_len..self.buf_len+n].copy_from_slice(&data[..n]);
+            self.buf_len += n;
+            data = &data[n..];
+            if self.buf_len < 64 {
+                return;
+            }
+            let block = self.buf;
+            compress(&mut self.state, &block);
+            self.buf_len = 0;
+        }
+
+        let mut blocks = data.chunks_exact(64);
+        for block in &mut blocks {
+            compress(&mut self.state, block);
+        }
+        let rest = blocks.remainder();
+        self.buf[..rest.len()].copy_from_slice(rest);
+        self.buf_len = rest.len();
+    }
+
+    /// Pads the input and returns the digest.
+    pub(crate) fn result(mut self) -> [u8; 20] {
+        let bits = self.len.wrapping_mul(8);
+
+        self.input(&[0x80]);
+        if self.buf_len > 56 {
+            self.input(&[0; 64][self.buf_len..]);
+        }
+        let pad = 56 - self.buf_len;
+        self.input(&[0; 56][..pad]);
+        self.input(&bits.to_be_bytes());
+        debug_assert_eq!(0, self.buf_len);
+
+        to_digest(&self.state)
+    }
+}
+
+/// Pads `message`, which must be at most `MAX_BLOCK_MESSAGE_LEN` bytes long, into a block.
+pub(crate) fn pad_block(message: &[u8]) -> Block {
+    debug_assert!(message.len() <= MAX_BLOCK_MESSAGE_LEN);
+
+    let mut bytes = [0u8; 64];
+    bytes[..message.len()].copy_from_slice(message);
+    bytes[message.len()] = 0x80;
+    bytes[56..].copy_from_slice(&(message.len() as u64 * 8).to_be_bytes());
+    to_words(&bytes)
+}
+
+/// Computes the digests of messages padded into blocks by `pad_block` and appends them to `dst`,
+/// with the selected backend.
+pub(crate) fn digest_blocks(blocks: &[Block], dst: &mut Vec<[u8; 20]>) {
+    digest_blocks_with_backend(backend(), blocks, dst);
+}
+
+/// Same as `digest_blocks` but with `backend`.
+///
+/// Blocks that do not fill the vectors of a SIMD backend are handled by the slower ones.
+///
+/// # Panics
+///
+/// Panics if the CPU does not support `backend`.
+pub(crate) fn digest_blocks_with_backend(backend: Backend, blocks: &[Block], dst: &mut Vec<[u8; 20]>) {
+    assert!(backend.is_supported(), "unsupported SHA-1 backend: {}", backend.name());
+
+    let mut rest = blocks;
+    for &b in Backend::ALL[..backend as usize + 1].iter().rev() {
+        if b.is_supported() {
+            rest = b.digest_batch(rest, dst);
+        }
+    }
+    debug_assert!(rest.is_empty());
+}
+
+// The portable batch routine, which compresses `LANES` blocks side by side.
+fn digest_blocks_portable(blocks: &[Block], dst: &mut Vec<[u8; 20]>) {
+    let mut chunks = blocks.chunks_exact(LANES);
+    for chunk in &mut chunks {
+        let mut lanes = [[0u32; 16]; LANES];
+        lanes.copy_from_slice(chunk);
+        dst.extend(compress_lanes(&lanes).iter().map(to_digest));
+    }
+    dst.extend(chunks.remainder().iter().map(|block| {
+        let mut state = H;
+        compress_words(&mut state, block);
+        to_digest(&state)
+    }));
+}
+
+/// An implementation of the batch SHA-1 of the 12 and 15-character tripcodes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Backend {
+    /// The portable implementation, which runs on any CPU.
+    Portable,
+    /// Implementation with SSE2, which compresses 4 blocks at once.
+    Sse2,
+    /// Implementation with AVX2, which compresses 8 blocks at once.
+    Avx2,
+}
+
+impl Backend {
+    /// All the backends, from the slowest to the fastest.
+    pub const ALL: [Backend; 3] = [Backend::Portable, Backend::Sse2, Backend::Avx2];
+
+    /// Returns the name of the backend, which is what `TRIPCODE_SHA1_BACKEND` takes.
+    pub fn name(self) -> &'static str {
+        match self {
+            Backend::Portable => "portable",
+            Backend::Sse2     => "sse2",
+            Backend::Avx2     => "avx2",
+        }
+    }
+
+    /// Returns the backend of the name returned by `name()`, ignoring ASCII case.
+    pub fn from_name(name: &str) -> Option<Backend> {
+        Backend::ALL.iter().cloned().find(|b| b.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Returns `true` if the CPU supports the backend.
+    ///
+    /// Without the `std` feature, only the CPU features enabled at compile time are detected.
+    pub fn is_supported(self) -> bool {
+        match self {
+            Backend::Portable => true,
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Backend::Sse2 => x86::has_sse2(),
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Backend::Avx2 => x86::has_avx2(),
+            #[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+            _ => false,
+        }
+    }
+
+    /// Returns the fastest backend that the CPU supports.
+    pub fn fastest() -> Backend {
+        *Backend::ALL.iter().rev().find(|b| b.is_supported()).unwrap()
+    }
+
+    // Digests as many of `blocks` as the backend handles at once, appends the digests to `dst`
+    // and returns the rest of the blocks. The backend must be supported.
+    fn digest_batch<'a>(self, blocks: &'a [Block], dst: &mut Vec<[u8; 20]>) -> &'a [Block] {
+        match self {
+            Backend::Portable => {
+                digest_blocks_portable(blocks, dst);
+                &[]
+            },
+            // Safe because the callers have checked that the CPU supports the backend.
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Backend::Sse2 => unsafe { x86::digest_blocks_sse2(blocks, dst) },
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Backend::Avx2 => unsafe { x86::digest_blocks_avx2(blocks, dst) },
+            #[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+            _ => blocks,
+        }
+    }
+}
+
+// One more than the index of the selected backend in `Backend::ALL`, or 0 until one is selected.
+static SELECTED: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns the backend that the batch SHA-1 runs on.
+///
+/// On first use, this selects the backend named by the `TRIPCODE_SHA1_BACKEND` environment
+/// variable if the CPU supports it, and the fastest supported one otherwise.
+pub fn backend() -> Backend {
+    match SELECTED.load(Ordering::Relaxed) {
+        0 => {
+            let backend = default_backend();
+            SELECTED.store(backend as usize + 1, Ordering::Relaxed);
+            backend
+        },
+        i => Backend::ALL[i - 1],
+    }
+}
+
+/// Makes the batch SHA-1 run on `backend`.
+///
+/// # Panics
+///
+/// Panics if the CPU does not support `backend`.
+pub fn set_backend(backend: Backend) {
+    assert!(backend.is_supported(), "unsupported SHA-1 backend: {}", backend.name());
+    SELECTED.store(backend as usize + 1, Ordering::Relaxed);
+}
+
+#[cfg(feature = "std")]
+fn default_backend() -> Backend {
+    std::env::var("TRIPCODE_SHA1_BACKEND").ok()
+        .and_then(|name| Backend::from_name(&name))
+        .filter(|b| b.is_supported())
+        .unwrap_or_else(Backend::fastest)
+}
+
+#[cfg(not(feature = "std"))]
+fn default_backend() -> Backend {
+    Backend::fastest()
+}
+
+fn to_words(block: &[u8]) -> Block {
+    let mut w = [0u32; 16];
+    for (w, b) in w.iter_mut().zip(block.chunks_exact(4)) {
+        *w = u32::from_be_bytes([b[0], b[1], b[2], b[3]]);
+    }
+    w
+}
+
+fn to_digest(state: &[u32; 5]) -> [u8; 20] {
+    let mut digest = [0u8; 20];
+    for (d, s) in digest.chunks_exact_mut(4).zip(state) {
+        d.copy_from_slice(&s.to_be_bytes());
+    }
+    digest
+}
+
+/// Processes a 64-byte block.
+fn compress(state: &mut [u32; 5], block: &[u8]) {
+    compress_words(state, &to_words(block));
+}
+
+fn compress_words(state: &mut [u32; 5], block: &Block) {
+    let mut w = [0u32; 80];
+    w[..16].copy_from_slice(block);
+    for t in 16..80 {
+        w[t] = (w[t-3] ^ w[t-8] ^ w[t-14] ^ w[t-16]).rotate_left(1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e] = *state;
+    for (t, &w) in w.iter().enumerate() {
+        let (f, k) = match t {
+            0..=19  => ((b & c) | (!b & d),          K[0]),
+            20..=39 => (b ^ c ^ d,                   K[1]),
+            40..=59 => ((b & c) | (b & d) | (c & d), K[2]),
+            _       => (b ^ c ^ d,                   K[3]),
+        };
+        let temp = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(w);
+        e = d;
+        d = c;
+        c = b.rotate_left(30);
+        b = a;
+        a = temp;
+    }
+
+    for (s, v) in state.iter_mut().zip(&[a, b, c, d, e]) {
+        *s = s.wrapping_add(*v);
+    }
+}
+
+/// Same as `compress_words` on the initial state, but for `LANES` blocks at once.
+///
+/// The lanes are independent of each other, so the compiler can vectorize them.
+fn compress_lanes(blocks: &[Block; LANES]) -> [[u32; 5]; LANES] {
+    let mut w = [[0u32; LANES]; 80];
+    for (t, w) in w.iter_mut().take(16).enumerate() {
+        for j in 0..LANES {
+            w[j] = blocks[j][t];
+        }
+    }
+    for t in 16..80 {
+        let (prev, next) = w.split_at_mut(t);
+        for (j, w) in next[0].iter_mut().enumerate() {
+            *w = (prev[t-3][j] ^ prev[t-8][j] ^ prev[t-14][j] ^ prev[t-16][j]).rotate_left(1);
+        }
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e] = [[H[0]; LANES], [H[1]; LANES], [H[2]; LANES], [H[3]; LANES], [H[4]; LANES]];
+    for (t, w) in w.iter().enumerate() {
+        for j in 0..LANES {
+            let (f, k) = match t {
+                0..=19  => ((b[j] & c[j]) | (!b[j] & d[j]),                K[0]),
+                20..=39 => (b[j] ^ c[j] ^ d[j],                            K[1]),
+                40..=59 => ((b[j] & c[j]) | (b[j] & d[j]) | (c[j] & d[j]), K[2]),
+                _       => (b[j] ^ c[j] ^ d[j],                            K[3]),
+            };
+            let temp = a[j].rotate_left(5).wrapping_add(f).wrapping_add(e[j]).wrapping_add(k).wrapping_add(w[j]);
+            e[j] = d[j];
+            d[j] = c[j];
+            c[j] = b[j].rotate_left(30);
+            b[j] = a[j];
+            a[j] = temp;
+        }
+    }
+
+    let mut ret = [H; LANES];
+    for (j, s) in ret.iter_mut().enumerate() {
+        for (s, v) in s.iter_mut().zip(&[a[j], b[j], c[j], d[j], e[j]]) {
+            *s = s.wrapping_add(*v);
+        }
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    fn hex(digest: [u8; 20]) -> [u8; 40] {
+        let mut ret = [0u8; 40];
+        for (i, &b) in digest.iter().enumerate() {
+            ret[2*i] = b"0123456789abcdef"[(b >> 4) as usize];
+            ret[2*i+1] = b"0123456789abcdef"[(b & 0xF) as usize];
+        }
+        ret
+    }
+
+    fn sha1(data: &[u8]) -> [u8; 40] {
+        let mut sha1 = Sha1::new();
+        sha1.input(data);
+        hex(sha1.result())
+    }
+
+    // The examples of FIPS 180-4 and the test vectors of RFC 3174.
+    #[test]
+    fn vectors() {
+        assert_eq!(b"da39a3ee5e6b4b0d3255bfef95601890afd80709", &sha1(b"")[..]);
+        assert_eq!(b"a9993e364706816aba3e25717850c26c9cd0d89d", &sha1(b"abc")[..]);
+        assert_eq!(b"84983e441c3bd26ebaae4aa1f95129e5e54670f1",
+            &sha1(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq")[..]);
+        assert_eq!(b"dea356a2cddd90c7a7ecedc5ebb563934f460452",
+            &sha1(&b"01234567".repeat(80))[..]);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn million_a() {
+        assert_eq!(b"34aa973cd4c4daa4f61eeb2bdbad27316534016f", &sha1(&b"a".repeat(1_000_000))[..]);
+    }
+
+    #[test]
+    fn multipart() {
+        let data: Vec<u8> = (0..200u8).collect();
+        let expected = sha1(&data);
+
+        for &split in &[0, 1, 55, 56, 63, 64, 65, 128, 199, 200] {
+            let mut sha1 = Sha1::new();
+            sha1.input(&data[..split]);
+            sha1.input(&data[split..]);
+            assert_eq!(&expected[..], &hex(sha1.result())[..], "split at {}", split);
+        }
+
+        let mut sha1 = Sha1::new();
+        for b in &data {
+            sha1.input(&[*b]);
+        }
+        assert_eq!(&expected[..], &hex(sha1.result())[..]);
+    }
+
+    #[test]
+    fn blocks() {
+        let messages: Vec<Vec<u8>> = (0..=MAX_BLOCK_MESSAGE_LEN).map(|n| (0..n as u8).collect()).collect();
+        let expected: Vec<_> = messages.iter().map(|m| {
+            let mut sha1 = Sha1::new();
+            sha1.input(m);
+            sha1.result()
+        }).collect();
+        let blocks: Vec<_> = messages.iter().map(|m| pad_block(m)).collect();
+
+        // Covers every combination of the AVX2, SSE2 and scalar paths.
+        for &backend in Backend::ALL.iter().filter(|b| b.is_supported()) {
+            for len in 0..20 {
+                let mut digests = Vec::new();
+                digest_blocks_with_backend(backend, &blocks[..len], &mut digests);
+                assert!(expected[..len] == digests[..], "{:?} with {} blocks", backend, len);
+            }
+            let mut digests = Vec::new();
+            digest_blocks_with_backend(backend, &blocks, &mut digests);
+            assert!(expected == digests, "{:?}", backend);
+        }
+        let mut digests = Vec::new();
+        digest_blocks(&blocks, &mut digests);
+        assert_eq!(expected, digests);
+
+        for (chunk, expected) in blocks.chunks_exact(LANES).zip(expected.chunks_exact(LANES)) {
+            let mut lanes = [[0u32; 16]; LANES];
+            lanes.copy_from_slice(chunk);
+            let digests: Vec<_> = compress_lanes(&lanes).iter().map(to_digest).collect();
+            assert_eq!(expected, &digests[..]);
+        }
+    }
+
+    #[test]
+    fn backend_names() {
+        for &backend in &Backend::ALL {
+            assert_eq!(Some(backend), Backend::from_name(backend.name()));
+        }
+        assert_eq!(Some(Backend::Avx2), Backend::from_name("AVX2"));
+        assert_eq!(None, Backend::from_name("avx512"));
+        assert!(Backend::fastest().is_supported());
+        assert!(backend().is_supported());
+    }
+}
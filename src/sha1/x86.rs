@@ -0,0 +1,133 @@
+// Copyright 2016 Huton. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! SSE2 and AVX2 backends of `digest_blocks`, which compress 4 and 8 blocks at once.
+
+#[cfg(target_arch = "x86")]
+use core::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64::*;
+use alloc::vec::Vec;
+use super::{Block, H, K};
+
+// Digests as many of `blocks` as fill `$lanes` lanes with `$digest`, appends the digests to
+// `dst` and returns the rest of the blocks.
+macro_rules! digest_blocks_fn {
+    ($name:ident, $digest:ident, $lanes:expr) => {
+        /// # Safety
+        ///
+        /// The CPU must support the instruction set of the backend.
+        pub unsafe fn $name<'a>(blocks: &'a [Block], dst: &mut Vec<[u8; 20]>) -> &'a [Block] {
+            let mut chunks = blocks.chunks_exact($lanes);
+            for chunk in &mut chunks {
+                $digest(chunk, dst);
+            }
+            chunks.remainder()
+        }
+    };
+}
+
+digest_blocks_fn!(digest_blocks_sse2, digest4_sse2, 4);
+digest_blocks_fn!(digest_blocks_avx2, digest8_avx2, 8);
+
+#[cfg(feature = "std")]
+pub fn has_avx2() -> bool {
+    is_x86_feature_detected!("avx2")
+}
+
+#[cfg(feature = "std")]
+pub fn has_sse2() -> bool {
+    is_x86_feature_detected!("sse2")
+}
+
+// Without `std`, the CPU features cannot be detected at runtime, so only the ones enabled
+// at compile time are used.
+
+#[cfg(not(feature = "std"))]
+pub fn has_avx2() -> bool {
+    cfg!(target_feature = "avx2")
+}
+
+#[cfg(not(feature = "std"))]
+pub fn has_sse2() -> bool {
+    cfg!(target_feature = "sse2")
+}
+
+// Rotates each 32-bit lane of `$x` left by `$n` bits.
+macro_rules! rotl {
+    ($x:expr, $n:expr, $or:ident, $sll:ident, $srl:ident) => {{
+        let x = $x;
+        $or($sll(x, _mm_cvtsi32_si128($n)), $srl(x, _mm_cvtsi32_si128(32 - $n)))
+    }};
+}
+
+// Digests `$lanes` blocks at once, storing the words of the blocks in the lanes of vectors of
+// type `$v`, which the intrinsics that follow operate on.
+macro_rules! digest_lanes {
+    ($blocks:expr, $dst:expr, $lanes:expr, $v:ty,
+        $set1:ident, $loadu:ident, $storeu:ident,
+        $add:ident, $xor:ident, $and:ident, $or:ident, $andnot:ident, $sll:ident, $srl:ident
+    ) => {{
+        let blocks: &[Block] = $blocks;
+
+        let mut w = [$set1(0); 80];
+        for (t, w) in w.iter_mut().take(16).enumerate() {
+            let mut words = [0u32; $lanes];
+            for (word, block) in words.iter_mut().zip(blocks) {
+                *word = block[t];
+            }
+            *w = $loadu(words.as_ptr() as *const $v);
+        }
+        for t in 16..80 {
+            w[t] = rotl!($xor($xor(w[t-3], w[t-8]), $xor(w[t-14], w[t-16])), 1, $or, $sll, $srl);
+        }
+
+        let h = [$set1(H[0] as i32), $set1(H[1] as i32), $set1(H[2] as i32), $set1(H[3] as i32),
+                 $set1(H[4] as i32)];
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (t, &w) in w.iter().enumerate() {
+            let (f, k) = match t {
+                0..=19  => ($or($and(b, c), $andnot(b, d)),                  K[0]),
+                20..=39 => ($xor($xor(b, c), d),                             K[1]),
+                40..=59 => ($or($or($and(b, c), $and(b, d)), $and(c, d)), K[2]),
+                _       => ($xor($xor(b, c), d),                             K[3]),
+            };
+            let temp = $add($add(rotl!(a, 5, $or, $sll, $srl), f), $add($add(e, $set1(k as i32)), w));
+            e = d;
+            d = c;
+            c = rotl!(b, 30, $or, $sll, $srl);
+            b = a;
+            a = temp;
+        }
+
+        let mut state = [[0u32; $lanes]; 5];
+        for ((s, h), v) in state.iter_mut().zip(&h).zip(&[a, b, c, d, e]) {
+            $storeu(s.as_mut_ptr() as *mut $v, $add(*h, *v));
+        }
+        for j in 0..$lanes {
+            $dst.push(super::to_digest(&[state[0][j], state[1][j], state[2][j], state[3][j], state[4][j]]));
+        }
+    }};
+}
+
+#[target_feature(enable = "sse2")]
+unsafe fn digest4_sse2(blocks: &[Block], dst: &mut Vec<[u8; 20]>) {
+    digest_lanes!(blocks, dst, 4, __m128i,
+        _mm_set1_epi32, _mm_loadu_si128, _mm_storeu_si128,
+        _mm_add_epi32, _mm_xor_si128, _mm_and_si128, _mm_or_si128, _mm_andnot_si128,
+        _mm_sll_epi32, _mm_srl_epi32);
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn digest8_avx2(blocks: &[Block], dst: &mut Vec<[u8; 20]>) {
+    digest_lanes!(blocks, dst, 8, __m256i,
+        _mm256_set1_epi32, _mm256_loadu_si256, _mm256_storeu_si256,
+        _mm256_add_epi32, _mm256_xor_si256, _mm256_and_si256, _mm256_or_si256, _mm256_andnot_si256,
+        _mm256_sll_epi32, _mm256_srl_epi32);
+}
@@ -7,7 +7,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use std::{cmp, ptr};
+use core::cmp;
 
 /// Helper macro for generating HTML escaping macros.
 macro_rules! escape {
@@ -41,11 +41,10 @@ macro_rules! mona_escape {
 /// Reinterprets the byte array as 64-bit big-endian unsigned integral value and returns it.
 /// Any out-of-bounds byte will be treated as if being zero.
 pub fn pack_u64_be(bytes: &[u8]) -> u64 {
-    let mut ret = 0u64;
-    unsafe {
-        ptr::copy_nonoverlapping(bytes.as_ptr(), &mut ret as *mut _ as *mut u8, cmp::min(bytes.len(), 8));
-    }
-    u64::from_be(ret)
+    let mut buf = [0u8; 8];
+    let len = cmp::min(bytes.len(), 8);
+    buf[..len].copy_from_slice(&bytes[..len]);
+    u64::from_be_bytes(buf)
 }
 
 /// Converts password for used by the DES cipher.
@@ -158,6 +157,19 @@ pub fn decode_salt_strict(salt1: u8, salt2: u8) -> Option<u32> {
     Some((try_dec!(salt1) << 26) | (try_dec!(salt2) << 18))
 }
 
+/// Writes the `n`th password of the keyspace to `dst`.
+///
+/// Passwords are enumerated like numerals in base `charset.len()`, whose last character is
+/// the least significant digit.
+#[cfg(feature = "std")]
+pub fn nth_password(charset: &[u8], mut n: u64, dst: &mut [u8]) {
+    let base = charset.len() as u64;
+    for c in dst.iter_mut().rev() {
+        *c = charset[(n % base) as usize];
+        n /= base;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,49 @@
+// Copyright 2016 Huton. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+WebAssembly build of the `tripcode` crate.
+
+The module exports the functions of `tripcode-ffi` (`tripcode_generate()` etc.) along with
+`tripcode_alloc()` and `tripcode_free()`, which let JavaScript pass passwords to them through
+the linear memory, and `tripcode_max_len()`, which returns `TRIPCODE_MAX_LEN`. `tripcode.mjs`
+wraps these exports in a JavaScript API.
+*/
+
+pub extern crate tripcode_ffi;
+
+use std::mem;
+
+/// Allocates `len` bytes in the linear memory and returns a pointer to them.
+///
+/// The memory must be released with `tripcode_free()`.
+#[no_mangle]
+pub extern "C" fn tripcode_alloc(len: usize) -> *mut u8 {
+    let mut buf = Vec::<u8>::with_capacity(len);
+    let ptr = buf.as_mut_ptr();
+    mem::forget(buf);
+    ptr
+}
+
+/// Releases memory allocated by `tripcode_alloc()`.
+///
+/// # Safety
+///
+/// `ptr` must have been returned by `tripcode_alloc(len)` with the same `len`, and must not
+/// have been released already. The memory must not be used after this call.
+#[no_mangle]
+pub unsafe extern "C" fn tripcode_free(ptr: *mut u8, len: usize) {
+    drop(Vec::from_raw_parts(ptr, 0, len));
+}
+
+/// Returns `TRIPCODE_MAX_LEN`, which JavaScript cannot read from the module otherwise.
+#[no_mangle]
+pub extern "C" fn tripcode_max_len() -> usize {
+    tripcode_ffi::TRIPCODE_MAX_LEN
+}
@@ -0,0 +1,68 @@
+// Runs the WebAssembly build under Node.js. Build it first with `npm run build`,
+// or set `TRIPCODE_WASM` to the path of the module.
+
+import assert from 'node:assert/strict';
+import { readFile } from 'node:fs/promises';
+import { test } from 'node:test';
+import { init } from '../tripcode.mjs';
+
+const path = process.env.TRIPCODE_WASM
+    ?? new URL('../../target/wasm32-unknown-unknown/release/tripcode_wasm.wasm', import.meta.url);
+const tripcode = await init(readFile(path));
+
+test('generate', () => {
+    assert.equal(tripcode.generate('fourchan', 'password'), 'ozOtJW9BFA');
+    assert.equal(tripcode.generate('fourchan', ''), 'jPpg5.obl6');
+    assert.equal(tripcode.generate('fourchan', '&'), 'MhCJJ7GVT.');
+    assert.equal(tripcode.generate('fourchan-nonescaping', '&'), '2r2Ga7GHRc');
+    assert.equal(tripcode.generate('mona10', 'password longer than 12 bytes'), 'ozOtJW9BFA');
+    assert.equal(tripcode.generate('mona-raw', '#0123456789abcdef./'), 'IP9Lda5FPc');
+    assert.equal(tripcode.generate('mona', 'password'), 'ozOtJW9BFA');
+    assert.equal(tripcode.generate('mona12', '<12 bytes'), '/9L00Vb1PBcb');
+    assert.equal(tripcode.generate('sc15', '$0123456789a'), 'h3Si!7m4Qie8e.u');
+});
+
+test('generate from bytes', () => {
+    // "トリップ" in Shift-JIS.
+    const sjis = new Uint8Array([0x83, 0x67, 0x83, 0x8A, 0x83, 0x62, 0x83, 0x76]);
+    assert.equal(tripcode.generate('fourchan', sjis), 'XSSH/ryx32');
+    assert.equal(tripcode.generate('fourchan', new TextEncoder().encode('password')), 'ozOtJW9BFA');
+});
+
+test('invalid passwords', () => {
+    assert.equal(tripcode.generate('mona-raw', '#abcdefghijklmnop'), null);
+    assert.equal(tripcode.generate('mona-raw', 'password'), null);
+});
+
+test('errors', () => {
+    assert.throws(() => tripcode.generate('md5', 'password'), TypeError);
+    assert.throws(() => tripcode.generate('fourchan', 42), TypeError);
+});
+
+test('generateDes', () => {
+    assert.equal(tripcode.generateDes('password', 'as'), 'ozOtJW9BFA');
+    assert.equal(tripcode.generateDes('', [0x48, 0x2E]), 'jPpg5.obl6');
+});
+
+test('decode', () => {
+    const value = tripcode.decode('fourchan', '..........');
+    assert.equal(value.format, 'ten');
+    assert.deepEqual(value.bytes, new Uint8Array(8));
+    assert.equal(tripcode.decode('fourchan', 'hocho.🔪'), null);
+});
+
+test('many calls', () => {
+    // The linear memory must not leak or be clobbered between calls.
+    for (let i = 0; i < 10000; i++) {
+        assert.equal(tripcode.generate('fourchan', 'x'.repeat(i % 100) + 'password').length, 10);
+    }
+    assert.equal(tripcode.generate('fourchan', 'password'), 'ozOtJW9BFA');
+});
+
+test('dispose', async () => {
+    const other = await init(readFile(path));
+    assert.equal(other.generate('fourchan', 'password'), 'ozOtJW9BFA');
+    other.dispose();
+    other.dispose();
+    assert.throws(() => other.generate('fourchan', 'password'), /disposed/);
+});
@@ -0,0 +1,186 @@
+// Copyright 2016 Huton. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/**
+ * JavaScript API for the WebAssembly build of the tripcode library.
+ *
+ *     import { init } from './tripcode.mjs';
+ *
+ *     const tripcode = await init(fetch('tripcode_wasm.wasm'));
+ *     tripcode.generate('fourchan', 'password'); // => 'ozOtJW9BFA'
+ *
+ * Passwords are given either as strings, which are encoded in UTF-8, or as `Uint8Array`s,
+ * which are passed as is (e.g. Shift-JIS-encoded passwords).
+ */
+
+/** Generator kinds, mapped to the `TRIPCODE_*` constants of `tripcode.h`. */
+export const KINDS = Object.freeze({
+    'fourchan': 0,
+    'fourchan-nonescaping': 1,
+    'mona': 2,
+    'mona-nonescaping': 3,
+    'mona10': 4,
+    'mona12': 5,
+    'mona12-nonescaping': 6,
+    'mona-raw': 7,
+    'sc': 8,
+    'sc-sjis': 9,
+    'sc15': 10,
+    'sc-katakana': 11,
+});
+
+/** Formats of decoded hash values, indexed by the `TRIPCODE_FORMAT_*` constants. */
+const FORMATS = ['ten', 'twelve', 'fifteen', 'katakana', 'error'];
+
+const ERR_INVALID_PASSWORD = -4;
+const ERR_INVALID_TRIPCODE = -5;
+// sizeof(tripcode_hash_value)
+const HASH_VALUE_SIZE = 16;
+
+/** Error thrown when the library returns an unexpected error code. */
+export class TripcodeError extends Error {
+    constructor(code, message) {
+        super(message);
+        this.name = 'TripcodeError';
+        this.code = code;
+    }
+}
+
+/**
+ * Instantiates the WebAssembly module.
+ *
+ * `source` is a `Response` (or a promise of it), a `BufferSource` holding the module,
+ * or a compiled `WebAssembly.Module`.
+ */
+export async function init(source) {
+    source = await source;
+
+    let instance;
+    if (source instanceof WebAssembly.Module) {
+        instance = await WebAssembly.instantiate(source, {});
+    } else if (typeof Response !== 'undefined' && source instanceof Response) {
+        instance = (await WebAssembly.instantiate(await source.arrayBuffer(), {})).instance;
+    } else {
+        instance = (await WebAssembly.instantiate(source, {})).instance;
+    }
+
+    return new Tripcode(instance.exports);
+}
+
+/** Tripcode generator backed by an instance of the WebAssembly module. */
+export class Tripcode {
+    constructor(exports) {
+        this._exports = exports;
+        this._encoder = new TextEncoder();
+        this._decoder = new TextDecoder();
+        // Reused for every result, since no result is longer than this.
+        this._outLen = Math.max(exports.tripcode_max_len() + 1, HASH_VALUE_SIZE);
+        this._out = exports.tripcode_alloc(this._outLen);
+    }
+
+    /**
+     * Generates a tripcode with the generator named `kind` (one of the keys of `KINDS`).
+     *
+     * Returns `null` when the generator rejects the password (`'mona-raw'` only).
+     */
+    generate(kind, password) {
+        const ret = this._withBytes(password, (ptr, len) =>
+            this._exports.tripcode_generate(kindOf(kind), ptr, len, this._out, this._outLen));
+        if (ret === ERR_INVALID_PASSWORD) {
+            return null;
+        }
+        return this._readString(ret);
+    }
+
+    /**
+     * Generates a DES-based tripcode with a pair of custom salt characters, given as
+     * a string of two ASCII characters or as an array of two bytes.
+     */
+    generateDes(password, salt) {
+        const [salt1, salt2] = typeof salt === 'string'
+            ? [salt.charCodeAt(0), salt.charCodeAt(1)]
+            : salt;
+        const ret = this._withBytes(password, (ptr, len) =>
+            this._exports.tripcode_generate_des(ptr, len, salt1 & 0xFF, salt2 & 0xFF,
+                this._out, this._outLen));
+        return this._readString(ret);
+    }
+
+    /**
+     * Decodes a tripcode into the hash value it represents.
+     *
+     * Returns an object with the `format` (`'ten'`, `'twelve'`, `'fifteen'`, `'katakana'`
+     * or `'error'`) and the big-endian `bytes` of the hash value, or `null` when the tripcode
+     * is invalid for the generator.
+     */
+    decode(kind, tripcode) {
+        const ret = this._withBytes(tripcode, (ptr, len) =>
+            this._exports.tripcode_decode(kindOf(kind), ptr, len, this._out));
+        if (ret === ERR_INVALID_TRIPCODE) {
+            return null;
+        }
+        check(this._exports, ret);
+
+        const view = new DataView(this._exports.memory.buffer, this._out, HASH_VALUE_SIZE);
+        return {
+            format: FORMATS[view.getUint32(0, true)],
+            bytes: new Uint8Array(this._exports.memory.buffer, this._out + 4, ret).slice(),
+        };
+    }
+
+    /**
+     * Releases the buffer that the generator keeps in the linear memory. The generator
+     * cannot be used afterwards. Calling `dispose()` again does nothing.
+     */
+    dispose() {
+        if (this._out !== null) {
+            this._exports.tripcode_free(this._out, this._outLen);
+            this._out = null;
+        }
+    }
+
+    _withBytes(input, f) {
+        if (this._out === null) {
+            throw new Error('the generator has been disposed');
+        }
+        const bytes = typeof input === 'string' ? this._encoder.encode(input) : input;
+        if (!(bytes instanceof Uint8Array)) {
+            throw new TypeError('expected a string or a Uint8Array');
+        }
+
+        const ptr = this._exports.tripcode_alloc(bytes.length);
+        try {
+            new Uint8Array(this._exports.memory.buffer, ptr, bytes.length).set(bytes);
+            return f(ptr, bytes.length);
+        } finally {
+            this._exports.tripcode_free(ptr, bytes.length);
+        }
+    }
+
+    _readString(ret) {
+        check(this._exports, ret);
+        return this._decoder.decode(new Uint8Array(this._exports.memory.buffer, this._out, ret));
+    }
+}
+
+function kindOf(kind) {
+    if (!Object.prototype.hasOwnProperty.call(KINDS, kind)) {
+        throw new TypeError(`unknown tripcode kind: ${kind}`);
+    }
+    return KINDS[kind];
+}
+
+function check(exports, ret) {
+    if (ret < 0) {
+        const ptr = exports.tripcode_strerror(ret);
+        const memory = new Uint8Array(exports.memory.buffer);
+        const end = memory.indexOf(0, ptr);
+        throw new TripcodeError(ret, new TextDecoder().decode(memory.subarray(ptr, end)));
+    }
+}